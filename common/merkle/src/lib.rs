@@ -40,6 +40,19 @@ impl Merkle {
                     .collect()
             })
     }
+
+    /// Verify that the leaf is in the tree of the root. The proof nodes are
+    /// the siblings from the leaf up to the root.
+    pub fn verify_proof(root: &Hash, leaf: Hash, proof: &[ProofNode]) -> bool {
+        let computed = proof.iter().fold(leaf, |hash, node| {
+            if node.is_right {
+                merge(&hash, &node.hash)
+            } else {
+                merge(&node.hash, &hash)
+            }
+        });
+        &computed == root
+    }
 }
 
 fn merge(left: &Hash, right: &Hash) -> Hash {
@@ -0,0 +1,115 @@
+//! Verify the proofs aggregated from the votes of validators. Both the nodes
+//! and the light clients check epochs in the same way, so this crate only
+//! depends on the types of overlord.
+
+use std::convert::TryFrom;
+use std::error::Error;
+
+use bytes::Bytes;
+use derive_more::Display;
+use overlord::types::{Vote, VoteType};
+
+use common_crypto::{BLS12381PublicKey, Crypto, PublicKey, BLS12381};
+use protocol::fixed_codec::ProtocolFixedCodec;
+use protocol::types::{Epoch, Hash, Proof, Validator};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+/// Check that the proof proves the epoch. The proof must be signed by the
+/// precommit votes of more than 2/3 vote weight of the epoch validators.
+pub fn check_proof(epoch: &Epoch, proof: &Proof, validators: &[Validator]) -> ProtocolResult<()> {
+    let epoch_id = epoch.header.epoch_id;
+    let invalid = |reason: &str| ProofError::InvalidProof {
+        epoch_id,
+        reason: reason.to_owned(),
+    };
+
+    if proof.epoch_id != epoch_id {
+        return Err(invalid("epoch id mismatch").into());
+    }
+    if proof.epoch_hash != Hash::digest(epoch.encode_fixed()?) {
+        return Err(invalid("epoch hash mismatch").into());
+    }
+
+    let vote = Vote {
+        epoch_id:   proof.epoch_id,
+        round:      proof.round,
+        vote_type:  VoteType::Precommit,
+        epoch_hash: proof.epoch_hash.as_bytes(),
+    };
+    verify_aggregated_vote(&vote, &proof.signature, &proof.bitmap, validators)
+        .map_err(|reason| invalid(reason).into())
+}
+
+/// Verify that the signature is aggregated from the votes of more than 2/3
+/// vote weight of the validators. The aggregated signature is verified by the
+/// aggregated public key of the voters in the bitmap.
+pub fn verify_aggregated_vote(
+    vote: &Vote,
+    signature: &Bytes,
+    bitmap: &Bytes,
+    validators: &[Validator],
+) -> Result<(), &'static str> {
+    let voters = extract_voters(validators, bitmap);
+    if count_voters(bitmap) != voters.len() {
+        return Err("bitmap out of the validator list");
+    }
+    if !reach_quorum(validators, &voters) {
+        return Err("not enough vote weight");
+    }
+
+    let mut pub_keys = Vec::with_capacity(voters.len());
+    for voter in voters.iter() {
+        let pub_key = BLS12381PublicKey::try_from(voter.bls_pub_key.as_ref())
+            .map_err(|_| "invalid BLS public key")?;
+        pub_keys.push(pub_key);
+    }
+    let aggregated_key = BLS12381PublicKey::aggregate(pub_keys);
+
+    let hash = Hash::digest(Bytes::from(rlp::encode(vote))).as_bytes();
+    BLS12381::verify_signature(&hash, signature, &aggregated_key.to_bytes())
+        .map_err(|_| "aggregated signature mismatch")
+}
+
+/// Extract the voters from the bitmap of an aggregated signature. The bitmap is
+/// indexed by the authority list, which is sorted in the same way as overlord.
+pub fn extract_voters(validators: &[Validator], bitmap: &Bytes) -> Vec<Validator> {
+    let mut authority = validators.to_vec();
+    authority.sort_by_key(|v| (v.address.as_bytes(), v.propose_weight, v.vote_weight));
+
+    authority
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| {
+            bitmap
+                .get(index / 8)
+                .map_or(false, |byte| byte & (0x80 >> (index % 8)) != 0)
+        })
+        .map(|(_, v)| v)
+        .collect()
+}
+
+fn count_voters(bitmap: &Bytes) -> usize {
+    bitmap.iter().map(|byte| byte.count_ones() as usize).sum()
+}
+
+// A quorum is more than 2/3 of the total vote weight.
+fn reach_quorum(validators: &[Validator], voters: &[Validator]) -> bool {
+    let total: u64 = validators.iter().map(|v| u64::from(v.vote_weight)).sum();
+    let voted: u64 = voters.iter().map(|v| u64::from(v.vote_weight)).sum();
+    voted * 3 > total * 2
+}
+
+#[derive(Debug, Display)]
+pub enum ProofError {
+    /// The proof does not prove the epoch.
+    #[display(fmt = "Invalid proof of {} epoch: {}", epoch_id, reason)]
+    InvalidProof { epoch_id: u64, reason: String },
+}
+
+impl Error for ProofError {}
+
+impl From<ProofError> for ProtocolError {
+    fn from(err: ProofError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Consensus, Box::new(err))
+    }
+}
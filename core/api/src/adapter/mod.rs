@@ -4,24 +4,39 @@ use std::sync::Arc;
 use async_trait::async_trait;
 
 use protocol::traits::executor::{ExecutorFactory, TrieDB};
-use protocol::traits::{APIAdapter, Context, MemPool, Storage};
-use protocol::types::{Address, AssetID, Balance, Epoch, Hash, Receipt, SignedTransaction};
+use protocol::traits::{
+    APIAdapter, ConsensusMonitor, ConsensusStatus, Context, MemPool, MemPoolStats, Storage,
+    TxStatus,
+};
+use protocol::types::{
+    Address, AssetID, Balance, Epoch, Evidence, Hash, Proof, Receipt, SignedTransaction,
+    TransactionTrace, UserAddress,
+};
 use protocol::ProtocolResult;
 
-pub struct DefaultAPIAdapter<EF, M, S, DB> {
-    mempool: Arc<M>,
-    storage: Arc<S>,
-    trie_db: Arc<DB>,
+pub struct DefaultAPIAdapter<EF, M, S, DB, C> {
+    mempool:   Arc<M>,
+    storage:   Arc<S>,
+    trie_db:   Arc<DB>,
+    consensus: Arc<C>,
 
     pin_ef: PhantomData<EF>,
 }
 
-impl<EF: ExecutorFactory<DB>, M: MemPool, S: Storage, DB: TrieDB> DefaultAPIAdapter<EF, M, S, DB> {
-    pub fn new(mempool: Arc<M>, storage: Arc<S>, trie_db: Arc<DB>) -> Self {
+impl<EF, M, S, DB, C> DefaultAPIAdapter<EF, M, S, DB, C>
+where
+    EF: ExecutorFactory<DB>,
+    M: MemPool,
+    S: Storage,
+    DB: TrieDB,
+    C: ConsensusMonitor,
+{
+    pub fn new(mempool: Arc<M>, storage: Arc<S>, trie_db: Arc<DB>, consensus: Arc<C>) -> Self {
         Self {
             mempool,
             storage,
             trie_db,
+            consensus,
 
             pin_ef: PhantomData,
         }
@@ -29,8 +44,13 @@ impl<EF: ExecutorFactory<DB>, M: MemPool, S: Storage, DB: TrieDB> DefaultAPIAdap
 }
 
 #[async_trait]
-impl<EF: ExecutorFactory<DB>, M: MemPool, S: Storage, DB: TrieDB> APIAdapter
-    for DefaultAPIAdapter<EF, M, S, DB>
+impl<EF, M, S, DB, C> APIAdapter for DefaultAPIAdapter<EF, M, S, DB, C>
+where
+    EF: ExecutorFactory<DB>,
+    M: MemPool,
+    S: Storage,
+    DB: TrieDB,
+    C: ConsensusMonitor,
 {
     async fn insert_signed_txs(
         &self,
@@ -49,6 +69,15 @@ impl<EF: ExecutorFactory<DB>, M: MemPool, S: Storage, DB: TrieDB> APIAdapter
         Ok(epoch)
     }
 
+    async fn get_proof(&self, _ctx: Context, epoch_id: Option<u64>) -> ProtocolResult<Proof> {
+        let proof = match epoch_id {
+            Some(id) => self.storage.get_proof(id).await?,
+            None => self.storage.get_latest_proof().await?,
+        };
+
+        Ok(proof)
+    }
+
     async fn get_receipt_by_tx_hash(
         &self,
         _ctx: Context,
@@ -57,6 +86,14 @@ impl<EF: ExecutorFactory<DB>, M: MemPool, S: Storage, DB: TrieDB> APIAdapter
         self.storage.get_receipt(tx_hash).await
     }
 
+    async fn get_trace_by_tx_hash(
+        &self,
+        _ctx: Context,
+        tx_hash: Hash,
+    ) -> ProtocolResult<TransactionTrace> {
+        self.storage.get_trace(tx_hash).await
+    }
+
     async fn get_balance(
         &self,
         ctx: Context,
@@ -77,4 +114,41 @@ impl<EF: ExecutorFactory<DB>, M: MemPool, S: Storage, DB: TrieDB> APIAdapter
 
         executor.get_balance(address, id)
     }
+
+    async fn get_evidences(&self, _ctx: Context, epoch_id: u64) -> ProtocolResult<Vec<Evidence>> {
+        self.storage.get_evidences(epoch_id).await
+    }
+
+    async fn get_consensus_status(&self, _ctx: Context) -> ProtocolResult<ConsensusStatus> {
+        Ok(self.consensus.consensus_status())
+    }
+
+    async fn get_transaction_status(
+        &self,
+        ctx: Context,
+        tx_hash: Hash,
+    ) -> ProtocolResult<TxStatus> {
+        if self
+            .storage
+            .get_transaction_by_hash(tx_hash.clone())
+            .await
+            .is_ok()
+        {
+            return Ok(TxStatus::Committed);
+        }
+
+        self.mempool.get_tx_status(ctx, tx_hash).await
+    }
+
+    async fn get_pending_transactions(
+        &self,
+        ctx: Context,
+        address: UserAddress,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        self.mempool.get_pending_txs(ctx, address).await
+    }
+
+    async fn get_mempool_stats(&self, ctx: Context) -> ProtocolResult<MemPoolStats> {
+        self.mempool.get_stats(ctx).await
+    }
 }
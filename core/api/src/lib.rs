@@ -20,8 +20,9 @@ use protocol::traits::{APIAdapter, Context};
 
 use crate::config::GraphQLConfig;
 use crate::schema::{
-    Address, AssetID, Balance, Bytes, ContractType, Epoch, Hash, InputDeployAction,
-    InputRawTransaction, InputTransactionEncryption, InputTransferAction, Uint64,
+    Address, AssetID, Balance, Bytes, ConsensusStatus, ContractType, Epoch, Evidence, Hash,
+    InputDeployAction, InputRawTransaction, InputTransactionEncryption, InputTransferAction,
+    MempoolStats, PendingTransaction, Proof, TransactionStatus, TransactionTrace, Uint64,
 };
 use http::header::HeaderValue;
 use tide::middleware::{CorsMiddleware, CorsOrigin};
@@ -69,6 +70,18 @@ impl Query {
         Ok(Epoch::from(epoch))
     }
 
+    #[graphql(
+        name = "getProof",
+        description = "Get the proof of an epoch, or the latest proof if the epoch ID is omitted"
+    )]
+    fn get_proof(state_ctx: &State, epoch_id: Option<Uint64>) -> FieldResult<Proof> {
+        let epoch_id = opt_hex_to_u64(epoch_id.map(|id| id.as_hex()))?;
+
+        let proof = block_on(state_ctx.adapter.get_proof(Context::new(), epoch_id))
+            .map_err(FieldError::from)?;
+        Ok(Proof::from(proof))
+    }
+
     #[graphql(
         name = "getBalance",
         description = "Get the asset balance of an account",
@@ -100,6 +113,96 @@ impl Query {
             Err(_) => Ok(Balance::from(protocol::types::Balance::from_bytes_be(b""))),
         }
     }
+
+    #[graphql(
+        name = "traceTransaction",
+        description = "Get the state diffs of a transaction, only available when \
+                       the node is started with executor tracing enabled"
+    )]
+    fn trace_transaction(state_ctx: &State, tx_hash: Hash) -> FieldResult<TransactionTrace> {
+        let tx_hash = protocol::types::Hash::from_hex(&tx_hash.as_hex())?;
+
+        let trace = block_on(
+            state_ctx
+                .adapter
+                .get_trace_by_tx_hash(Context::new(), tx_hash),
+        )
+        .map_err(FieldError::from)?;
+        Ok(TransactionTrace::from(trace))
+    }
+
+    #[graphql(
+        name = "getEvidences",
+        description = "Get the evidences of validators signing two distinct consensus \
+                       messages at an epoch"
+    )]
+    fn get_evidences(state_ctx: &State, epoch_id: Uint64) -> FieldResult<Vec<Evidence>> {
+        let epoch_id = hex_to_u64(&epoch_id.as_hex())?;
+
+        let evidences = block_on(state_ctx.adapter.get_evidences(Context::new(), epoch_id))
+            .map_err(FieldError::from)?;
+        let evidences = evidences
+            .into_iter()
+            .map(Evidence::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(evidences)
+    }
+
+    #[graphql(
+        name = "consensusStatus",
+        description = "Get the round, step, locked proposal and votes of the running consensus"
+    )]
+    fn consensus_status(state_ctx: &State) -> FieldResult<ConsensusStatus> {
+        let status = block_on(state_ctx.adapter.get_consensus_status(Context::new()))
+            .map_err(FieldError::from)?;
+        Ok(ConsensusStatus::from(status))
+    }
+
+    #[graphql(
+        name = "getTransactionStatus",
+        description = "Get whether a transaction is pending in the mempool, ordered by \
+                       the running consensus or committed"
+    )]
+    fn get_transaction_status(state_ctx: &State, tx_hash: Hash) -> FieldResult<TransactionStatus> {
+        let tx_hash = protocol::types::Hash::from_hex(&tx_hash.as_hex())?;
+
+        let status = block_on(
+            state_ctx
+                .adapter
+                .get_transaction_status(Context::new(), tx_hash),
+        )
+        .map_err(FieldError::from)?;
+        Ok(TransactionStatus::from(status))
+    }
+
+    #[graphql(
+        name = "getPendingTransactions",
+        description = "Get the transactions of an account waiting in the mempool"
+    )]
+    fn get_pending_transactions(
+        state_ctx: &State,
+        address: Address,
+    ) -> FieldResult<Vec<PendingTransaction>> {
+        let address = protocol::types::UserAddress::from_hex(&address.as_hex())?;
+
+        let txs = block_on(
+            state_ctx
+                .adapter
+                .get_pending_transactions(Context::new(), address),
+        )
+        .map_err(FieldError::from)?;
+        Ok(txs.into_iter().map(PendingTransaction::from).collect())
+    }
+
+    #[graphql(
+        name = "mempoolStats",
+        description = "Get the statistics of the mempool"
+    )]
+    fn mempool_stats(state_ctx: &State) -> FieldResult<MempoolStats> {
+        let stats = block_on(state_ctx.adapter.get_mempool_stats(Context::new()))
+            .map_err(FieldError::from)?;
+        Ok(MempoolStats::from(stats))
+    }
 }
 
 struct Mutation;
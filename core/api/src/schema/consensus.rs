@@ -0,0 +1,90 @@
+use crate::schema::{Address, Bytes, Hash, Uint64};
+
+#[derive(GraphQLEnum, Clone)]
+#[graphql(description = "The step of the current consensus round")]
+pub enum ConsensusStep {
+    Propose,
+    Prevote,
+    Precommit,
+    Commit,
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(
+    description = "The consensus state observed by the node. The votes only contain \
+                   the votes of the node and the votes sent to it as the leader."
+)]
+pub struct ConsensusStatus {
+    #[graphql(description = "The epoch in consensus")]
+    epoch_id: Uint64,
+    round: Uint64,
+    step: ConsensusStep,
+    #[graphql(description = "The hash of the proposal received in the current round")]
+    proposal: Option<Hash>,
+    #[graphql(description = "The proposal locked by a prevote QC")]
+    locked: Option<LockedProposal>,
+    votes: Vec<ValidatorVotes>,
+    #[graphql(description = "Whether the node is synchronizing epochs from others")]
+    syncing: bool,
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "The proposal locked by a prevote QC")]
+pub struct LockedProposal {
+    round:      Uint64,
+    epoch_hash: Hash,
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "The votes of a validator in the current round")]
+pub struct ValidatorVotes {
+    address: Address,
+    #[graphql(description = "The voted epoch hash, empty if voted nil")]
+    prevote: Option<Bytes>,
+    #[graphql(description = "The voted epoch hash, empty if voted nil")]
+    precommit: Option<Bytes>,
+}
+
+impl From<protocol::traits::ConsensusStep> for ConsensusStep {
+    fn from(step: protocol::traits::ConsensusStep) -> Self {
+        match step {
+            protocol::traits::ConsensusStep::Propose => ConsensusStep::Propose,
+            protocol::traits::ConsensusStep::Prevote => ConsensusStep::Prevote,
+            protocol::traits::ConsensusStep::Precommit => ConsensusStep::Precommit,
+            protocol::traits::ConsensusStep::Commit => ConsensusStep::Commit,
+        }
+    }
+}
+
+impl From<protocol::traits::ConsensusStatus> for ConsensusStatus {
+    fn from(status: protocol::traits::ConsensusStatus) -> Self {
+        ConsensusStatus {
+            epoch_id: Uint64::from(status.epoch_id),
+            round:    Uint64::from(status.round),
+            step:     ConsensusStep::from(status.step),
+            proposal: status.proposal.map(Hash::from),
+            locked:   status.locked.map(LockedProposal::from),
+            votes:    status.votes.into_iter().map(ValidatorVotes::from).collect(),
+            syncing:  status.syncing,
+        }
+    }
+}
+
+impl From<protocol::traits::LockedProposal> for LockedProposal {
+    fn from(locked: protocol::traits::LockedProposal) -> Self {
+        LockedProposal {
+            round:      Uint64::from(locked.round),
+            epoch_hash: Hash::from(locked.epoch_hash),
+        }
+    }
+}
+
+impl From<protocol::traits::ValidatorVotes> for ValidatorVotes {
+    fn from(votes: protocol::traits::ValidatorVotes) -> Self {
+        ValidatorVotes {
+            address:   Address::from(protocol::types::Address::User(votes.address)),
+            prevote:   votes.prevote.map(Bytes::from),
+            precommit: votes.precommit.map(Bytes::from),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::schema::{Address, Hash, MerkleRoot, Uint64};
+use crate::schema::{Address, Bytes, Hash, MerkleRoot, Uint64};
 
 #[derive(GraphQLObject, Clone)]
 #[graphql(
@@ -31,6 +31,10 @@ pub struct EpochHeader {
     pub order_root: MerkleRoot,
     #[graphql(description = "The merkle roots of all the confirms")]
     pub confirm_root: Vec<MerkleRoot>,
+    #[graphql(
+        description = "The latest executed epoch, the state root and the receipt roots are the execution results up to it"
+    )]
+    pub exec_epoch_id: Uint64,
     #[graphql(description = "The merkle root of state root")]
     pub state_root: MerkleRoot,
     #[graphql(description = "The merkle roots of receipts")]
@@ -45,6 +49,24 @@ pub struct EpochHeader {
     // validators:        Vec<Validator>,
 }
 
+#[derive(GraphQLObject, Clone)]
+#[graphql(
+    description = "The proof of an epoch, it is aggregated from the precommit votes \
+                   of more than 2/3 vote weight of the validators"
+)]
+pub struct Proof {
+    #[graphql(description = "The proved epoch")]
+    pub epoch_id: Uint64,
+    #[graphql(description = "The consensus round in which the epoch was committed")]
+    pub round: Uint64,
+    #[graphql(description = "The hash of the serialized proved epoch")]
+    pub epoch_hash: Hash,
+    #[graphql(description = "The aggregated BLS signature of the voters")]
+    pub signature: Bytes,
+    #[graphql(description = "The voters in the sorted validator list of the epoch")]
+    pub bitmap: Bytes,
+}
+
 impl From<protocol::types::EpochHeader> for EpochHeader {
     fn from(epoch_header: protocol::types::EpochHeader) -> Self {
         EpochHeader {
@@ -58,6 +80,7 @@ impl From<protocol::types::EpochHeader> for EpochHeader {
                 .into_iter()
                 .map(MerkleRoot::from)
                 .collect(),
+            exec_epoch_id:     Uint64::from(epoch_header.exec_epoch_id),
             state_root:        MerkleRoot::from(epoch_header.state_root),
             receipt_root:      epoch_header
                 .receipt_root
@@ -83,3 +106,15 @@ impl From<protocol::types::Epoch> for Epoch {
         }
     }
 }
+
+impl From<protocol::types::Proof> for Proof {
+    fn from(proof: protocol::types::Proof) -> Self {
+        Proof {
+            epoch_id:   Uint64::from(proof.epoch_id),
+            round:      Uint64::from(proof.round),
+            epoch_hash: Hash::from(proof.epoch_hash),
+            signature:  Bytes::from(proof.signature),
+            bitmap:     Bytes::from(proof.bitmap),
+        }
+    }
+}
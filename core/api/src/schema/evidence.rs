@@ -0,0 +1,64 @@
+use std::convert::TryFrom;
+
+use protocol::fixed_codec::ProtocolFixedCodec;
+use protocol::{ProtocolError, ProtocolResult};
+
+use crate::schema::{Address, Bytes, Uint64};
+
+#[derive(GraphQLEnum, Clone)]
+#[graphql(description = "The type of the consensus messages signed twice")]
+pub enum EvidenceType {
+    Proposal,
+    Prevote,
+    Precommit,
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(
+    description = "The evidence that a validator signed two distinct consensus messages \
+                   for the same epoch, round and type"
+)]
+pub struct Evidence {
+    #[graphql(description = "The validator who signed the messages")]
+    validator: Address,
+    epoch_id: Uint64,
+    round: Uint64,
+    evidence_type: EvidenceType,
+    #[graphql(description = "The rlp encoded signed message seen first")]
+    first: Bytes,
+    #[graphql(description = "The rlp encoded signed message seen second")]
+    second: Bytes,
+    #[graphql(
+        description = "The fixed codec encoding of the evidence, it can be submitted \
+                       as the argument of a contract call for slashing"
+    )]
+    encoded: Bytes,
+}
+
+impl From<protocol::types::EvidenceType> for EvidenceType {
+    fn from(evidence_type: protocol::types::EvidenceType) -> Self {
+        match evidence_type {
+            protocol::types::EvidenceType::Proposal => EvidenceType::Proposal,
+            protocol::types::EvidenceType::Prevote => EvidenceType::Prevote,
+            protocol::types::EvidenceType::Precommit => EvidenceType::Precommit,
+        }
+    }
+}
+
+impl TryFrom<protocol::types::Evidence> for Evidence {
+    type Error = ProtocolError;
+
+    fn try_from(evidence: protocol::types::Evidence) -> ProtocolResult<Self> {
+        let encoded = evidence.encode_fixed()?;
+
+        Ok(Evidence {
+            validator:     Address::from(protocol::types::Address::User(evidence.validator)),
+            epoch_id:      Uint64::from(evidence.epoch_id),
+            round:         Uint64::from(evidence.round),
+            evidence_type: EvidenceType::from(evidence.evidence_type),
+            first:         Bytes::from(evidence.first),
+            second:        Bytes::from(evidence.second),
+            encoded:       Bytes::from(encoded),
+        })
+    }
+}
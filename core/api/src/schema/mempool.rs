@@ -0,0 +1,72 @@
+use crate::schema::{Fee, Hash, Uint64};
+
+#[derive(GraphQLEnum, Clone)]
+#[graphql(description = "The status of a transaction")]
+pub enum TransactionStatus {
+    #[graphql(description = "Waiting in the mempool to be packaged")]
+    Pending,
+    #[graphql(description = "Synchronized from the proposal of other nodes")]
+    Proposed,
+    #[graphql(description = "Ordered in the proposal of the running consensus")]
+    Ordered,
+    #[graphql(description = "Committed in an epoch")]
+    Committed,
+    Unknown,
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "A transaction waiting in the mempool")]
+pub struct PendingTransaction {
+    tx_hash: Hash,
+    nonce:   Hash,
+    timeout: Uint64,
+    fee:     Fee,
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "The statistics of the mempool")]
+pub struct MempoolStats {
+    #[graphql(description = "The number of transactions in the pool")]
+    pool_size: Uint64,
+    #[graphql(description = "The number of transactions fetched for the running consensus")]
+    callback_size: Uint64,
+    incumbent_queue_len: Uint64,
+    candidate_queue_len: Uint64,
+    #[graphql(description = "The smallest timeout of the transactions in the pool")]
+    oldest_timeout: Option<Uint64>,
+}
+
+impl From<protocol::traits::TxStatus> for TransactionStatus {
+    fn from(status: protocol::traits::TxStatus) -> Self {
+        match status {
+            protocol::traits::TxStatus::Pending => TransactionStatus::Pending,
+            protocol::traits::TxStatus::Proposed => TransactionStatus::Proposed,
+            protocol::traits::TxStatus::Ordered => TransactionStatus::Ordered,
+            protocol::traits::TxStatus::Committed => TransactionStatus::Committed,
+            protocol::traits::TxStatus::Unknown => TransactionStatus::Unknown,
+        }
+    }
+}
+
+impl From<protocol::types::SignedTransaction> for PendingTransaction {
+    fn from(tx: protocol::types::SignedTransaction) -> Self {
+        PendingTransaction {
+            tx_hash: Hash::from(tx.tx_hash),
+            nonce:   Hash::from(tx.raw.nonce),
+            timeout: Uint64::from(tx.raw.timeout),
+            fee:     Fee::from(tx.raw.fee),
+        }
+    }
+}
+
+impl From<protocol::traits::MemPoolStats> for MempoolStats {
+    fn from(stats: protocol::traits::MemPoolStats) -> Self {
+        MempoolStats {
+            pool_size:           Uint64::from(stats.pool_size as u64),
+            callback_size:       Uint64::from(stats.callback_size as u64),
+            incumbent_queue_len: Uint64::from(stats.incumbent_queue_len as u64),
+            candidate_queue_len: Uint64::from(stats.candidate_queue_len as u64),
+            oldest_timeout:      stats.oldest_timeout.map(Uint64::from),
+        }
+    }
+}
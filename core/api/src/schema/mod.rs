@@ -1,7 +1,15 @@
+mod consensus;
 mod epoch;
+mod evidence;
+mod mempool;
+mod trace;
 mod transaction;
 
-pub use epoch::{Epoch, EpochHeader};
+pub use consensus::{ConsensusStatus, ConsensusStep, LockedProposal, ValidatorVotes};
+pub use epoch::{Epoch, EpochHeader, Proof};
+pub use evidence::{Evidence, EvidenceType};
+pub use mempool::{MempoolStats, PendingTransaction, TransactionStatus};
+pub use trace::{ContractTrace, StateDiff, TransactionTrace};
 pub use transaction::{
     ContractType, InputDeployAction, InputRawTransaction, InputTransactionEncryption,
     InputTransferAction,
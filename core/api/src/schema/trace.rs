@@ -0,0 +1,61 @@
+use crate::schema::{Address, Bytes, Hash, Uint64};
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "The state diffs made by a transaction")]
+pub struct TransactionTrace {
+    #[graphql(description = "The hash of the transaction")]
+    tx_hash: Hash,
+    #[graphql(description = "The epoch in which the transaction was executed")]
+    epoch_id: Uint64,
+    #[graphql(description = "The contracts whose state was touched")]
+    contracts: Vec<ContractTrace>,
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "The state diffs of a contract")]
+pub struct ContractTrace {
+    address: Address,
+    diffs:   Vec<StateDiff>,
+}
+
+#[derive(GraphQLObject, Clone)]
+#[graphql(description = "The value of a state key before and after a transaction")]
+pub struct StateDiff {
+    key: Bytes,
+    #[graphql(description = "Empty if the key did not exist before")]
+    before: Option<Bytes>,
+    after: Bytes,
+}
+
+impl From<protocol::types::TransactionTrace> for TransactionTrace {
+    fn from(trace: protocol::types::TransactionTrace) -> Self {
+        TransactionTrace {
+            tx_hash:   Hash::from(trace.tx_hash),
+            epoch_id:  Uint64::from(trace.epoch_id),
+            contracts: trace
+                .contracts
+                .into_iter()
+                .map(ContractTrace::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<protocol::types::ContractTrace> for ContractTrace {
+    fn from(trace: protocol::types::ContractTrace) -> Self {
+        ContractTrace {
+            address: Address::from(trace.address),
+            diffs:   trace.diffs.into_iter().map(StateDiff::from).collect(),
+        }
+    }
+}
+
+impl From<protocol::types::StateDiff> for StateDiff {
+    fn from(diff: protocol::types::StateDiff) -> Self {
+        StateDiff {
+            key:    Bytes::from(diff.key),
+            before: diff.before.map(Bytes::from),
+            after:  Bytes::from(diff.after),
+        }
+    }
+}
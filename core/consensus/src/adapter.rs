@@ -5,17 +5,20 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use log::debug;
 
-use protocol::traits::executor::{ExecutorExecResp, ExecutorFactory, TrieDB};
+use protocol::traits::executor::{EpochReward, ExecutorExecResp, ExecutorFactory, TrieDB};
 use protocol::traits::{
     ConsensusAdapter, Context, Gossip, MemPool, MessageTarget, MixedTxHashes, NodeInfo, Priority,
     Rpc, Storage,
 };
 use protocol::types::{
-    Address, Epoch, Hash, MerkleRoot, Proof, Receipt, SignedTransaction, Validator,
+    Address, Epoch, Evidence, Hash, MerkleRoot, Proof, Receipt, SignedTransaction,
+    TransactionTrace, ValidatorSet,
 };
 use protocol::ProtocolResult;
 
-use crate::fixed_types::{ConsensusRpcRequest, ConsensusRpcResponse, PullTxsRequest};
+use crate::fixed_types::{
+    ConsensusRpcRequest, ConsensusRpcResponse, PullEpochsRequest, PullTxsRequest,
+};
 use crate::{ConsensusError, MsgType};
 
 pub struct OverlordConsensusAdapter<
@@ -31,6 +34,7 @@ pub struct OverlordConsensusAdapter<
     mempool: Arc<M>,
     storage: Arc<S>,
     trie_db: Arc<DB>,
+    trace:   bool,
 
     pin_ef: PhantomData<EF>,
 }
@@ -100,6 +104,7 @@ where
         cycles_price: u64,
         coinbase: Address,
         signed_txs: Vec<SignedTransaction>,
+        reward: EpochReward,
     ) -> ProtocolResult<ExecutorExecResp> {
         let mut executor = EF::from_root(
             node_info.chain_id,
@@ -109,7 +114,10 @@ where
             cycles_price,
             coinbase,
         )?;
-        executor.exec(signed_txs)
+        if self.trace {
+            executor.enable_trace();
+        }
+        executor.exec(signed_txs, reward)
     }
 
     async fn flush_mempool(&self, ctx: Context, txs: Vec<Hash>) -> ProtocolResult<()> {
@@ -124,7 +132,16 @@ where
         self.storage.insert_receipts(receipts).await
     }
 
+    async fn save_traces(
+        &self,
+        _ctx: Context,
+        traces: Vec<TransactionTrace>,
+    ) -> ProtocolResult<()> {
+        self.storage.insert_traces(traces).await
+    }
+
     async fn save_proof(&self, _ctx: Context, proof: Proof) -> ProtocolResult<()> {
+        self.storage.insert_proof(proof.clone()).await?;
         self.storage.update_latest_proof(proof).await
     }
 
@@ -136,13 +153,25 @@ where
         self.storage.insert_transactions(signed_txs).await
     }
 
+    async fn save_evidence(&self, _ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        self.storage.insert_evidence(evidence).await
+    }
+
     async fn get_last_validators(
         &self,
-        _ctx: Context,
+        node_info: NodeInfo,
+        state_root: MerkleRoot,
         epoch_id: u64,
-    ) -> ProtocolResult<Vec<Validator>> {
-        let epoch = self.storage.get_epoch_by_epoch_id(epoch_id).await?;
-        Ok(epoch.header.validators)
+    ) -> ProtocolResult<Option<ValidatorSet>> {
+        let executor = EF::from_root(
+            node_info.chain_id,
+            state_root,
+            Arc::clone(&self.trie_db),
+            epoch_id,
+            0,
+            Address::User(node_info.self_address),
+        )?;
+        executor.get_validators(epoch_id)
     }
 
     async fn get_current_epoch_id(&self, _ctx: Context) -> ProtocolResult<u64> {
@@ -171,6 +200,48 @@ where
         }
     }
 
+    async fn pull_epochs(
+        &self,
+        ctx: Context,
+        begin: u64,
+        count: u64,
+        end: &str,
+    ) -> ProtocolResult<Vec<Epoch>> {
+        debug!("consensus: send rpc pull {} epochs from {}", count, begin);
+        let res = self
+            .rpc
+            .call::<ConsensusRpcRequest, ConsensusRpcResponse>(
+                ctx,
+                end,
+                ConsensusRpcRequest::PullEpochBatch(PullEpochsRequest::new(begin, count)),
+                Priority::High,
+            )
+            .await?;
+
+        match res {
+            ConsensusRpcResponse::PullEpochBatch(epochs) => Ok(epochs.inner),
+            _ => Err(ConsensusError::RpcErr(MsgType::RpcPullEpochs).into()),
+        }
+    }
+
+    async fn pull_proof(&self, ctx: Context, epoch_id: u64, end: &str) -> ProtocolResult<Proof> {
+        debug!("consensus: send rpc pull proof {}", epoch_id);
+        let res = self
+            .rpc
+            .call::<ConsensusRpcRequest, ConsensusRpcResponse>(
+                ctx,
+                end,
+                ConsensusRpcRequest::PullProof(epoch_id),
+                Priority::High,
+            )
+            .await?;
+
+        match res {
+            ConsensusRpcResponse::PullProof(proof) => Ok(*proof),
+            _ => Err(ConsensusError::RpcErr(MsgType::RpcPullProof).into()),
+        }
+    }
+
     async fn pull_txs(
         &self,
         ctx: Context,
@@ -197,6 +268,14 @@ where
     async fn get_epoch_by_id(&self, _ctx: Context, epoch_id: u64) -> ProtocolResult<Epoch> {
         self.storage.get_epoch_by_epoch_id(epoch_id).await
     }
+
+    async fn get_signed_txs(
+        &self,
+        _ctx: Context,
+        hashes: Vec<Hash>,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        self.storage.get_transactions(hashes).await
+    }
 }
 
 impl<EF, G, M, R, S, DB> OverlordConsensusAdapter<EF, G, M, R, S, DB>
@@ -214,6 +293,7 @@ where
         mempool: Arc<M>,
         storage: Arc<S>,
         trie_db: Arc<DB>,
+        trace: bool,
     ) -> Self {
         OverlordConsensusAdapter {
             rpc,
@@ -221,6 +301,7 @@ where
             mempool,
             storage,
             trie_db,
+            trace,
 
             pin_ef: PhantomData,
         }
@@ -1,23 +1,32 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use bincode::deserialize;
 use creep::Context;
+use futures::channel::{mpsc::channel, oneshot};
+use futures::future::{self, Either};
 use futures::lock::Mutex;
-use log::{debug, info};
+use log::{debug, error, info};
 use overlord::types::{AggregatedVote, Node, OverlordMsg, SignedProposal, SignedVote, Status};
 use overlord::{DurationConfig, Overlord, OverlordHandler};
 use parking_lot::RwLock;
 
-use common_crypto::{PrivateKey, Secp256k1PrivateKey};
+use common_crypto::BLS12381PrivateKey;
 
-use protocol::traits::{Consensus, ConsensusAdapter, CurrentConsensusStatus, NodeInfo};
-use protocol::types::{Address, Hash, Proof, Validator};
-use protocol::{fixed_codec::ProtocolFixedCodec, ProtocolResult};
+use protocol::traits::{
+    Consensus, ConsensusAdapter, ConsensusMonitor, ConsensusStatus, CurrentConsensusStatus,
+    NodeInfo,
+};
+use protocol::types::{Hash, Validator};
+use protocol::{fixed_codec::ProtocolFixedCodec, ProtocolError, ProtocolResult};
 
-use crate::engine::ConsensusEngine;
+use crate::engine::{proposal_to_wal, qc_to_wal, ConsensusEngine};
+use crate::execution::{execute_epochs, ExecStatus, MAX_EXEC_LAG};
 use crate::fixed_types::{FixedEpochID, FixedPill, FixedSignedTxs};
-use crate::util::OverlordCrypto;
+use crate::synchronization::{next_epoch, prefetch_epochs, pull_proof, SyncEpoch, SyncPeers};
+use crate::util::{check_proof, OverlordCrypto};
+use crate::wal::{ConsensusWal, WalMsgType};
 use crate::{ConsensusError, MsgType};
 
 /// Provide consensus
@@ -29,8 +38,14 @@ pub struct OverlordConsensus<Adapter: ConsensusAdapter + 'static> {
     handler: OverlordHandler<FixedPill>,
     /// A consensus engine for synchronous.
     engine: Arc<ConsensusEngine<Adapter>>,
+    /// The write-ahead log of consensus messages.
+    wal: Arc<ConsensusWal>,
+    /// The peers to synchronize from.
+    peers: Arc<SyncPeers>,
     /// Synchronization lock.
     lock: Mutex<()>,
+    /// Receive the error of the background execution.
+    exec_err: Mutex<Option<oneshot::Receiver<ProtocolError>>>,
 }
 
 #[async_trait]
@@ -38,6 +53,10 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
     async fn set_proposal(&self, ctx: Context, proposal: Vec<u8>) -> ProtocolResult<()> {
         let signed_proposal: SignedProposal<FixedPill> = rlp::decode(&proposal)
             .map_err(|_| ConsensusError::DecodeErr(MsgType::SignedProposal))?;
+        self.wal.cache_proposal(proposal_to_wal(&signed_proposal));
+        if let Some(evidence) = self.engine.record_proposal(&signed_proposal) {
+            self.engine.save_evidence(ctx.clone(), evidence).await?;
+        }
         self.handler
             .send_msg(ctx, OverlordMsg::SignedProposal(signed_proposal))
             .map_err(|e| ConsensusError::OverlordErr(Box::new(e)))?;
@@ -47,6 +66,9 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
     async fn set_vote(&self, ctx: Context, vote: Vec<u8>) -> ProtocolResult<()> {
         let signed_vote: SignedVote =
             rlp::decode(&vote).map_err(|_| ConsensusError::DecodeErr(MsgType::SignedVote))?;
+        if let Some(evidence) = self.engine.record_vote(&signed_vote) {
+            self.engine.save_evidence(ctx.clone(), evidence).await?;
+        }
         self.handler
             .send_msg(ctx, OverlordMsg::SignedVote(signed_vote))
             .map_err(|e| ConsensusError::OverlordErr(Box::new(e)))?;
@@ -56,6 +78,11 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
     async fn set_qc(&self, ctx: Context, qc: Vec<u8>) -> ProtocolResult<()> {
         let aggregated_vote: AggregatedVote =
             rlp::decode(&qc).map_err(|_| ConsensusError::DecodeErr(MsgType::AggregateVote))?;
+        // Overlord only checks the format of the aggregated signature.
+        self.engine.record_qc(&aggregated_vote)?;
+        if let Some(qc) = qc_to_wal(&aggregated_vote) {
+            self.wal.cache_qc(qc);
+        }
         self.handler
             .send_msg(ctx, OverlordMsg::AggregatedVote(aggregated_vote))
             .map_err(|e| ConsensusError::OverlordErr(Box::new(e)))?;
@@ -63,17 +90,18 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
     }
 
     async fn update_epoch(&self, ctx: Context, msg: Vec<u8>) -> ProtocolResult<()> {
+        // Reveive the rich epoch ID, and remember the epoch of the peer.
+        let epoch_id: FixedEpochID =
+            deserialize(&msg).map_err(|_| ConsensusError::DecodeErr(MsgType::RichEpochID))?;
+        let rich_epoch_id = epoch_id.inner - 1;
+        self.peers.update(&ctx, rich_epoch_id);
+
         let sync_lock = self.lock.try_lock();
         if sync_lock.is_none() {
             // Synchronization is processing.
             return Ok(());
         }
 
-        // Reveive the rich epoch ID.
-        let epoch_id: FixedEpochID =
-            deserialize(&msg).map_err(|_| ConsensusError::DecodeErr(MsgType::RichEpochID))?;
-        let rich_epoch_id = epoch_id.inner - 1;
-
         // TODO: fix to get_epoch_by_epoch_id()
         let current_epoch_id = self
             .engine
@@ -94,80 +122,70 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
         info!("self {}, chain {}", current_epoch_id, rich_epoch_id);
         info!("consensus: start synchronization");
 
-        let mut state_root = Hash::from_empty();
         let mut current_hash = if current_epoch_id != 0 {
             let current_epoch = self
                 .engine
                 .get_epoch_by_id(ctx.clone(), current_epoch_id)
                 .await?;
-            state_root = current_epoch.header.state_root.clone();
-            let tmp = Hash::digest(current_epoch.encode_fixed()?);
-
-            // Check epoch for the first time.
-            let epoch_header = self
-                .engine
-                .pull_epoch(ctx.clone(), current_epoch_id + 1)
-                .await?
-                .header;
-            self.check_proof(current_epoch_id + 1, epoch_header.proof.clone())?;
-            if tmp != epoch_header.pre_hash {
-                return Err(ConsensusError::SyncEpochHashErr(current_epoch_id + 1).into());
-            }
-            tmp
+            Hash::digest(current_epoch.encode_fixed()?)
         } else {
             Hash::from_empty()
         };
 
+        // Epochs and their transactions are prefetched in background while
+        // the previous epochs are committed.
+        let mut epochs = prefetch_epochs(
+            Arc::clone(&self.engine),
+            Arc::clone(&self.peers),
+            current_epoch_id + 1,
+            rich_epoch_id,
+        );
+        let mut current = next_epoch(&mut epochs).await?;
+
         // Start to synchronization.
         for id in (current_epoch_id + 1)..=rich_epoch_id {
             info!("consensus: start synchronization epoch {}", id);
+            let SyncEpoch { epoch, txs, peer } = current;
 
-            // First pull a new block.
-            debug!("consensus: synchronization pull epoch {}", id);
-            let epoch = self.engine.pull_epoch(ctx.clone(), id).await?;
-
-            // Check proof and previous hash.
-            debug!("consensus: synchronization check proof and previous hash");
-            let proof = epoch.header.proof.clone();
-            self.check_proof(id, proof.clone())?;
+            // Check previous hash.
             if id != 1 && current_hash != epoch.header.pre_hash {
+                self.peers.record_invalid(peer);
                 return Err(ConsensusError::SyncEpochHashErr(id).into());
             }
-            self.engine.save_proof(ctx.clone(), proof.clone()).await?;
 
-            // Then pull signed transactions.
-            debug!("consensus: synchronization pull signed transactions");
-            let txs = self
-                .engine
-                .pull_txs(ctx.clone(), epoch.ordered_tx_hashes.clone())
-                .await?;
-
-            // After get the signed transactions:
-            // 1. Execute the signed transactions.
-            // 2. Save the signed transactions.
-            // 3. Save the latest proof.
-            // 4. Save the new epoch.
-            // 5. Save the receipt.
-            debug!("consensus: synchronization executor the epoch");
-            let exec_resp = self
-                .engine
-                .exec(
-                    state_root.clone(),
-                    epoch.header.epoch_id,
-                    Address::User(epoch.header.proposer.clone()),
-                    txs.clone(),
-                )
-                .await?;
-            state_root = exec_resp.state_root.clone();
+            // The proof of an epoch is carried by the next epoch, so check
+            // the proof with the next epoch before committing. The rich epoch
+            // has no next epoch yet, its proof is pulled from the peers.
+            debug!("consensus: synchronization check proof of epoch {}", id);
+            let validators = self.engine.get_current_validators();
+            let (proof, next) = if id < rich_epoch_id {
+                let next = next_epoch(&mut epochs).await?;
+
+                if let Err(e) = check_proof(&epoch, &next.epoch.header.proof, &validators) {
+                    self.peers.record_invalid(next.peer);
+                    return Err(e);
+                }
+                (next.epoch.header.proof.clone(), Some(next))
+            } else {
+                let proof = pull_proof(&self.engine, &self.peers, &epoch, &validators).await?;
+                (proof, None)
+            };
+
+            // Save the checked proof of this epoch, the proof in its header
+            // is the proof of the previous epoch.
+            self.engine.save_proof(ctx.clone(), proof.clone()).await?;
 
+            // The epoch is executed in background, the execution results are
+            // checked by the proofs of the following epochs.
             debug!("consensus: synchronization update the rich status");
-            self.engine
-                .update_status(epoch.header.epoch_id, epoch.clone(), proof, exec_resp, txs)
-                .await?;
-
-            // Update the previous hash and last epoch.
-            info!("consensus: finish synchronization {} epoch", id);
             current_hash = Hash::digest(epoch.encode_fixed()?);
+            self.engine.commit_epoch(epoch, proof, txs).await?;
+            info!("consensus: finish synchronization {} epoch", id);
+
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
         }
 
         debug!(
@@ -187,46 +205,87 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
     }
 }
 
+impl<Adapter: ConsensusAdapter + 'static> ConsensusMonitor for OverlordConsensus<Adapter> {
+    fn consensus_status(&self) -> ConsensusStatus {
+        // The lock is held while synchronizing.
+        let syncing = self.lock.try_lock().is_none();
+        self.engine.monitor().status(syncing)
+    }
+}
+
 impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
     pub fn new(
         current_consensus_status: CurrentConsensusStatus,
         node_info: NodeInfo,
-        priv_key: Secp256k1PrivateKey,
+        bls_priv_key: BLS12381PrivateKey,
+        wal_path: PathBuf,
         adapter: Arc<Adapter>,
-    ) -> Self {
+    ) -> ProtocolResult<Self> {
+        let wal = Arc::new(ConsensusWal::new(wal_path)?);
+        let crypto = OverlordCrypto::new(
+            node_info.self_address.clone(),
+            bls_priv_key,
+            &current_consensus_status.validators,
+        );
+        let exec_status = Arc::new(ExecStatus::new(
+            current_consensus_status.exec_epoch_id,
+            current_consensus_status.state_root.clone(),
+        ));
+        let (exec_sender, exec_receiver) = channel(MAX_EXEC_LAG as usize);
+        let latest_epoch_id = current_consensus_status.epoch_id - 1;
         let current_consensus_status = Arc::new(RwLock::new(current_consensus_status));
 
         let engine = Arc::new(ConsensusEngine::new(
             Arc::clone(&current_consensus_status),
             node_info.clone(),
             Arc::clone(&adapter),
+            crypto.clone(),
+            Arc::clone(&wal),
+            exec_status,
+            exec_sender,
         ));
 
-        let crypto = OverlordCrypto::new(priv_key.pub_key(), priv_key);
+        // The committed epochs are executed in background, an execution error
+        // stops the node.
+        let (exec_err_tx, exec_err_rx) = oneshot::channel();
+        let exec_engine = Arc::clone(&engine);
+        runtime::spawn(async move {
+            if let Err(e) = execute_epochs(exec_engine, exec_receiver, latest_epoch_id).await {
+                error!("consensus: execute epochs failed {:?}", e);
+                let _ = exec_err_tx.send(e);
+            }
+        });
+
         let overlord = Overlord::new(
             node_info.self_address.as_bytes(),
             Arc::clone(&engine),
             crypto,
         );
         let overlord_handler = overlord.get_handler();
+        let epoch_id = current_consensus_status.read().epoch_id;
 
         overlord_handler
             .send_msg(
                 Context::new(),
                 OverlordMsg::RichStatus(gen_overlord_status(
-                    current_consensus_status.read().epoch_id,
+                    epoch_id,
                     current_consensus_status.read().consensus_interval,
                     current_consensus_status.read().validators.clone(),
                 )),
             )
             .unwrap();
 
-        Self {
+        replay_wal(&overlord_handler, &wal, epoch_id)?;
+
+        Ok(Self {
             inner: Arc::new(overlord),
             handler: overlord_handler,
             lock: Mutex::new(()),
             engine,
-        }
+            wal,
+            peers: Arc::new(SyncPeers::new()),
+            exec_err: Mutex::new(Some(exec_err_rx)),
+        })
     }
 
     pub async fn run(
@@ -234,17 +293,75 @@ impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
         interval: u64,
         timer_config: Option<DurationConfig>,
     ) -> ProtocolResult<()> {
-        self.inner
-            .run(interval, timer_config)
+        let exec_err = self
+            .exec_err
+            .lock()
             .await
-            .map_err(|e| ConsensusError::OverlordErr(Box::new(e)))?;
+            .take()
+            .expect("overlord consensus runs once");
+        let overlord = Box::pin(self.inner.run(interval, timer_config));
+
+        // The committed epochs are never executed after an execution error,
+        // return it rather than stalling.
+        match future::select(overlord, exec_err).await {
+            Either::Left((res, _)) => res.map_err(|e| ConsensusError::OverlordErr(Box::new(e)))?,
+            Either::Right((Ok(e), _)) => return Err(e),
+            Either::Right((Err(_), overlord)) => overlord
+                .await
+                .map_err(|e| ConsensusError::OverlordErr(Box::new(e)))?,
+        }
 
         Ok(())
     }
+}
 
-    fn check_proof(&self, _epoch_id: u64, _proof: Proof) -> ProtocolResult<()> {
-        Ok(())
+/// Replay the lock, its prevote QC and the messages signed before the restart
+/// into overlord, so that it recovers the lock and the round and does not sign
+/// conflicting messages.
+fn replay_wal(
+    handler: &OverlordHandler<FixedPill>,
+    wal: &ConsensusWal,
+    epoch_id: u64,
+) -> ProtocolResult<()> {
+    let msgs = replay_msgs(wal, epoch_id)?;
+    if !msgs.is_empty() {
+        info!(
+            "consensus: replay {} WAL messages of epoch {}",
+            msgs.len(),
+            epoch_id
+        );
+    }
+
+    for msg in msgs.into_iter() {
+        handler
+            .send_msg(Context::new(), msg)
+            .map_err(|e| ConsensusError::OverlordErr(Box::new(e)))?;
     }
+    Ok(())
+}
+
+fn replay_msgs(wal: &ConsensusWal, epoch_id: u64) -> ProtocolResult<Vec<OverlordMsg<FixedPill>>> {
+    wal.info()
+        .replay_msgs(epoch_id)
+        .into_iter()
+        .map(|msg| {
+            let overlord_msg = match msg.msg_type {
+                WalMsgType::Proposal => OverlordMsg::SignedProposal(
+                    rlp::decode(&msg.msg)
+                        .map_err(|_| ConsensusError::DecodeErr(MsgType::SignedProposal))?,
+                ),
+                WalMsgType::Prevote | WalMsgType::Precommit => OverlordMsg::SignedVote(
+                    rlp::decode(&msg.msg)
+                        .map_err(|_| ConsensusError::DecodeErr(MsgType::SignedVote))?,
+                ),
+                WalMsgType::PrevoteQC => OverlordMsg::AggregatedVote(
+                    rlp::decode(&msg.msg)
+                        .map_err(|_| ConsensusError::DecodeErr(MsgType::AggregateVote))?,
+                ),
+            };
+            Ok(overlord_msg)
+        })
+        .collect()
 }
 
 fn gen_overlord_status(epoch_id: u64, interval: u64, validators: Vec<Validator>) -> Status {
@@ -264,3 +381,136 @@ fn gen_overlord_status(epoch_id: u64, interval: u64, validators: Vec<Validator>)
         authority_list,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use overlord::types::{
+        AggregatedSignature, AggregatedVote, OverlordMsg, Proposal, SignedProposal, SignedVote,
+        Vote, VoteType,
+    };
+    use rand::random;
+
+    use protocol::types::{Epoch, EpochHeader, Hash, Pill, Proof, UserAddress};
+
+    use crate::engine::{proposal_to_wal, qc_to_wal, vote_to_wal};
+    use crate::fixed_types::FixedPill;
+    use crate::wal::ConsensusWal;
+
+    use super::replay_msgs;
+
+    fn mock_proposal(epoch_id: u64, round: u64) -> SignedProposal<FixedPill> {
+        let nonce = Hash::digest(Bytes::from("XXXX"));
+        let proposer = UserAddress::from_hex("10CAB8EEA4799C21379C20EF5BAA2CC8AF1BEC475B").unwrap();
+        let header = EpochHeader {
+            chain_id: nonce.clone(),
+            epoch_id,
+            pre_hash: nonce.clone(),
+            timestamp: 1000,
+            logs_bloom: Default::default(),
+            order_root: nonce.clone(),
+            confirm_root: Vec::new(),
+            exec_epoch_id: 0,
+            state_root: nonce.clone(),
+            receipt_root: Vec::new(),
+            cycles_used: 999_999,
+            proposer: proposer.clone(),
+            proof: Proof {
+                epoch_id:   epoch_id - 1,
+                round:      0,
+                epoch_hash: nonce.clone(),
+                signature:  Bytes::new(),
+                bitmap:     Bytes::new(),
+            },
+            validator_version: 1,
+            validators: Vec::new(),
+        };
+        let pill = Pill {
+            epoch:          Epoch {
+                header,
+                ordered_tx_hashes: Vec::new(),
+            },
+            propose_hashes: Vec::new(),
+        };
+
+        SignedProposal {
+            signature: Bytes::from("proposal signature"),
+            proposal:  Proposal {
+                epoch_id,
+                round,
+                content: FixedPill { inner: pill },
+                epoch_hash: Hash::digest(Bytes::from("epoch")).as_bytes(),
+                lock: None,
+                proposer: proposer.as_bytes(),
+            },
+        }
+    }
+
+    fn mock_vote(proposal: &SignedProposal<FixedPill>, vote_type: VoteType) -> SignedVote {
+        SignedVote {
+            signature: Bytes::from("vote signature"),
+            vote:      Vote {
+                epoch_id: proposal.proposal.epoch_id,
+                round: proposal.proposal.round,
+                vote_type,
+                epoch_hash: proposal.proposal.epoch_hash.clone(),
+            },
+        }
+    }
+
+    fn mock_prevote_qc(proposal: &SignedProposal<FixedPill>) -> AggregatedVote {
+        AggregatedVote {
+            signature:  AggregatedSignature {
+                signature:      Bytes::from("aggregated signature"),
+                address_bitmap: Bytes::from(vec![0b1110_0000]),
+            },
+            vote_type:  VoteType::Prevote,
+            epoch_id:   proposal.proposal.epoch_id,
+            round:      proposal.proposal.round,
+            epoch_hash: proposal.proposal.epoch_hash.clone(),
+            leader:     proposal.proposal.proposer.clone(),
+        }
+    }
+
+    #[test]
+    fn test_replay_lock_after_restart() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("huobi_chain_replay_{}", random::<u64>()));
+        let wal = ConsensusWal::new(path.clone()).unwrap();
+
+        // Receive the proposal of round 1 and its prevote QC, then precommit
+        // it.
+        let proposal = mock_proposal(1, 1);
+        let prevote_qc = mock_prevote_qc(&proposal);
+        let prevote = mock_vote(&proposal, VoteType::Prevote);
+        let precommit = mock_vote(&proposal, VoteType::Precommit);
+        wal.cache_proposal(proposal_to_wal(&proposal));
+        wal.cache_qc(qc_to_wal(&prevote_qc).unwrap());
+        block_on(wal.save_signed(vote_to_wal(&prevote))).unwrap();
+        block_on(wal.save_signed(vote_to_wal(&precommit))).unwrap();
+
+        // A precommit QC is never kept.
+        let mut precommit_qc = mock_prevote_qc(&proposal);
+        precommit_qc.vote_type = VoteType::Precommit;
+        assert!(qc_to_wal(&precommit_qc).is_none());
+
+        // Killed and restarted, overlord gets back the locked proposal, the QC
+        // it locks on and the votes of this node.
+        drop(wal);
+        let wal = ConsensusWal::new(path.clone()).unwrap();
+        assert_eq!(replay_msgs(&wal, 1).unwrap(), vec![
+            OverlordMsg::SignedProposal(proposal),
+            OverlordMsg::AggregatedVote(prevote_qc),
+            OverlordMsg::SignedVote(prevote),
+            OverlordMsg::SignedVote(precommit),
+        ]);
+
+        // Nothing is replayed once the node restarts at another epoch.
+        assert!(replay_msgs(&wal, 2).unwrap().is_empty());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,279 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_timer::Delay;
+use log::{error, info};
+use parking_lot::RwLock;
+
+use common_merkle::Merkle;
+use protocol::fixed_codec::ProtocolFixedCodec;
+use protocol::traits::executor::EpochReward;
+use protocol::traits::{
+    ConsensusAdapter, ConsensusMonitor, ConsensusStatus, ConsensusStep, Context,
+    CurrentConsensusStatus, Gossip, MessageCodec, NodeInfo, Priority, Rpc,
+};
+use protocol::types::{Address, Epoch, EpochHeader, Hash, Proof, UserAddress};
+use protocol::ProtocolResult;
+
+use crate::engine::time_now;
+use crate::util::receipt_root;
+use crate::ConsensusError;
+
+/// The interval to check the mempool when sealing instantly.
+const DEV_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A single node consensus for development. The node seals epochs by itself
+/// without voting, either on a timer or as soon as there are transactions in
+/// the mempool.
+///
+/// The sealed epochs are executed before sealing, so the header of an epoch
+/// carries its own execution results.
+pub struct DevConsensus<Adapter> {
+    current_consensus_status: RwLock<CurrentConsensusStatus>,
+    node_info:                NodeInfo,
+    adapter:                  Arc<Adapter>,
+    interval:                 Option<u64>,
+}
+
+impl<Adapter: ConsensusAdapter + 'static> DevConsensus<Adapter> {
+    /// Seal an epoch every `interval` milliseconds even if it is empty, or
+    /// seal instantly when transactions arrive if it is `None`.
+    pub fn new(
+        current_consensus_status: CurrentConsensusStatus,
+        node_info: NodeInfo,
+        adapter: Arc<Adapter>,
+        interval: Option<u64>,
+    ) -> Self {
+        DevConsensus {
+            current_consensus_status: RwLock::new(current_consensus_status),
+            node_info,
+            adapter,
+            interval,
+        }
+    }
+
+    pub async fn run(&self) -> ProtocolResult<()> {
+        match self.interval {
+            Some(interval) => info!("consensus: development mode, seal every {}ms", interval),
+            None => info!("consensus: development mode, seal instantly"),
+        }
+
+        loop {
+            let delay = self
+                .interval
+                .map_or(DEV_POLL_INTERVAL, Duration::from_millis);
+            Delay::new(delay).await;
+
+            if let Err(e) = self.seal(Context::new()).await {
+                error!("consensus: development mode seal epoch failed {:?}", e);
+            }
+        }
+    }
+
+    /// Seal an epoch with the transactions in the mempool. Return whether an
+    /// epoch is sealed.
+    pub async fn seal(&self, ctx: Context) -> ProtocolResult<bool> {
+        let status = { self.current_consensus_status.read().clone() };
+        let epoch_id = status.epoch_id;
+
+        let (ordered_tx_hashes, _) = self
+            .adapter
+            .get_txs_from_mempool(ctx.clone(), epoch_id, status.cycles_limit)
+            .await?
+            .clap();
+        if ordered_tx_hashes.is_empty() && self.interval.is_none() {
+            return Ok(false);
+        }
+
+        let txs = self
+            .adapter
+            .get_full_txs(ctx.clone(), ordered_tx_hashes.clone())
+            .await?;
+
+        // There are no votes, the proof only links the previous epoch.
+        let proof = Proof {
+            epoch_id:   epoch_id - 1,
+            round:      0,
+            epoch_hash: status.prev_hash.clone(),
+            signature:  Bytes::new(),
+            bitmap:     Bytes::new(),
+        };
+        let reward = EpochReward {
+            schedule: status.reward_schedule.clone(),
+            signers:  Vec::new(),
+        };
+        let exec_resp = self
+            .adapter
+            .execute(
+                self.node_info.clone(),
+                status.state_root.clone(),
+                epoch_id,
+                status.cycles_price,
+                Address::User(self.node_info.self_address.clone()),
+                txs.clone(),
+                reward,
+            )
+            .await?;
+
+        // The validator set scheduled by the governance contract takes effect at
+        // the next epoch, the same as the overlord consensus.
+        let validator_set = self
+            .adapter
+            .get_last_validators(
+                self.node_info.clone(),
+                exec_resp.state_root.clone(),
+                epoch_id + 1,
+            )
+            .await?;
+
+        let order_root = Merkle::from_hashes(ordered_tx_hashes.clone())
+            .get_root_hash()
+            .unwrap_or_else(Hash::from_empty);
+        let header = EpochHeader {
+            chain_id:          self.node_info.chain_id.clone(),
+            pre_hash:          status.prev_hash.clone(),
+            epoch_id:          status.epoch_id,
+            timestamp:         time_now(),
+            logs_bloom:        status.logs_bloom,
+            order_root:        order_root.clone(),
+            confirm_root:      vec![order_root],
+            exec_epoch_id:     epoch_id,
+            state_root:        exec_resp.state_root.clone(),
+            receipt_root:      vec![receipt_root(&exec_resp.receipts)?],
+            cycles_used:       status.cycles_used,
+            proposer:          self.node_info.self_address.clone(),
+            proof:             proof.clone(),
+            validator_version: status.validator_version,
+            validators:        status.validators.clone(),
+        };
+        let epoch = Epoch {
+            header,
+            ordered_tx_hashes,
+        };
+
+        self.adapter
+            .save_receipts(ctx.clone(), exec_resp.receipts)
+            .await?;
+        if !exec_resp.traces.is_empty() {
+            self.adapter
+                .save_traces(ctx.clone(), exec_resp.traces)
+                .await?;
+        }
+        self.adapter.save_signed_txs(ctx.clone(), txs).await?;
+        self.adapter.save_epoch(ctx.clone(), epoch.clone()).await?;
+        self.adapter.save_proof(ctx.clone(), proof.clone()).await?;
+        self.adapter
+            .flush_mempool(ctx, epoch.ordered_tx_hashes.clone())
+            .await?;
+
+        {
+            let mut current_consensus_status = self.current_consensus_status.write();
+            current_consensus_status.epoch_id = epoch_id + 1;
+            current_consensus_status.prev_hash = Hash::digest(epoch.encode_fixed()?);
+            current_consensus_status.proof = proof;
+            current_consensus_status.exec_epoch_id = epoch_id;
+            current_consensus_status.state_root = epoch.header.state_root.clone();
+
+            if let Some(validator_set) = validator_set {
+                if validator_set.version != current_consensus_status.validator_version {
+                    info!(
+                        "consensus: validator set of version {} takes effect at epoch {}",
+                        validator_set.version,
+                        epoch_id + 1
+                    );
+                    current_consensus_status.validator_version = validator_set.version;
+                    current_consensus_status.validators = validator_set.validators;
+                }
+            }
+        }
+
+        info!(
+            "consensus: development mode seal epoch {} with {} transactions",
+            epoch_id,
+            epoch.ordered_tx_hashes.len()
+        );
+        Ok(true)
+    }
+}
+
+/// There are no rounds or votes in the development mode.
+impl<Adapter: ConsensusAdapter + 'static> ConsensusMonitor for DevConsensus<Adapter> {
+    fn consensus_status(&self) -> ConsensusStatus {
+        ConsensusStatus {
+            epoch_id: self.current_consensus_status.read().epoch_id,
+            round:    0,
+            step:     ConsensusStep::Propose,
+            proposal: None,
+            locked:   None,
+            votes:    Vec::new(),
+            syncing:  false,
+        }
+    }
+}
+
+/// The network of the development mode. There are no peers, so broadcasts are
+/// dropped and calls fail.
+#[derive(Clone, Debug, Default)]
+pub struct DevNetwork;
+
+#[async_trait]
+impl Gossip for DevNetwork {
+    async fn broadcast<M>(
+        &self,
+        _cx: Context,
+        _end: &str,
+        _msg: M,
+        _p: Priority,
+    ) -> ProtocolResult<()>
+    where
+        M: MessageCodec,
+    {
+        Ok(())
+    }
+
+    async fn users_cast<M>(
+        &self,
+        _cx: Context,
+        _end: &str,
+        _users: Vec<UserAddress>,
+        _msg: M,
+        _p: Priority,
+    ) -> ProtocolResult<()>
+    where
+        M: MessageCodec,
+    {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Rpc for DevNetwork {
+    async fn call<M, R>(
+        &self,
+        _ctx: Context,
+        end: &str,
+        _msg: M,
+        _pri: Priority,
+    ) -> ProtocolResult<R>
+    where
+        M: MessageCodec,
+        R: MessageCodec,
+    {
+        Err(ConsensusError::Other(format!("no peer serves {} in development mode", end)).into())
+    }
+
+    async fn response<M>(
+        &self,
+        _cx: Context,
+        _end: &str,
+        _msg: M,
+        _p: Priority,
+    ) -> ProtocolResult<()>
+    where
+        M: MessageCodec,
+    {
+        Ok(())
+    }
+}
@@ -1,44 +1,71 @@
-use std::collections::HashSet;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{error::Error, sync::Arc};
 
 use async_trait::async_trait;
 use bincode::serialize;
 use bytes::Bytes;
+use futures::channel::mpsc::Sender;
 use futures::lock::Mutex;
-use overlord::types::{Commit, Node, OverlordMsg, Status};
-use overlord::Consensus as Engine;
+use futures::SinkExt;
+use log::{info, warn};
+use overlord::types::{
+    AggregatedVote, Commit, Node, OverlordMsg, SignedProposal, SignedVote, Status, VoteType,
+};
+use overlord::{Consensus as Engine, Crypto};
 use parking_lot::RwLock;
 use rlp::Encodable;
 
 use common_merkle::Merkle;
 use protocol::fixed_codec::ProtocolFixedCodec;
 use protocol::traits::{
-    executor::ExecutorExecResp, ConsensusAdapter, Context, CurrentConsensusStatus, MessageTarget,
-    NodeInfo,
+    executor::{EpochReward, ExecutorExecResp},
+    ConsensusAdapter, Context, CurrentConsensusStatus, MessageTarget, NodeInfo,
 };
 use protocol::types::{
-    Address, Epoch, EpochHeader, Hash, MerkleRoot, Pill, Proof, SignedTransaction, UserAddress,
-    Validator,
+    Address, Epoch, EpochHeader, Evidence, Hash, MerkleRoot, Pill, Proof, SignedTransaction,
+    UserAddress, Validator, ValidatorSet,
 };
 use protocol::{ProtocolError, ProtocolResult};
 
+use crate::evidence::{verify_evidence, EvidencePool, SignedMessage};
+use crate::execution::{ExecStatus, ExecTask, ExecutedInfo, MAX_EXEC_LAG};
 use crate::fixed_types::{FixedEpochID, FixedPill, FixedSignedTxs};
 use crate::message::{
     END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_RICH_EPOCH_ID, END_GOSSIP_SIGNED_PROPOSAL,
     END_GOSSIP_SIGNED_VOTE, RPC_SYNC_PULL,
 };
+use crate::monitor::StatusMonitor;
+use crate::util::{check_qc, extract_voters, receipt_root, OverlordCrypto};
+use crate::wal::{ConsensusWal, WalMessage, WalMsgType};
 use crate::ConsensusError;
 
+/// The max seconds that the timestamp of a proposed epoch can be ahead of the
+/// local time.
+const MAX_TIMESTAMP_DRIFT: u64 = 10;
+/// The max duration to wait for the executor when proposing or checking an
+/// epoch.
+const EXEC_WAIT_TIMEOUT: Duration = Duration::from_secs(3);
+/// The signed messages are recorded for evidences only if their epoch is at
+/// most this far from the current epoch.
+const MAX_EVIDENCE_EPOCH_DISTANCE: u64 = 1;
+
 /// validator is for create new epoch, and authority is for build overlord
 /// status.
 pub struct ConsensusEngine<Adapter> {
     current_consensus_status: Arc<RwLock<CurrentConsensusStatus>>,
     node_info:                NodeInfo,
     exemption_hash:           RwLock<HashSet<Bytes>>,
-
-    adapter:  Arc<Adapter>,
-    pub lock: Mutex<()>,
+    proposers:                RwLock<HashMap<Bytes, HashSet<Bytes>>>,
+    evidences:                EvidencePool,
+    monitor:                  StatusMonitor,
+    crypto:                   OverlordCrypto,
+
+    adapter:     Arc<Adapter>,
+    wal:         Arc<ConsensusWal>,
+    exec_status: Arc<ExecStatus>,
+    exec_sender: Sender<ExecTask>,
+    pub lock:    Mutex<()>,
 }
 
 #[async_trait]
@@ -61,6 +88,31 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
         if current_consensus_status.epoch_id != epoch_id {
             return Err(ProtocolError::from(ConsensusError::MissingEpochHeader(epoch_id)).into());
         }
+
+        // Do not propose until the execution catches up, and carry the results
+        // of the epochs executed after the ones carried by previous headers.
+        self.exec_status
+            .wait(epoch_id.saturating_sub(MAX_EXEC_LAG + 1), EXEC_WAIT_TIMEOUT)
+            .await?;
+        let executed = self
+            .exec_status
+            .results(current_consensus_status.exec_epoch_id, epoch_id - 1);
+        let (last_executed, exec_state_root) = match executed.last() {
+            Some(info) => (info.epoch_id, info.state_root.clone()),
+            None => (
+                current_consensus_status.exec_epoch_id,
+                current_consensus_status.state_root.clone(),
+            ),
+        };
+        let confirm_roots = executed
+            .iter()
+            .map(|info| info.order_root.clone())
+            .collect::<Vec<_>>();
+        let receipt_roots = executed
+            .iter()
+            .map(|info| info.receipt_root.clone())
+            .collect::<Vec<_>>();
+
         let tmp_epoch_id = epoch_id;
         let order_root = Merkle::from_hashes(ordered_tx_hashes.clone()).get_root_hash();
         let header = EpochHeader {
@@ -70,13 +122,14 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
             timestamp:         time_now(),
             logs_bloom:        current_consensus_status.logs_bloom,
             order_root:        order_root.unwrap_or_else(Hash::from_empty),
-            confirm_root:      vec![current_consensus_status.order_root.clone()],
-            state_root:        current_consensus_status.state_root.clone(),
-            receipt_root:      current_consensus_status.receipt_root.clone(),
+            confirm_root:      confirm_roots,
+            exec_epoch_id:     last_executed,
+            state_root:        exec_state_root,
+            receipt_root:      receipt_roots,
             cycles_used:       current_consensus_status.cycles_used,
             proposer:          self.node_info.self_address.clone(),
             proof:             current_consensus_status.proof.clone(),
-            validator_version: 0u64,
+            validator_version: current_consensus_status.validator_version,
             validators:        current_consensus_status.validators.clone(),
         };
         let epoch = Epoch {
@@ -91,7 +144,9 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
         let fixed_pill = FixedPill {
             inner: pill.clone(),
         };
-        let hash = Hash::digest(pill.encode_fixed()?).as_bytes();
+        // The hash of the epoch is voted, so that the proof can be checked
+        // against the epoch without the propose hashes.
+        let hash = Hash::digest(pill.epoch.encode_fixed()?).as_bytes();
         let mut set = self.exemption_hash.write();
         set.insert(hash.clone());
 
@@ -101,7 +156,7 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
     async fn check_epoch(
         &self,
         ctx: Context,
-        _epoch_id: u64,
+        epoch_id: u64,
         hash: Bytes,
         epoch: FixedPill,
     ) -> Result<FixedSignedTxs, Box<dyn Error + Send>> {
@@ -113,6 +168,8 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
         // If the epoch is proposed by self, it does not need to check. Get full signed
         // transactions directly.
         if !exemption {
+            self.check_header(ctx.clone(), epoch_id, &hash, &epoch.inner.epoch)
+                .await?;
             self.adapter
                 .sync_txs(ctx.clone(), epoch.get_propose_hashes())
                 .await?;
@@ -168,18 +225,6 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
             .flush_mempool(ctx.clone(), pill.epoch.ordered_tx_hashes.clone())
             .await?;
 
-        // Execute transactions
-        let status = self.current_consensus_status.read().clone();
-        let coinbase = Address::User(pill.epoch.header.proposer.clone());
-        let exec_resp = self
-            .exec(
-                status.state_root.clone(),
-                epoch_id,
-                coinbase,
-                full_txs.clone(),
-            )
-            .await?;
-
         // Broadcast rich epoch ID
         let msg = serialize(&FixedEpochID::new(epoch_id + 1)).map_err(|_| {
             ProtocolError::from(ConsensusError::Other(
@@ -187,8 +232,10 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
             ))
         })?;
 
-        self.update_status(epoch_id, pill.epoch, proof, exec_resp, full_txs)
-            .await?;
+        self.commit_epoch(pill.epoch, proof, full_txs).await?;
+        self.wal.commit(epoch_id).await?;
+        self.proposers.write().clear();
+        self.evidences.prune(epoch_id);
 
         self.adapter
             .transmit(
@@ -210,6 +257,7 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
     }
 
     /// Only signed proposal and aggregated vote will be broadcast to others.
+    /// The signed proposal is written to the WAL before it is sent.
     async fn broadcast_to_other(
         &self,
         ctx: Context,
@@ -217,11 +265,18 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
     ) -> Result<(), Box<dyn Error + Send>> {
         let (end, msg) = match msg {
             OverlordMsg::SignedProposal(sp) => {
-                let bytes = sp.rlp_bytes();
-                (END_GOSSIP_SIGNED_PROPOSAL, bytes)
+                let proposal = &sp.proposal;
+                self.monitor
+                    .on_proposal(proposal.epoch_id, proposal.round, &proposal.epoch_hash);
+                let saved = self.wal.save_signed(proposal_to_wal(&sp)).await?;
+                (END_GOSSIP_SIGNED_PROPOSAL, saved.msg.to_vec())
             }
 
             OverlordMsg::AggregatedVote(av) => {
+                self.monitor.on_qc(&av);
+                if let Some(qc) = qc_to_wal(&av) {
+                    self.wal.cache_qc(qc);
+                }
                 let bytes = av.rlp_bytes();
                 (END_GOSSIP_AGGREGATED_VOTE, bytes)
             }
@@ -234,7 +289,8 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
         Ok(())
     }
 
-    /// Only signed vote will be transmit to the relayer.
+    /// Only signed vote will be transmit to the relayer. The signed vote is
+    /// written to the WAL before it is sent.
     async fn transmit_to_relayer(
         &self,
         ctx: Context,
@@ -242,7 +298,11 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
         msg: OverlordMsg<FixedPill>,
     ) -> Result<(), Box<dyn Error + Send>> {
         let msg = match msg {
-            OverlordMsg::SignedVote(sv) => sv.rlp_bytes(),
+            OverlordMsg::SignedVote(sv) => {
+                self.monitor
+                    .on_vote(&self.node_info.self_address, &sv.vote, true);
+                self.wal.save_signed(vote_to_wal(&sv)).await?.msg.to_vec()
+            }
             _ => unreachable!(),
         };
 
@@ -264,8 +324,10 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill, FixedSignedTxs>
         ctx: Context,
         epoch_id: u64,
     ) -> Result<Vec<Node>, Box<dyn Error + Send>> {
-        let validators = self.adapter.get_last_validators(ctx, epoch_id).await?;
-        let mut res = validators
+        let epoch = self.adapter.get_epoch_by_id(ctx, epoch_id).await?;
+        let mut res = epoch
+            .header
+            .validators
             .into_iter()
             .map(|v| Node {
                 address:        v.address.as_bytes(),
@@ -285,11 +347,24 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
         current_consensus_status: Arc<RwLock<CurrentConsensusStatus>>,
         node_info: NodeInfo,
         adapter: Arc<Adapter>,
+        crypto: OverlordCrypto,
+        wal: Arc<ConsensusWal>,
+        exec_status: Arc<ExecStatus>,
+        exec_sender: Sender<ExecTask>,
     ) -> Self {
+        let monitor = StatusMonitor::new(current_consensus_status.read().epoch_id);
+
         Self {
             current_consensus_status,
             node_info,
             exemption_hash: RwLock::new(HashSet::new()),
+            proposers: RwLock::new(HashMap::new()),
+            evidences: EvidencePool::new(),
+            monitor,
+            crypto,
+            wal,
+            exec_status,
+            exec_sender,
             adapter,
             lock: Mutex::new(()),
         }
@@ -299,8 +374,19 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
         self.adapter.get_current_epoch_id(ctx).await
     }
 
-    pub async fn pull_epoch(&self, ctx: Context, epoch_id: u64) -> ProtocolResult<Epoch> {
-        self.adapter.pull_epoch(ctx, epoch_id, RPC_SYNC_PULL).await
+    pub async fn pull_epochs(
+        &self,
+        ctx: Context,
+        begin: u64,
+        count: u64,
+    ) -> ProtocolResult<Vec<Epoch>> {
+        self.adapter
+            .pull_epochs(ctx, begin, count, RPC_SYNC_PULL)
+            .await
+    }
+
+    pub async fn pull_proof(&self, ctx: Context, epoch_id: u64) -> ProtocolResult<Proof> {
+        self.adapter.pull_proof(ctx, epoch_id, RPC_SYNC_PULL).await
     }
 
     pub async fn pull_txs(
@@ -315,14 +401,33 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
         self.adapter.get_epoch_by_id(ctx, epoch_id).await
     }
 
+    pub async fn get_signed_txs(
+        &self,
+        ctx: Context,
+        hashes: Vec<Hash>,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        self.adapter.get_signed_txs(ctx, hashes).await
+    }
+
+    pub fn exec_status(&self) -> &ExecStatus {
+        &self.exec_status
+    }
+
+    /// The `prev_proof` is the proof of previous epoch carried by the epoch
+    /// header, its signers share the epoch rewards with the proposer.
     pub async fn exec(
         &self,
         state_root: MerkleRoot,
         epoch_id: u64,
         address: Address,
+        prev_proof: &Proof,
         txs: Vec<SignedTransaction>,
     ) -> ProtocolResult<ExecutorExecResp> {
         let status = { self.current_consensus_status.read().clone() };
+        let reward = EpochReward {
+            schedule: status.reward_schedule.clone(),
+            signers:  self.get_signers(prev_proof).await?,
+        };
 
         self.adapter
             .execute(
@@ -332,31 +437,80 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
                 status.cycles_price,
                 address,
                 txs,
+                reward,
             )
             .await
     }
 
-    /// **TODO:** parallelism
-    /// After get the signed transactions:
-    /// 1. Execute the signed transactions.
-    /// 2. Save the signed transactions.
-    /// 3. Save the latest proof.
-    /// 4. Save the new epoch.
-    /// 5. Save the receipt.
-    pub async fn update_status(
+    /// Execute the epoch on the given state, save the receipts and return the
+    /// roots after executing it.
+    pub async fn execute_epoch(
         &self,
-        epoch_id: u64,
+        state_root: MerkleRoot,
         epoch: Epoch,
-        proof: Proof,
-        exec_resp: ExecutorExecResp,
         txs: Vec<SignedTransaction>,
-    ) -> ProtocolResult<()> {
+    ) -> ProtocolResult<ExecutedInfo> {
+        let epoch_id = epoch.header.epoch_id;
+        let exec_resp = self
+            .exec(
+                state_root,
+                epoch_id,
+                Address::User(epoch.header.proposer.clone()),
+                &epoch.header.proof,
+                txs,
+            )
+            .await?;
+
         // Save receipts
         self.adapter
             .save_receipts(Context::new(), exec_resp.receipts.clone())
             .await?;
+        // Save the state diffs if tracing is enabled
+        if !exec_resp.traces.is_empty() {
+            self.adapter
+                .save_traces(Context::new(), exec_resp.traces.clone())
+                .await?;
+        }
+
+        Ok(ExecutedInfo {
+            epoch_id,
+            order_root: epoch.header.order_root,
+            receipt_root: receipt_root(&exec_resp.receipts)?,
+            state_root: exec_resp.state_root,
+        })
+    }
+
+    /// Get the signers of the proof from the validators of the proved epoch.
+    async fn get_signers(&self, proof: &Proof) -> ProtocolResult<Vec<Validator>> {
+        if proof.bitmap.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let epoch = self
+            .adapter
+            .get_epoch_by_id(Context::new(), proof.epoch_id)
+            .await?;
+        Ok(extract_voters(&epoch.header.validators, &proof.bitmap))
+    }
+
+    /// After get the signed transactions:
+    /// 1. Save the signed transactions.
+    /// 2. Save the new epoch.
+    /// 3. Update the status with the execution results carried by the header.
+    /// 4. Update the validators if a new validator set takes effect.
+    /// 5. Send the epoch to the executor, wait if the execution lags too far.
+    pub async fn commit_epoch(
+        &self,
+        epoch: Epoch,
+        proof: Proof,
+        txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        let epoch_id = epoch.header.epoch_id;
+
         // Save signed transactions
-        self.adapter.save_signed_txs(Context::new(), txs).await?;
+        self.adapter
+            .save_signed_txs(Context::new(), txs.clone())
+            .await?;
 
         // Save the epoch.
         self.adapter
@@ -364,41 +518,283 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
             .await?;
 
         let prev_hash = Hash::digest(epoch.encode_fixed()?);
+        let header = &epoch.header;
+
+        // The governance contract may schedule a new validator set. The state
+        // carried by the header is used so that all nodes agree on it.
+        let validator_set = self
+            .adapter
+            .get_last_validators(
+                self.node_info.clone(),
+                header.state_root.clone(),
+                epoch_id + 1,
+            )
+            .await?;
+
         {
             let mut current_consensus_status = self.current_consensus_status.write();
             current_consensus_status.epoch_id = epoch_id + 1;
             current_consensus_status.prev_hash = prev_hash;
             current_consensus_status.proof = proof;
+            current_consensus_status.exec_epoch_id = header.exec_epoch_id;
+            current_consensus_status.state_root = header.state_root.clone();
 
-            // Update state root
-            current_consensus_status.state_root = exec_resp.state_root.clone();
-
-            // Update order root
-            let ordered_root = Merkle::from_hashes(epoch.ordered_tx_hashes.clone())
-                .get_root_hash()
-                .unwrap_or_else(Hash::from_empty);
-            current_consensus_status.order_root = ordered_root.clone();
-
-            // Update confirm root
-            current_consensus_status.confirm_root = vec![ordered_root];
-
-            // Update receipt root
-            current_consensus_status.receipt_root = {
-                let receipt_root = Merkle::from_hashes(
-                    exec_resp
-                        .receipts
-                        .iter()
-                        .map(|receipt| Hash::digest(receipt.to_owned().encode_fixed().unwrap()))
-                        .collect::<Vec<_>>(),
-                )
-                .get_root_hash()
-                .unwrap_or_else(Hash::from_empty);
-                vec![receipt_root]
-            };
+            if let Some(validator_set) = validator_set {
+                self.update_validators(&mut current_consensus_status, validator_set, epoch_id + 1);
+            }
         }
+        self.exec_status.prune(header.exec_epoch_id);
+        self.monitor.on_commit(epoch_id);
+
+        self.exec_sender
+            .clone()
+            .send(ExecTask { epoch, txs })
+            .await
+            .map_err(|_| ConsensusError::Other("executor stopped".to_string()))?;
         Ok(())
     }
 
+    /// Record the signer of a received proposal if the signature is valid, it
+    /// must be the proposer of the epoch header. Return the evidence if the
+    /// signer has proposed another epoch in the same round.
+    pub fn record_proposal(&self, signed_proposal: &SignedProposal<FixedPill>) -> Option<Evidence> {
+        let message = SignedMessage::from_proposal(signed_proposal);
+        if !self.near_current_epoch(message.epoch_id) {
+            return None;
+        }
+        let signer = self
+            .crypto
+            .verify_signature(message.signature.clone(), message.hash.clone())
+            .ok()?;
+
+        self.proposers
+            .write()
+            .entry(signed_proposal.proposal.epoch_hash.clone())
+            .or_insert_with(HashSet::new)
+            .insert(signer.clone());
+        let proposal = &signed_proposal.proposal;
+        self.monitor
+            .on_proposal(proposal.epoch_id, proposal.round, &proposal.epoch_hash);
+
+        let signer = UserAddress::from_bytes(signer).ok()?;
+        self.evidences
+            .record(signer, &message, Bytes::from(signed_proposal.rlp_bytes()))
+    }
+
+    /// Return the evidence if the signer of a received vote has signed another
+    /// vote of the same type in the same round.
+    pub fn record_vote(&self, signed_vote: &SignedVote) -> Option<Evidence> {
+        let message = SignedMessage::from_vote(signed_vote);
+        if !self.near_current_epoch(message.epoch_id) {
+            return None;
+        }
+        let signer = self
+            .crypto
+            .verify_signature(message.signature.clone(), message.hash.clone())
+            .ok()?;
+
+        let signer = UserAddress::from_bytes(signer).ok()?;
+        self.monitor.on_vote(&signer, &signed_vote.vote, false);
+        self.evidences
+            .record(signer, &message, Bytes::from(signed_vote.rlp_bytes()))
+    }
+
+    /// Record a received QC after verifying it against the current
+    /// validators, the QCs built by this node are recorded when they are
+    /// broadcast.
+    pub fn record_qc(&self, aggregated_vote: &AggregatedVote) -> ProtocolResult<()> {
+        check_qc(aggregated_vote, &self.get_current_validators())?;
+        self.monitor.on_qc(aggregated_vote);
+        Ok(())
+    }
+
+    pub fn monitor(&self) -> &StatusMonitor {
+        &self.monitor
+    }
+
+    /// Save the evidence after verifying it against the current validators,
+    /// an invalid evidence is dropped.
+    pub async fn save_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        if !self.near_current_epoch(evidence.epoch_id) {
+            return Ok(());
+        }
+        if let Err(e) = verify_evidence(&evidence, &self.get_current_validators()) {
+            warn!("consensus: drop evidence {:?}", e);
+            return Ok(());
+        }
+
+        warn!(
+            "consensus: validator {:?} signed two {:?} messages at epoch {} round {}",
+            evidence.validator, evidence.evidence_type, evidence.epoch_id, evidence.round
+        );
+        self.adapter.save_evidence(ctx, evidence).await
+    }
+
+    // The messages of far epochs are never checked by overlord, keeping them
+    // would let a peer grow the evidence pool and the storage without limit.
+    fn near_current_epoch(&self, epoch_id: u64) -> bool {
+        let current = self.current_consensus_status.read().epoch_id;
+        epoch_id + MAX_EVIDENCE_EPOCH_DISTANCE >= current
+            && epoch_id <= current + MAX_EVIDENCE_EPOCH_DISTANCE
+    }
+
+    /// Check the proposed epoch header against the local status.
+    async fn check_header(
+        &self,
+        ctx: Context,
+        epoch_id: u64,
+        hash: &Bytes,
+        epoch: &Epoch,
+    ) -> ProtocolResult<()> {
+        let header = &epoch.header;
+        let status = { self.current_consensus_status.read().clone() };
+
+        if &Hash::digest(epoch.encode_fixed()?).as_bytes() != hash {
+            return Err(ConsensusError::InvalidEpochHash { epoch_id }.into());
+        }
+        if header.chain_id != self.node_info.chain_id {
+            return Err(ConsensusError::InvalidChainId {
+                epoch_id,
+                chain_id: header.chain_id.clone(),
+            }
+            .into());
+        }
+        if header.epoch_id != epoch_id || header.epoch_id != status.epoch_id {
+            return Err(ConsensusError::InvalidEpochId {
+                expect: status.epoch_id,
+                actual: header.epoch_id,
+            }
+            .into());
+        }
+        if header.pre_hash != status.prev_hash {
+            return Err(ConsensusError::InvalidPrevHash {
+                epoch_id,
+                pre_hash: header.pre_hash.clone(),
+            }
+            .into());
+        }
+
+        let prev_epoch = self.adapter.get_epoch_by_id(ctx, epoch_id - 1).await?;
+        if header.timestamp < prev_epoch.header.timestamp
+            || header.timestamp > time_now() + MAX_TIMESTAMP_DRIFT
+        {
+            return Err(ConsensusError::InvalidTimestamp {
+                epoch_id,
+                timestamp: header.timestamp,
+            }
+            .into());
+        }
+
+        let order_root = Merkle::from_hashes(epoch.ordered_tx_hashes.clone())
+            .get_root_hash()
+            .unwrap_or_else(Hash::from_empty);
+        if header.order_root != order_root {
+            return Err(ConsensusError::InvalidOrderRoot {
+                epoch_id,
+                order_root: header.order_root.clone(),
+            }
+            .into());
+        }
+        self.check_exec_roots(epoch_id, header, &status).await?;
+        if header.validator_version != status.validator_version
+            || header.validators != status.validators
+        {
+            return Err(ConsensusError::InvalidValidators { epoch_id }.into());
+        }
+
+        let signed = self.proposers.read().get(hash).map_or(false, |signers| {
+            signers.contains(&header.proposer.as_bytes())
+        });
+        if !signed {
+            return Err(ConsensusError::InvalidProposer {
+                epoch_id,
+                proposer: header.proposer.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Check the execution results carried by the header against the local
+    /// execution.
+    async fn check_exec_roots(
+        &self,
+        epoch_id: u64,
+        header: &EpochHeader,
+        status: &CurrentConsensusStatus,
+    ) -> ProtocolResult<()> {
+        let exec_epoch_id = header.exec_epoch_id;
+        if exec_epoch_id < status.exec_epoch_id
+            || exec_epoch_id >= epoch_id
+            || exec_epoch_id + MAX_EXEC_LAG + 1 < epoch_id
+        {
+            return Err(ConsensusError::InvalidExecEpochId {
+                epoch_id,
+                exec_epoch_id,
+            }
+            .into());
+        }
+
+        self.exec_status
+            .wait(exec_epoch_id, EXEC_WAIT_TIMEOUT)
+            .await?;
+        let executed = self
+            .exec_status
+            .results(status.exec_epoch_id, exec_epoch_id);
+        if executed.len() as u64 != exec_epoch_id - status.exec_epoch_id {
+            return Err(ConsensusError::Other(format!(
+                "missing execution results until epoch {}",
+                exec_epoch_id
+            ))
+            .into());
+        }
+
+        let confirm_root = executed
+            .iter()
+            .map(|info| info.order_root.clone())
+            .collect::<Vec<_>>();
+        if header.confirm_root != confirm_root {
+            return Err(ConsensusError::InvalidConfirmRoot { epoch_id }.into());
+        }
+
+        let state_root = executed
+            .last()
+            .map_or_else(|| status.state_root.clone(), |info| info.state_root.clone());
+        if header.state_root != state_root {
+            return Err(ConsensusError::InvalidStateRoot {
+                epoch_id,
+                state_root: header.state_root.clone(),
+            }
+            .into());
+        }
+
+        let receipt_root = executed
+            .iter()
+            .map(|info| info.receipt_root.clone())
+            .collect::<Vec<_>>();
+        if header.receipt_root != receipt_root {
+            return Err(ConsensusError::InvalidReceiptRoot { epoch_id }.into());
+        }
+        Ok(())
+    }
+
+    fn update_validators(
+        &self,
+        current_consensus_status: &mut CurrentConsensusStatus,
+        validator_set: ValidatorSet,
+        epoch_id: u64,
+    ) {
+        if validator_set.version != current_consensus_status.validator_version {
+            info!(
+                "consensus: validator set of version {} takes effect at epoch {}",
+                validator_set.version, epoch_id
+            );
+            self.crypto.update_validators(&validator_set.validators);
+            current_consensus_status.validator_version = validator_set.version;
+            current_consensus_status.validators = validator_set.validators;
+        }
+    }
+
     pub async fn save_proof(&self, ctx: Context, proof: Proof) -> ProtocolResult<()> {
         self.adapter.save_proof(ctx, proof).await
     }
@@ -408,12 +804,55 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
         current_consensus_status.consensus_interval
     }
 
+    pub fn get_current_validators(&self) -> Vec<Validator> {
+        self.current_consensus_status.read().validators.clone()
+    }
+
     pub fn get_current_authority_list(&self) -> Vec<Node> {
         let current_consensus_status = self.current_consensus_status.read();
         covert_to_overlord_authority(&current_consensus_status.validators)
     }
 }
 
+pub fn proposal_to_wal(sp: &SignedProposal<FixedPill>) -> WalMessage {
+    WalMessage {
+        epoch_id: sp.proposal.epoch_id,
+        round:    sp.proposal.round,
+        msg_type: WalMsgType::Proposal,
+        hash:     sp.proposal.epoch_hash.clone(),
+        msg:      Bytes::from(sp.rlp_bytes()),
+    }
+}
+
+pub fn vote_to_wal(sv: &SignedVote) -> WalMessage {
+    let msg_type = match sv.vote.vote_type {
+        VoteType::Prevote => WalMsgType::Prevote,
+        VoteType::Precommit => WalMsgType::Precommit,
+    };
+
+    WalMessage {
+        epoch_id: sv.vote.epoch_id,
+        round: sv.vote.round,
+        msg_type,
+        hash: sv.vote.epoch_hash.clone(),
+        msg: Bytes::from(sv.rlp_bytes()),
+    }
+}
+
+/// Only the prevote QCs are kept in the WAL, overlord locks on them.
+pub fn qc_to_wal(av: &AggregatedVote) -> Option<WalMessage> {
+    match av.vote_type {
+        VoteType::Prevote => Some(WalMessage {
+            epoch_id: av.epoch_id,
+            round:    av.round,
+            msg_type: WalMsgType::PrevoteQC,
+            hash:     av.epoch_hash.clone(),
+            msg:      Bytes::from(av.rlp_bytes()),
+        }),
+        VoteType::Precommit => None,
+    }
+}
+
 fn covert_to_overlord_authority(validators: &[Validator]) -> Vec<Node> {
     let mut authority = validators
         .iter()
@@ -427,7 +866,7 @@ fn covert_to_overlord_authority(validators: &[Validator]) -> Vec<Node> {
     authority
 }
 
-fn time_now() -> u64 {
+pub fn time_now() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
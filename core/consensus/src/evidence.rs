@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use common_crypto::{Crypto, BLS12381};
+use overlord::types::{SignedProposal, SignedVote, VoteType};
+use parking_lot::RwLock;
+
+use protocol::types::{Evidence, EvidenceType, Hash, UserAddress, Validator};
+use protocol::ProtocolResult;
+
+use crate::fixed_types::FixedPill;
+use crate::util::split_signature;
+use crate::ConsensusError;
+
+/// A signed proposal or vote of overlord.
+#[derive(Clone, Debug)]
+pub struct SignedMessage {
+    pub evidence_type: EvidenceType,
+    pub epoch_id: u64,
+    pub round: u64,
+    /// The hash signed by the signature.
+    pub hash: Bytes,
+    pub signature: Bytes,
+}
+
+impl SignedMessage {
+    /// Decode an rlp encoded signed proposal or vote of the type.
+    pub fn decode(evidence_type: EvidenceType, msg: &[u8]) -> ProtocolResult<Self> {
+        let invalid = || ConsensusError::Other(format!("invalid signed {:?}", evidence_type));
+
+        let message = if evidence_type == EvidenceType::Proposal {
+            let signed_proposal: SignedProposal<FixedPill> =
+                rlp::decode(msg).map_err(|_| invalid())?;
+            SignedMessage::from_proposal(&signed_proposal)
+        } else {
+            let signed_vote: SignedVote = rlp::decode(msg).map_err(|_| invalid())?;
+            SignedMessage::from_vote(&signed_vote)
+        };
+
+        if message.evidence_type != evidence_type {
+            return Err(invalid().into());
+        }
+        Ok(message)
+    }
+
+    pub fn from_proposal(signed_proposal: &SignedProposal<FixedPill>) -> Self {
+        let proposal = &signed_proposal.proposal;
+
+        SignedMessage {
+            evidence_type: EvidenceType::Proposal,
+            epoch_id:      proposal.epoch_id,
+            round:         proposal.round,
+            hash:          Hash::digest(Bytes::from(rlp::encode(proposal))).as_bytes(),
+            signature:     signed_proposal.signature.clone(),
+        }
+    }
+
+    pub fn from_vote(signed_vote: &SignedVote) -> Self {
+        let vote = &signed_vote.vote;
+        let evidence_type = match vote.vote_type {
+            VoteType::Prevote => EvidenceType::Prevote,
+            VoteType::Precommit => EvidenceType::Precommit,
+        };
+
+        SignedMessage {
+            evidence_type,
+            epoch_id: vote.epoch_id,
+            round: vote.round,
+            hash: Hash::digest(Bytes::from(rlp::encode(vote))).as_bytes(),
+            signature: signed_vote.signature.clone(),
+        }
+    }
+}
+
+type MessageKey = (UserAddress, EvidenceType, u64, u64);
+
+/// The signed messages received from validators, indexed by the signer, type,
+/// epoch and round. The signatures must be verified before recording.
+#[derive(Default)]
+pub struct EvidencePool {
+    seen: RwLock<HashMap<MessageKey, (Bytes, Bytes)>>,
+}
+
+impl EvidencePool {
+    pub fn new() -> Self {
+        EvidencePool::default()
+    }
+
+    /// Record a message signed by the validator. Return the evidence if the
+    /// validator has signed a distinct message of the same type for the same
+    /// epoch and round.
+    pub fn record(
+        &self,
+        validator: UserAddress,
+        message: &SignedMessage,
+        msg: Bytes,
+    ) -> Option<Evidence> {
+        let key = (
+            validator.clone(),
+            message.evidence_type,
+            message.epoch_id,
+            message.round,
+        );
+
+        let mut seen = self.seen.write();
+        match seen.get(&key) {
+            Some((hash, _)) if hash == &message.hash => None,
+            Some((_, first)) => Some(Evidence {
+                validator,
+                epoch_id: message.epoch_id,
+                round: message.round,
+                evidence_type: message.evidence_type,
+                first: first.clone(),
+                second: msg,
+            }),
+            None => {
+                seen.insert(key, (message.hash.clone(), msg));
+                None
+            }
+        }
+    }
+
+    /// Drop the messages until the committed epoch.
+    pub fn prune(&self, epoch_id: u64) {
+        self.seen.write().retain(|key, _| key.2 > epoch_id);
+    }
+}
+
+/// Verify that the evidence proves the validator signed two distinct messages
+/// of the same type for the same epoch and round. A slashing contract can
+/// check the submitted evidence in the same way.
+pub fn verify_evidence(evidence: &Evidence, validators: &[Validator]) -> ProtocolResult<()> {
+    let invalid = |reason: &str| ConsensusError::InvalidEvidence {
+        validator: evidence.validator.clone(),
+        reason:    reason.to_owned(),
+    };
+
+    let validator = validators
+        .iter()
+        .find(|v| v.address == evidence.validator)
+        .ok_or_else(|| invalid("not a validator"))?;
+
+    let first = SignedMessage::decode(evidence.evidence_type, &evidence.first)?;
+    let second = SignedMessage::decode(evidence.evidence_type, &evidence.second)?;
+    if first.hash == second.hash {
+        return Err(invalid("the same message").into());
+    }
+
+    for message in [first, second].iter() {
+        if message.epoch_id != evidence.epoch_id || message.round != evidence.round {
+            return Err(invalid("epoch id or round mismatch").into());
+        }
+
+        let (address, signature) = split_signature(message.signature.clone())?;
+        if address != validator.address.as_bytes() {
+            return Err(invalid("signer mismatch").into());
+        }
+        BLS12381::verify_signature(&message.hash, &signature, &validator.bls_pub_key)
+            .map_err(|_| invalid("signature mismatch"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryFrom;
+
+    use bytes::Bytes;
+    use overlord::types::{SignedVote, Vote, VoteType};
+    use overlord::Crypto;
+
+    use common_crypto::{BLS12381PrivateKey, PrivateKey, PublicKey};
+    use protocol::types::{EvidenceType, Hash, UserAddress, Validator};
+
+    use crate::util::OverlordCrypto;
+
+    use super::{verify_evidence, EvidencePool, SignedMessage};
+
+    fn mock_validator(i: u8) -> (Validator, BLS12381PrivateKey) {
+        let mut key = [0u8; 48];
+        key[47] = i;
+        let key = BLS12381PrivateKey::try_from(key.as_ref()).unwrap();
+
+        let validator = Validator {
+            address:        UserAddress::from_hex(&format!(
+                "1000000000000000000000000000000000000000{:02}",
+                i
+            ))
+            .unwrap(),
+            bls_pub_key:    key.pub_key().to_bytes(),
+            propose_weight: 1,
+            vote_weight:    1,
+        };
+        (validator, key)
+    }
+
+    fn mock_vote(
+        validator: &Validator,
+        key: &BLS12381PrivateKey,
+        vote_type: VoteType,
+        epoch_hash: Hash,
+    ) -> Bytes {
+        let vote = Vote {
+            epoch_id: 10,
+            round: 1,
+            vote_type,
+            epoch_hash: epoch_hash.as_bytes(),
+        };
+        let crypto =
+            OverlordCrypto::new(validator.address.clone(), key.clone(), &[validator.clone()]);
+        let signature = crypto
+            .sign(crypto.hash(Bytes::from(rlp::encode(&vote))))
+            .unwrap();
+
+        Bytes::from(rlp::encode(&SignedVote { signature, vote }))
+    }
+
+    #[test]
+    fn test_detect_double_sign() {
+        let (validator, key) = mock_validator(1);
+        let pool = EvidencePool::new();
+        let record = |msg: Bytes| {
+            let message = SignedMessage::decode(EvidenceType::Prevote, &msg).unwrap();
+            pool.record(validator.address.clone(), &message, msg)
+        };
+
+        let first = mock_vote(&validator, &key, VoteType::Prevote, Hash::from_empty());
+        let second = mock_vote(
+            &validator,
+            &key,
+            VoteType::Prevote,
+            Hash::digest(Bytes::from("epoch")),
+        );
+        assert!(record(first.clone()).is_none());
+        // Receiving the same vote again is not an evidence.
+        assert!(record(first.clone()).is_none());
+
+        let evidence = record(second.clone()).unwrap();
+        assert_eq!(evidence.validator, validator.address);
+        assert_eq!((evidence.epoch_id, evidence.round), (10, 1));
+        assert_eq!(evidence.evidence_type, EvidenceType::Prevote);
+        assert_eq!((evidence.first, evidence.second), (first.clone(), second));
+
+        // A precommit is not the same type as a prevote.
+        let precommit = mock_vote(&validator, &key, VoteType::Precommit, Hash::from_empty());
+        let message = SignedMessage::decode(EvidenceType::Precommit, &precommit).unwrap();
+        assert!(pool
+            .record(validator.address.clone(), &message, precommit)
+            .is_none());
+
+        pool.prune(10);
+        assert!(record(first).is_none());
+    }
+
+    #[test]
+    fn test_verify_evidence() {
+        let (validator, key) = mock_validator(1);
+        let (other, other_key) = mock_validator(2);
+        let validators = vec![validator.clone(), other.clone()];
+        let pool = EvidencePool::new();
+
+        let first = mock_vote(&validator, &key, VoteType::Precommit, Hash::from_empty());
+        let second = mock_vote(
+            &validator,
+            &key,
+            VoteType::Precommit,
+            Hash::digest(Bytes::from("epoch")),
+        );
+        for msg in vec![first.clone(), second].into_iter() {
+            let message = SignedMessage::decode(EvidenceType::Precommit, &msg).unwrap();
+            if let Some(evidence) = pool.record(validator.address.clone(), &message, msg) {
+                assert!(verify_evidence(&evidence, &validators).is_ok());
+                assert!(verify_evidence(&evidence, &[other.clone()]).is_err());
+
+                let mut same = evidence.clone();
+                same.second = first.clone();
+                assert!(verify_evidence(&same, &validators).is_err());
+
+                // The messages must be signed by the validator.
+                let mut forged = evidence.clone();
+                forged.second = mock_vote(
+                    &other,
+                    &other_key,
+                    VoteType::Precommit,
+                    Hash::digest(Bytes::from("epoch")),
+                );
+                assert!(verify_evidence(&forged, &validators).is_err());
+
+                let mut wrong_type = evidence;
+                wrong_type.evidence_type = EvidenceType::Prevote;
+                assert!(verify_evidence(&wrong_type, &validators).is_err());
+                return;
+            }
+        }
+        panic!("no evidence detected");
+    }
+}
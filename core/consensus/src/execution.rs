@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::channel::mpsc::Receiver;
+use futures::StreamExt;
+use futures_timer::Delay;
+use log::{debug, info};
+use parking_lot::RwLock;
+
+use protocol::traits::{ConsensusAdapter, Context};
+use protocol::types::{Epoch, MerkleRoot, SignedTransaction};
+use protocol::ProtocolResult;
+
+use crate::engine::ConsensusEngine;
+use crate::ConsensusError;
+
+/// The max number of committed epochs waiting to be executed. A proposer does
+/// not propose a new epoch until the execution catches up.
+pub const MAX_EXEC_LAG: u64 = 10;
+const EXEC_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A committed epoch sent to the executor with its signed transactions.
+pub struct ExecTask {
+    pub epoch: Epoch,
+    pub txs:   Vec<SignedTransaction>,
+}
+
+/// The roots after executing an epoch, they are carried by the header of a
+/// following epoch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutedInfo {
+    pub epoch_id:     u64,
+    pub order_root:   MerkleRoot,
+    pub state_root:   MerkleRoot,
+    pub receipt_root: MerkleRoot,
+}
+
+struct ExecState {
+    // The latest executed epoch and the state root after executing it.
+    epoch_id:   u64,
+    state_root: MerkleRoot,
+    // The latest executed epoch confirmed by a committed header, only the
+    // results after it are kept.
+    confirmed: u64,
+    results:   BTreeMap<u64, ExecutedInfo>,
+}
+
+/// The progress of the background executor.
+pub struct ExecStatus {
+    inner: RwLock<ExecState>,
+}
+
+impl ExecStatus {
+    pub fn new(epoch_id: u64, state_root: MerkleRoot) -> Self {
+        ExecStatus {
+            inner: RwLock::new(ExecState {
+                epoch_id,
+                state_root,
+                confirmed: epoch_id,
+                results: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Return the latest executed epoch ID and the state root after it.
+    pub fn latest(&self) -> (u64, MerkleRoot) {
+        let inner = self.inner.read();
+        (inner.epoch_id, inner.state_root.clone())
+    }
+
+    pub fn push(&self, info: ExecutedInfo) {
+        let mut inner = self.inner.write();
+        inner.epoch_id = info.epoch_id;
+        inner.state_root = info.state_root.clone();
+        if info.epoch_id > inner.confirmed {
+            inner.results.insert(info.epoch_id, info);
+        }
+    }
+
+    /// Return the results of the executed epochs in `(after, until]`.
+    pub fn results(&self, after: u64, until: u64) -> Vec<ExecutedInfo> {
+        if until <= after {
+            return Vec::new();
+        }
+
+        self.inner
+            .read()
+            .results
+            .range(after + 1..=until)
+            .map(|(_, info)| info.clone())
+            .collect()
+    }
+
+    /// The results until the epoch are carried by a committed header, they are
+    /// not needed any more.
+    pub fn prune(&self, epoch_id: u64) {
+        let mut inner = self.inner.write();
+        if epoch_id > inner.confirmed {
+            inner.confirmed = epoch_id;
+            inner.results = inner.results.split_off(&(epoch_id + 1));
+        }
+    }
+
+    /// Wait until the epoch is executed.
+    pub async fn wait(&self, epoch_id: u64, timeout: Duration) -> ProtocolResult<()> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let executed = { self.inner.read().epoch_id };
+            if executed >= epoch_id {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(ConsensusError::Other(format!(
+                    "wait for executing epoch {} timeout",
+                    epoch_id
+                ))
+                .into());
+            }
+            Delay::new(EXEC_POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Execute the committed epochs in order. The committed epochs not executed
+/// before the restart are executed first. An execution error is returned, the
+/// following epochs can not be executed on a missing state.
+pub async fn execute_epochs<Adapter: ConsensusAdapter + 'static>(
+    engine: Arc<ConsensusEngine<Adapter>>,
+    mut receiver: Receiver<ExecTask>,
+    latest_epoch_id: u64,
+) -> ProtocolResult<()> {
+    execute_stored(&engine, latest_epoch_id).await?;
+
+    while let Some(task) = receiver.next().await {
+        execute_task(&engine, task).await?;
+    }
+    Ok(())
+}
+
+async fn execute_stored<Adapter: ConsensusAdapter + 'static>(
+    engine: &ConsensusEngine<Adapter>,
+    latest_epoch_id: u64,
+) -> ProtocolResult<()> {
+    let (exec_epoch_id, _) = engine.exec_status().latest();
+    if exec_epoch_id < latest_epoch_id {
+        info!(
+            "consensus: execute stored epochs from {} to {}",
+            exec_epoch_id + 1,
+            latest_epoch_id
+        );
+    }
+
+    for epoch_id in (exec_epoch_id + 1)..=latest_epoch_id {
+        let epoch = engine.get_epoch_by_id(Context::new(), epoch_id).await?;
+        let txs = engine
+            .get_signed_txs(Context::new(), epoch.ordered_tx_hashes.clone())
+            .await?;
+        execute_task(engine, ExecTask { epoch, txs }).await?;
+    }
+    Ok(())
+}
+
+async fn execute_task<Adapter: ConsensusAdapter + 'static>(
+    engine: &ConsensusEngine<Adapter>,
+    task: ExecTask,
+) -> ProtocolResult<()> {
+    let (_, state_root) = engine.exec_status().latest();
+    let epoch_id = task.epoch.header.epoch_id;
+
+    debug!("consensus: execute epoch {}", epoch_id);
+    let info = engine
+        .execute_epoch(state_root, task.epoch, task.txs)
+        .await?;
+    engine.exec_status().push(info);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use futures::executor::block_on;
+
+    use protocol::types::Hash;
+
+    use super::{ExecStatus, ExecutedInfo};
+
+    fn executed(epoch_id: u64) -> ExecutedInfo {
+        ExecutedInfo {
+            epoch_id,
+            order_root: Hash::digest(epoch_id.to_be_bytes().to_vec().into()),
+            state_root: Hash::digest(vec![epoch_id as u8; 8].into()),
+            receipt_root: Hash::from_empty(),
+        }
+    }
+
+    #[test]
+    fn test_exec_status() {
+        let status = ExecStatus::new(3, Hash::from_empty());
+        // The results confirmed before are ignored.
+        status.push(executed(3));
+        assert!(status.results(0, 3).is_empty());
+
+        for id in 4..=8 {
+            status.push(executed(id));
+        }
+        assert_eq!(status.latest(), (8, executed(8).state_root));
+        assert_eq!(status.results(3, 8).len(), 5);
+        assert_eq!(status.results(3, 5), vec![executed(4), executed(5)]);
+        assert!(status.results(5, 5).is_empty());
+
+        status.prune(6);
+        assert!(status.results(3, 6).is_empty());
+        assert_eq!(status.results(3, 8), vec![executed(7), executed(8)]);
+        // Pruning never goes back.
+        status.prune(4);
+        assert_eq!(status.results(6, 8).len(), 2);
+    }
+
+    #[test]
+    fn test_wait_execution() {
+        let status = ExecStatus::new(3, Hash::from_empty());
+        assert!(block_on(status.wait(3, Duration::from_millis(0))).is_ok());
+        assert!(block_on(status.wait(4, Duration::from_millis(50))).is_err());
+
+        status.push(executed(4));
+        assert!(block_on(status.wait(4, Duration::from_millis(50))).is_ok());
+    }
+}
@@ -7,7 +7,7 @@ use overlord::Codec;
 
 use protocol::codec::{Deserialize, Serialize};
 use protocol::traits::MessageCodec;
-use protocol::types::{Epoch, Hash, Pill, SignedTransaction};
+use protocol::types::{Epoch, Hash, Pill, Proof, SignedTransaction};
 use protocol::{fixed_codec::ProtocolFixedCodec, ProtocolResult};
 
 use crate::{ConsensusError, MsgType};
@@ -16,12 +16,16 @@ use crate::{ConsensusError, MsgType};
 pub enum ConsensusRpcRequest {
     PullEpochs(u64),
     PullTxs(PullTxsRequest),
+    PullEpochBatch(PullEpochsRequest),
+    PullProof(u64),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ConsensusRpcResponse {
     PullEpochs(Box<Epoch>),
     PullTxs(Box<FixedSignedTxs>),
+    PullEpochBatch(Box<FixedEpochs>),
+    PullProof(Box<Proof>),
 }
 
 #[async_trait]
@@ -41,6 +45,18 @@ impl MessageCodec for ConsensusRpcResponse {
                 tmp.extend_from_slice(b"b");
                 tmp
             }
+
+            ConsensusRpcResponse::PullEpochBatch(epochs) => {
+                let mut tmp = Bytes::from(rlp::encode_list(&epochs.inner));
+                tmp.extend_from_slice(b"c");
+                tmp
+            }
+
+            ConsensusRpcResponse::PullProof(proof) => {
+                let mut tmp = proof.encode_fixed()?;
+                tmp.extend_from_slice(b"d");
+                tmp
+            }
         };
         Ok(bytes)
     }
@@ -60,6 +76,20 @@ impl MessageCodec for ConsensusRpcResponse {
                     .map_err(|_| ConsensusError::DecodeErr(MsgType::RpcPullTxs))?;
                 Ok(ConsensusRpcResponse::PullTxs(Box::new(res)))
             }
+
+            b"c" => {
+                let inner: Vec<Epoch> = rlp::Rlp::new(bytes.as_ref())
+                    .as_list()
+                    .map_err(|_| ConsensusError::DecodeErr(MsgType::RpcPullEpochs))?;
+                Ok(ConsensusRpcResponse::PullEpochBatch(Box::new(
+                    FixedEpochs { inner },
+                )))
+            }
+
+            b"d" => {
+                let res: Proof = ProtocolFixedCodec::decode_fixed(bytes)?;
+                Ok(ConsensusRpcResponse::PullProof(Box::new(res)))
+            }
             _ => unreachable!(),
         }
     }
@@ -115,6 +145,30 @@ impl PullTxsRequest {
     }
 }
 
+/// Request at most `count` consecutive epochs starting from `begin`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PullEpochsRequest {
+    pub begin: u64,
+    pub count: u64,
+}
+
+impl PullEpochsRequest {
+    pub fn new(begin: u64, count: u64) -> Self {
+        PullEpochsRequest { begin, count }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedEpochs {
+    pub inner: Vec<Epoch>,
+}
+
+impl FixedEpochs {
+    pub fn new(inner: Vec<Epoch>) -> Self {
+        FixedEpochs { inner }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct FixedSignedTxs {
     #[serde(with = "core_network::serde_multi")]
@@ -155,7 +209,7 @@ mod test {
         TransactionAction, UserAddress,
     };
 
-    use super::{ConsensusRpcResponse, FixedSignedTxs};
+    use super::{ConsensusRpcResponse, FixedEpochs, FixedSignedTxs};
 
     fn gen_epoch(epoch_id: u64, epoch_hash: Hash) -> Epoch {
         let nonce = Hash::digest(Bytes::from("XXXX"));
@@ -168,6 +222,7 @@ mod test {
             logs_bloom: Default::default(),
             order_root: nonce.clone(),
             confirm_root: Vec::new(),
+            exec_epoch_id: 0,
             state_root: nonce.clone(),
             receipt_root: Vec::new(),
             cycles_used: 999_999,
@@ -265,5 +320,18 @@ mod test {
         let bytes = origin.encode().await.unwrap();
         let res: ConsensusRpcResponse = MessageCodec::decode(bytes).await.unwrap();
         assert_eq!(origin, res);
+
+        let epochs = (0..10)
+            .map(|id| gen_epoch(id, Hash::from_empty()))
+            .collect::<Vec<_>>();
+        let mut origin = ConsensusRpcResponse::PullEpochBatch(Box::new(FixedEpochs::new(epochs)));
+        let bytes = origin.encode().await.unwrap();
+        let res: ConsensusRpcResponse = MessageCodec::decode(bytes).await.unwrap();
+        assert_eq!(origin, res);
+
+        let mut origin = ConsensusRpcResponse::PullProof(Box::new(mock_proof(Hash::from_empty())));
+        let bytes = origin.encode().await.unwrap();
+        let res: ConsensusRpcResponse = MessageCodec::decode(bytes).await.unwrap();
+        assert_eq!(origin, res);
     }
 }
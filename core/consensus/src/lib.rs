@@ -1,10 +1,18 @@
 mod engine;
+mod execution;
+mod monitor;
+mod synchronization;
+#[cfg(test)]
+mod tests;
 
 pub mod adapter;
 pub mod consensus;
+pub mod dev;
+pub mod evidence;
 pub mod fixed_types;
 pub mod message;
 pub mod util;
+pub mod wal;
 
 pub use overlord::DurationConfig;
 
@@ -14,7 +22,7 @@ use derive_more::{Display, From};
 
 use common_crypto::CryptoError;
 
-use protocol::types::Hash;
+use protocol::types::{Hash, UserAddress};
 use protocol::{ProtocolError, ProtocolErrorKind};
 
 #[derive(Clone, Debug, Display, PartialEq, Eq)]
@@ -36,6 +44,9 @@ pub enum MsgType {
 
     #[display(fmt = "Rpc Pull Transactions")]
     RpcPullTxs,
+
+    #[display(fmt = "Rpc Pull Proof")]
+    RpcPullProof,
 }
 
 /// Consensus errors defines here.
@@ -73,6 +84,13 @@ pub enum ConsensusError {
     #[display(fmt = "Crypto error {:?}", _0)]
     CryptoErr(Box<CryptoError>),
 
+    /// The evidence does not prove the validator signed twice.
+    #[display(fmt = "Invalid evidence of {:?}: {}", validator, reason)]
+    InvalidEvidence {
+        validator: UserAddress,
+        reason:    String,
+    },
+
     /// The synchronous epoch does not pass the checks.
     #[display(fmt = "Synchronization {} epoch error", _0)]
     SyncEpochHashErr(u64),
@@ -81,10 +99,81 @@ pub enum ConsensusError {
     #[display(fmt = "Synchronization {} proof error", _0)]
     SyncEpochProofErr(u64),
 
+    /// The QC is not aggregated from the votes of a quorum.
+    #[display(fmt = "Invalid QC of {} epoch round {}: {}", epoch_id, round, reason)]
+    InvalidQC {
+        epoch_id: u64,
+        round:    u64,
+        reason:   String,
+    },
+
+    /// The proposed hash is not the hash of the epoch.
+    #[display(fmt = "Epoch {} mismatches the proposed hash", epoch_id)]
+    InvalidEpochHash { epoch_id: u64 },
+
+    /// The proposed epoch header has a wrong chain ID.
+    #[display(fmt = "Epoch {} has invalid chain ID {:?}", epoch_id, chain_id)]
+    InvalidChainId { epoch_id: u64, chain_id: Hash },
+
+    /// The proposed epoch ID is not the current epoch ID.
+    #[display(fmt = "Invalid epoch ID {}, expect {}", actual, expect)]
+    InvalidEpochId { expect: u64, actual: u64 },
+
+    /// The previous hash of the proposed epoch is not the hash of the latest
+    /// epoch.
+    #[display(fmt = "Epoch {} has invalid previous hash {:?}", epoch_id, pre_hash)]
+    InvalidPrevHash { epoch_id: u64, pre_hash: Hash },
+
+    /// The timestamp goes back or drifts too far into the future.
+    #[display(fmt = "Epoch {} has invalid timestamp {}", epoch_id, timestamp)]
+    InvalidTimestamp { epoch_id: u64, timestamp: u64 },
+
+    /// The order root is not the merkle root of the ordered transactions.
+    #[display(fmt = "Epoch {} has invalid order root {:?}", epoch_id, order_root)]
+    InvalidOrderRoot { epoch_id: u64, order_root: Hash },
+
+    /// The confirm root mismatches the local execution.
+    #[display(fmt = "Epoch {} has invalid confirm root", epoch_id)]
+    InvalidConfirmRoot { epoch_id: u64 },
+
+    /// The state root mismatches the local execution.
+    #[display(fmt = "Epoch {} has invalid state root {:?}", epoch_id, state_root)]
+    InvalidStateRoot { epoch_id: u64, state_root: Hash },
+
+    /// The receipt root mismatches the local execution.
+    #[display(fmt = "Epoch {} has invalid receipt root", epoch_id)]
+    InvalidReceiptRoot { epoch_id: u64 },
+
+    /// The executed epoch carried by the header is out of range.
+    #[display(
+        fmt = "Epoch {} has invalid executed epoch ID {}",
+        epoch_id,
+        exec_epoch_id
+    )]
+    InvalidExecEpochId {
+        epoch_id:      u64,
+        exec_epoch_id: u64,
+    },
+
+    /// The validators mismatch the local status.
+    #[display(fmt = "Epoch {} has invalid validators", epoch_id)]
+    InvalidValidators { epoch_id: u64 },
+
+    /// The proposer in the header is not the signer of the proposal.
+    #[display(fmt = "Epoch {} has invalid proposer {:?}", epoch_id, proposer)]
+    InvalidProposer {
+        epoch_id: u64,
+        proposer: UserAddress,
+    },
+
     /// The Rpc response mismatch the request.
     #[display(fmt = "Synchronization Rpc {:?} message mismatch", _0)]
     RpcErr(MsgType),
 
+    /// Read or write the consensus WAL error.
+    #[display(fmt = "Consensus WAL error {:?}", _0)]
+    WalErr(String),
+
     ///
     #[display(fmt = "Get merkle root failed {:?}", _0)]
     MerkleErr(String),
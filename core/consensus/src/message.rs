@@ -11,7 +11,9 @@ use serde::{Deserialize, Serialize};
 use protocol::traits::{Consensus, Context, MessageHandler, Priority, Rpc, Storage};
 use protocol::ProtocolResult;
 
-use crate::fixed_types::{ConsensusRpcRequest, ConsensusRpcResponse, FixedEpochID, FixedSignedTxs};
+use crate::fixed_types::{
+    ConsensusRpcRequest, ConsensusRpcResponse, FixedEpochID, FixedEpochs, FixedSignedTxs,
+};
 
 pub const END_GOSSIP_SIGNED_PROPOSAL: &str = "/gossip/consensus/signed_proposal";
 pub const END_GOSSIP_SIGNED_VOTE: &str = "/gossip/consensus/signed_vote";
@@ -20,6 +22,9 @@ pub const END_GOSSIP_RICH_EPOCH_ID: &str = "/gossip/consensus/rich_epoch_id";
 pub const RPC_SYNC_PULL: &str = "/rpc_call/consensus/sync_pull";
 pub const RPC_RESP_SYNC_PULL: &str = "/rpc_resp/consensus/sync_pull";
 
+/// The max number of epochs responded to a batch pull.
+pub const MAX_PULL_EPOCHS: u64 = 64;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Proposal(pub Vec<u8>);
 
@@ -173,6 +178,42 @@ impl<R: Rpc + 'static, S: Storage + 'static> MessageHandler for RpcHandler<R, S>
                     )
                     .await
             }
+
+            ConsensusRpcRequest::PullEpochBatch(req) => {
+                debug!("message: get rpc pull epoch batch {:?}, {:?}", req, ctx);
+                let mut res = Vec::new();
+                if req.count != 0 {
+                    let latest_id = self.storage.get_latest_epoch().await?.header.epoch_id;
+                    let end =
+                        latest_id.min(req.begin.saturating_add(req.count.min(MAX_PULL_EPOCHS) - 1));
+                    for epoch_id in req.begin..=end {
+                        res.push(self.storage.get_epoch_by_epoch_id(epoch_id).await?);
+                    }
+                }
+
+                self.rpc
+                    .response(
+                        ctx,
+                        RPC_RESP_SYNC_PULL,
+                        ConsensusRpcResponse::PullEpochBatch(Box::new(FixedEpochs::new(res))),
+                        Priority::High,
+                    )
+                    .await
+            }
+
+            ConsensusRpcRequest::PullProof(epoch_id) => {
+                debug!("message: get rpc pull proof {:?}, {:?}", epoch_id, ctx);
+                let res = self.storage.get_proof(epoch_id).await?;
+
+                self.rpc
+                    .response(
+                        ctx,
+                        RPC_RESP_SYNC_PULL,
+                        ConsensusRpcResponse::PullProof(Box::new(res)),
+                        Priority::High,
+                    )
+                    .await
+            }
         }
     }
 }
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+use overlord::types::{AggregatedVote, Vote, VoteType};
+use parking_lot::RwLock;
+
+use protocol::traits::{ConsensusStatus, ConsensusStep, LockedProposal, ValidatorVotes};
+use protocol::types::{Hash, UserAddress};
+
+struct MonitorState {
+    epoch_id: u64,
+    round:    u64,
+    step:     ConsensusStep,
+    proposal: Option<Hash>,
+    locked:   Option<LockedProposal>,
+    votes:    BTreeMap<UserAddress, ValidatorVotes>,
+}
+
+/// Track the state of overlord from the messages it sends and receives, only
+/// the messages of the current epoch are counted.
+pub struct StatusMonitor {
+    inner: RwLock<MonitorState>,
+}
+
+impl StatusMonitor {
+    pub fn new(epoch_id: u64) -> Self {
+        StatusMonitor {
+            inner: RwLock::new(MonitorState {
+                epoch_id,
+                round: 0,
+                step: ConsensusStep::Propose,
+                proposal: None,
+                locked: None,
+                votes: BTreeMap::new(),
+            }),
+        }
+    }
+
+    pub fn on_proposal(&self, epoch_id: u64, round: u64, epoch_hash: &Bytes) {
+        let mut inner = self.inner.write();
+        if inner.enter(epoch_id, round) {
+            inner.proposal = Hash::from_bytes(epoch_hash.clone()).ok();
+        }
+    }
+
+    /// Record a vote signed by the voter, the step goes forward only by the
+    /// votes of this node.
+    pub fn on_vote(&self, voter: &UserAddress, vote: &Vote, is_self: bool) {
+        let mut inner = self.inner.write();
+        if !inner.enter(vote.epoch_id, vote.round) {
+            return;
+        }
+
+        let votes = inner
+            .votes
+            .entry(voter.clone())
+            .or_insert_with(|| ValidatorVotes {
+                address:   voter.clone(),
+                prevote:   None,
+                precommit: None,
+            });
+        let step = match vote.vote_type {
+            VoteType::Prevote => {
+                votes.prevote = Some(vote.epoch_hash.clone());
+                ConsensusStep::Prevote
+            }
+            VoteType::Precommit => {
+                votes.precommit = Some(vote.epoch_hash.clone());
+                ConsensusStep::Precommit
+            }
+        };
+
+        if is_self && step > inner.step {
+            inner.step = step;
+        }
+    }
+
+    /// A prevote QC for a proposal locks it, and a precommit QC for a
+    /// proposal commits it.
+    pub fn on_qc(&self, qc: &AggregatedVote) {
+        let mut inner = self.inner.write();
+        if !inner.enter(qc.epoch_id, qc.round) {
+            return;
+        }
+
+        let epoch_hash = match Hash::from_bytes(qc.epoch_hash.clone()) {
+            Ok(hash) if !qc.epoch_hash.is_empty() => hash,
+            _ => return,
+        };
+        match qc.vote_type {
+            VoteType::Prevote => {
+                inner.locked = Some(LockedProposal {
+                    round: qc.round,
+                    epoch_hash,
+                });
+            }
+            VoteType::Precommit => inner.step = ConsensusStep::Commit,
+        }
+    }
+
+    /// The epoch is committed, the consensus goes to the next epoch.
+    pub fn on_commit(&self, epoch_id: u64) {
+        let mut inner = self.inner.write();
+        if epoch_id >= inner.epoch_id {
+            inner.epoch_id = epoch_id + 1;
+            inner.reset_round(0);
+            inner.locked = None;
+        }
+    }
+
+    pub fn status(&self, syncing: bool) -> ConsensusStatus {
+        let inner = self.inner.read();
+
+        ConsensusStatus {
+            epoch_id: inner.epoch_id,
+            round: inner.round,
+            step: inner.step,
+            proposal: inner.proposal.clone(),
+            locked: inner.locked.clone(),
+            votes: inner.votes.values().cloned().collect(),
+            syncing,
+        }
+    }
+}
+
+impl MonitorState {
+    // Go to the round of a message of the current epoch. Return false if the
+    // message is out of date or of another epoch.
+    fn enter(&mut self, epoch_id: u64, round: u64) -> bool {
+        if epoch_id != self.epoch_id || round < self.round {
+            return false;
+        }
+        if round > self.round {
+            self.reset_round(round);
+        }
+        true
+    }
+
+    fn reset_round(&mut self, round: u64) {
+        self.round = round;
+        self.step = ConsensusStep::Propose;
+        self.proposal = None;
+        self.votes.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::Bytes;
+    use overlord::types::{Vote, VoteType};
+
+    use protocol::traits::ConsensusStep;
+    use protocol::types::{Hash, UserAddress};
+
+    use super::StatusMonitor;
+
+    fn mock_vote(epoch_id: u64, round: u64, vote_type: VoteType, epoch_hash: Bytes) -> Vote {
+        Vote {
+            epoch_id,
+            round,
+            vote_type,
+            epoch_hash,
+        }
+    }
+
+    #[test]
+    fn test_monitor_rounds() {
+        let monitor = StatusMonitor::new(5);
+        let me = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+        let other = UserAddress::from_hex("100000000000000000000000000000000000000002").unwrap();
+        let hash = Hash::digest(Bytes::from("epoch")).as_bytes();
+
+        monitor.on_proposal(5, 0, &hash);
+        monitor.on_vote(
+            &other,
+            &mock_vote(5, 0, VoteType::Prevote, hash.clone()),
+            false,
+        );
+        let status = monitor.status(false);
+        assert_eq!(status.step, ConsensusStep::Propose);
+        assert_eq!(status.proposal.unwrap().as_bytes(), hash);
+        assert_eq!(status.votes.len(), 1);
+
+        monitor.on_vote(&me, &mock_vote(5, 0, VoteType::Prevote, hash.clone()), true);
+        monitor.on_vote(
+            &me,
+            &mock_vote(5, 0, VoteType::Precommit, Bytes::new()),
+            true,
+        );
+        let status = monitor.status(true);
+        assert_eq!(status.step, ConsensusStep::Precommit);
+        assert_eq!(status.votes[0].precommit, Some(Bytes::new()));
+        assert!(status.syncing);
+
+        // The votes of other epochs and previous rounds are ignored.
+        monitor.on_vote(&me, &mock_vote(6, 0, VoteType::Prevote, hash.clone()), true);
+        monitor.on_vote(
+            &other,
+            &mock_vote(5, 1, VoteType::Prevote, hash.clone()),
+            false,
+        );
+        monitor.on_vote(&me, &mock_vote(5, 0, VoteType::Prevote, hash.clone()), true);
+        let status = monitor.status(false);
+        assert_eq!((status.epoch_id, status.round), (5, 1));
+        assert_eq!(status.step, ConsensusStep::Propose);
+        assert!(status.proposal.is_none());
+        assert_eq!(status.votes.len(), 1);
+
+        monitor.on_commit(5);
+        let status = monitor.status(false);
+        assert_eq!((status.epoch_id, status.round), (6, 0));
+        assert!(status.votes.is_empty());
+    }
+}
@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::channel::mpsc::{channel, Receiver, Sender};
+use futures::future::{select, Either};
+use futures::{pin_mut, SinkExt, StreamExt};
+use futures_timer::Delay;
+use log::{debug, warn};
+use parking_lot::RwLock;
+
+use core_network::{NetworkContext, SessionId};
+use protocol::traits::{ConsensusAdapter, Context};
+use protocol::types::{Epoch, Proof, SignedTransaction, Validator};
+use protocol::ProtocolResult;
+
+use crate::engine::ConsensusEngine;
+use crate::message::MAX_PULL_EPOCHS;
+use crate::util::check_proof;
+use crate::{ConsensusError, MsgType};
+
+/// The max number of pulled epochs waiting to be executed, it bounds the
+/// memory used by the synchronization.
+const SYNC_BUFFER_SIZE: usize = 256;
+/// The max number of peers tried for a batch of epochs.
+const SYNC_MAX_RETRY: usize = 3;
+const SYNC_RPC_TIMEOUT: Duration = Duration::from_secs(10);
+/// A peer serving invalid data is not chosen during the ban duration.
+const SYNC_BAN_DURATION: Duration = Duration::from_secs(600);
+
+const MAX_PEER_SCORE: i64 = 100;
+const FAILURE_PENALTY: i64 = 5;
+const INVALID_PENALTY: i64 = 50;
+
+/// An epoch pulled from other nodes with its signed transactions.
+pub struct SyncEpoch {
+    pub epoch: Epoch,
+    pub txs: Vec<SignedTransaction>,
+    /// The peer serving the epoch.
+    pub peer: SessionId,
+}
+
+struct SyncPeer {
+    ctx:          Context,
+    epoch_id:     u64,
+    score:        i64,
+    banned_until: Option<Instant>,
+}
+
+/// The peers to synchronize from. The epoch ID of a peer is updated by its
+/// rich epoch ID gossip, and its score is updated by the results of the
+/// requests it serves.
+#[derive(Default)]
+pub struct SyncPeers {
+    peers: RwLock<HashMap<SessionId, SyncPeer>>,
+}
+
+impl SyncPeers {
+    pub fn new() -> Self {
+        SyncPeers::default()
+    }
+
+    /// Update the epoch ID announced by the peer of the context.
+    pub fn update(&self, ctx: &Context, epoch_id: u64) {
+        let sid = match ctx.session_id() {
+            Ok(sid) => sid,
+            Err(_) => return,
+        };
+
+        let mut peers = self.peers.write();
+        let peer = peers.entry(sid).or_insert_with(|| SyncPeer {
+            ctx: ctx.clone(),
+            epoch_id,
+            score: 0,
+            banned_until: None,
+        });
+        peer.epoch_id = peer.epoch_id.max(epoch_id);
+    }
+
+    /// Choose the peer with the best score among the ones having the epoch
+    /// and not tried yet.
+    pub fn choose(&self, epoch_id: u64, tried: &[SessionId]) -> Option<(SessionId, Context)> {
+        let now = Instant::now();
+        let mut peers = self.peers.write();
+
+        for peer in peers.values_mut() {
+            if peer.banned_until.map_or(false, |until| until <= now) {
+                peer.banned_until = None;
+                peer.score = 0;
+            }
+        }
+
+        peers
+            .iter()
+            .filter(|(sid, peer)| {
+                peer.banned_until.is_none() && peer.epoch_id >= epoch_id && !tried.contains(sid)
+            })
+            .max_by_key(|(_, peer)| (peer.score, peer.epoch_id))
+            .map(|(sid, peer)| (*sid, peer.ctx.clone()))
+    }
+
+    pub fn record_success(&self, sid: SessionId) {
+        if let Some(peer) = self.peers.write().get_mut(&sid) {
+            peer.score = (peer.score + 1).min(MAX_PEER_SCORE);
+        }
+    }
+
+    /// The peer fails to respond in time.
+    pub fn record_failure(&self, sid: SessionId) {
+        if let Some(peer) = self.peers.write().get_mut(&sid) {
+            peer.score -= FAILURE_PENALTY;
+        }
+    }
+
+    /// The peer serves invalid data, ban it for a while.
+    pub fn record_invalid(&self, sid: SessionId) {
+        if let Some(peer) = self.peers.write().get_mut(&sid) {
+            warn!("consensus: ban synchronization peer {:?}", sid);
+            peer.score -= INVALID_PENALTY;
+            peer.banned_until = Some(Instant::now() + SYNC_BAN_DURATION);
+        }
+    }
+}
+
+/// Pull the epochs from `begin` to `end` in background. The epochs are pulled
+/// in batches together with their transactions, so that the following epochs
+/// are fetched while the previous ones are executed.
+pub fn prefetch_epochs<Adapter: ConsensusAdapter + 'static>(
+    engine: Arc<ConsensusEngine<Adapter>>,
+    peers: Arc<SyncPeers>,
+    begin: u64,
+    end: u64,
+) -> Receiver<ProtocolResult<SyncEpoch>> {
+    let (sender, receiver) = channel(SYNC_BUFFER_SIZE);
+    runtime::spawn(pull_epochs(engine, peers, begin, end, sender));
+    receiver
+}
+
+/// Receive the next prefetched epoch.
+pub async fn next_epoch(
+    receiver: &mut Receiver<ProtocolResult<SyncEpoch>>,
+) -> ProtocolResult<SyncEpoch> {
+    receiver
+        .next()
+        .await
+        .ok_or_else(|| ConsensusError::Other("synchronization pipeline closed".to_string()))?
+}
+
+async fn pull_epochs<Adapter: ConsensusAdapter + 'static>(
+    engine: Arc<ConsensusEngine<Adapter>>,
+    peers: Arc<SyncPeers>,
+    begin: u64,
+    end: u64,
+    mut sender: Sender<ProtocolResult<SyncEpoch>>,
+) {
+    let mut epoch_id = begin;
+    while epoch_id <= end {
+        let count = MAX_PULL_EPOCHS.min(end - epoch_id + 1);
+
+        match pull_batch_with_retry(&engine, &peers, epoch_id, count).await {
+            Ok(epochs) => {
+                epoch_id += epochs.len() as u64;
+                for epoch in epochs.into_iter() {
+                    // The receiver is dropped when the synchronization fails.
+                    if sender.send(Ok(epoch)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(e) => {
+                let _ = sender.send(Err(e)).await;
+                return;
+            }
+        }
+    }
+}
+
+/// Pull a batch of epochs, try another peer if the chosen one fails.
+async fn pull_batch_with_retry<Adapter: ConsensusAdapter + 'static>(
+    engine: &ConsensusEngine<Adapter>,
+    peers: &SyncPeers,
+    begin: u64,
+    count: u64,
+) -> ProtocolResult<Vec<SyncEpoch>> {
+    let mut tried = Vec::new();
+
+    while tried.len() < SYNC_MAX_RETRY {
+        let (sid, ctx) = match peers.choose(begin, &tried) {
+            Some(peer) => peer,
+            None => break,
+        };
+        tried.push(sid);
+
+        match pull_batch(engine, peers, sid, ctx, begin, count).await {
+            Ok(epochs) => {
+                peers.record_success(sid);
+                return Ok(epochs);
+            }
+            Err(e) => warn!(
+                "consensus: synchronization pull epoch {} from {:?} failed {:?}",
+                begin, sid, e
+            ),
+        }
+    }
+
+    Err(ConsensusError::Other(format!("no peer serves epoch {}", begin)).into())
+}
+
+async fn pull_batch<Adapter: ConsensusAdapter + 'static>(
+    engine: &ConsensusEngine<Adapter>,
+    peers: &SyncPeers,
+    sid: SessionId,
+    ctx: Context,
+    begin: u64,
+    count: u64,
+) -> ProtocolResult<Vec<SyncEpoch>> {
+    debug!(
+        "consensus: synchronization pull {} epochs from {} of {:?}",
+        count, begin, sid
+    );
+    let epochs = with_timeout(engine.pull_epochs(ctx.clone(), begin, count))
+        .await
+        .map_err(|e| {
+            peers.record_failure(sid);
+            e
+        })?;
+
+    // The epochs must be consecutive from `begin`.
+    if epochs.is_empty()
+        || epochs.len() as u64 > count
+        || epochs
+            .iter()
+            .enumerate()
+            .any(|(i, epoch)| epoch.header.epoch_id != begin + i as u64)
+    {
+        peers.record_invalid(sid);
+        return Err(ConsensusError::RpcErr(MsgType::RpcPullEpochs).into());
+    }
+
+    // Pull the transactions of the whole batch in one request.
+    let hashes = epochs
+        .iter()
+        .flat_map(|epoch| epoch.ordered_tx_hashes.clone())
+        .collect::<Vec<_>>();
+    let txs = if hashes.is_empty() {
+        Vec::new()
+    } else {
+        debug!(
+            "consensus: synchronization pull {} transactions",
+            hashes.len()
+        );
+        with_timeout(engine.pull_txs(ctx, hashes.clone()))
+            .await
+            .map_err(|e| {
+                peers.record_failure(sid);
+                e
+            })?
+    };
+
+    if txs.len() != hashes.len()
+        || txs
+            .iter()
+            .zip(hashes.iter())
+            .any(|(tx, h)| &tx.tx_hash != h)
+    {
+        peers.record_invalid(sid);
+        return Err(ConsensusError::RpcErr(MsgType::RpcPullTxs).into());
+    }
+
+    let mut txs = txs.into_iter();
+    let res = epochs
+        .into_iter()
+        .map(|epoch| {
+            let epoch_txs = txs
+                .by_ref()
+                .take(epoch.ordered_tx_hashes.len())
+                .collect::<Vec<_>>();
+            SyncEpoch {
+                epoch,
+                txs: epoch_txs,
+                peer: sid,
+            }
+        })
+        .collect::<Vec<_>>();
+    Ok(res)
+}
+
+/// Pull the proof of the epoch and check it, try another peer if the chosen
+/// one fails or serves an invalid proof. The proof of an epoch is carried by
+/// the next epoch, so this is only needed for the last synchronized epoch.
+pub async fn pull_proof<Adapter: ConsensusAdapter + 'static>(
+    engine: &ConsensusEngine<Adapter>,
+    peers: &SyncPeers,
+    epoch: &Epoch,
+    validators: &[Validator],
+) -> ProtocolResult<Proof> {
+    let epoch_id = epoch.header.epoch_id;
+    let mut tried = Vec::new();
+
+    while tried.len() < SYNC_MAX_RETRY {
+        let (sid, ctx) = match peers.choose(epoch_id, &tried) {
+            Some(peer) => peer,
+            None => break,
+        };
+        tried.push(sid);
+
+        debug!(
+            "consensus: synchronization pull proof of epoch {} from {:?}",
+            epoch_id, sid
+        );
+        let proof = match with_timeout(engine.pull_proof(ctx, epoch_id)).await {
+            Ok(proof) => proof,
+            Err(e) => {
+                peers.record_failure(sid);
+                warn!(
+                    "consensus: synchronization pull proof {} from {:?} failed {:?}",
+                    epoch_id, sid, e
+                );
+                continue;
+            }
+        };
+
+        match check_proof(epoch, &proof, validators) {
+            Ok(()) => {
+                peers.record_success(sid);
+                return Ok(proof);
+            }
+            Err(e) => {
+                peers.record_invalid(sid);
+                warn!(
+                    "consensus: synchronization proof {} from {:?} is invalid {:?}",
+                    epoch_id, sid, e
+                );
+            }
+        }
+    }
+
+    Err(ConsensusError::Other(format!("no peer serves the proof of epoch {}", epoch_id)).into())
+}
+
+async fn with_timeout<T, F: Future<Output = ProtocolResult<T>>>(fut: F) -> ProtocolResult<T> {
+    let delay = Delay::new(SYNC_RPC_TIMEOUT);
+    pin_mut!(fut);
+    pin_mut!(delay);
+
+    match select(fut, delay).await {
+        Either::Left((res, _)) => res,
+        Either::Right(_) => {
+            Err(ConsensusError::Other("synchronization rpc timeout".to_string()).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core_network::{NetworkContext, SessionId};
+    use protocol::traits::Context;
+
+    use super::SyncPeers;
+
+    fn peer_ctx(sid: usize) -> Context {
+        Context::new().set_session_id(SessionId::new(sid))
+    }
+
+    #[test]
+    fn test_choose_peer() {
+        let peers = SyncPeers::new();
+        peers.update(&peer_ctx(1), 10);
+        peers.update(&peer_ctx(2), 20);
+        peers.update(&peer_ctx(3), 30);
+        // A context without session is ignored.
+        peers.update(&Context::new(), 100);
+
+        // Only the peers ahead are chosen.
+        assert_eq!(peers.choose(25, &[]).unwrap().0, SessionId::new(3));
+        assert!(peers.choose(31, &[]).is_none());
+
+        // The peers with better score are preferred.
+        peers.record_success(SessionId::new(1));
+        assert_eq!(peers.choose(5, &[]).unwrap().0, SessionId::new(1));
+        peers.record_failure(SessionId::new(1));
+        assert_eq!(peers.choose(5, &[]).unwrap().0, SessionId::new(3));
+
+        // Fail over to the untried peers.
+        let tried = vec![SessionId::new(3)];
+        assert_eq!(peers.choose(5, &tried).unwrap().0, SessionId::new(2));
+
+        // The peers serving invalid data are banned.
+        peers.record_invalid(SessionId::new(2));
+        assert_eq!(peers.choose(5, &tried).unwrap().0, SessionId::new(1));
+        peers.record_invalid(SessionId::new(1));
+        assert!(peers.choose(5, &tried).is_none());
+
+        // An announced epoch never goes back.
+        peers.update(&peer_ctx(3), 1);
+        assert_eq!(peers.choose(30, &[]).unwrap().0, SessionId::new(3));
+    }
+}
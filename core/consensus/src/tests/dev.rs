@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::executor::block_on;
+
+use common_merkle::Merkle;
+use protocol::traits::executor::RewardSchedule;
+use protocol::traits::{ConsensusMonitor, Context, CurrentConsensusStatus, NodeInfo};
+use protocol::types::{Hash, ValidatorSet};
+
+use crate::dev::DevConsensus;
+
+use super::{mock_bls_key, mock_proof, mock_validator, MockAdapter};
+
+fn mock_dev(interval: Option<u64>) -> (DevConsensus<MockAdapter>, Arc<MockAdapter>) {
+    let validator = mock_validator(1, &mock_bls_key(1));
+    let status = CurrentConsensusStatus {
+        cycles_price:       1,
+        cycles_limit:       300_000_000,
+        epoch_id:           1,
+        prev_hash:          Hash::digest(Bytes::from("genesis")),
+        logs_bloom:         Default::default(),
+        exec_epoch_id:      0,
+        state_root:         Hash::digest(Bytes::from("state 0")),
+        cycles_used:        0,
+        proof:              mock_proof(0),
+        validators:         vec![validator.clone()],
+        validator_version:  0,
+        reward_schedule:    RewardSchedule::default(),
+        consensus_interval: 3000,
+    };
+    let node_info = NodeInfo {
+        chain_id:     Hash::digest(Bytes::from("chain")),
+        self_address: validator.address,
+    };
+
+    let adapter = Arc::new(MockAdapter::default());
+    let dev = DevConsensus::new(status, node_info, Arc::clone(&adapter), interval);
+    (dev, adapter)
+}
+
+#[test]
+fn test_seal_empty_pool() {
+    let (dev, adapter) = mock_dev(None);
+
+    // Nothing to seal instantly.
+    assert!(!block_on(dev.seal(Context::new())).unwrap());
+    assert!(adapter.epochs.lock().is_empty());
+    assert_eq!(dev.consensus_status().epoch_id, 1);
+}
+
+#[test]
+fn test_seal_txs() {
+    let (dev, adapter) = mock_dev(None);
+    let tx_hashes = vec![
+        Hash::digest(Bytes::from("tx 1")),
+        Hash::digest(Bytes::from("tx 2")),
+    ];
+    *adapter.mempool.lock() = tx_hashes.clone();
+
+    assert!(block_on(dev.seal(Context::new())).unwrap());
+    let epoch = adapter.epochs.lock()[&1].clone();
+    let order_root = Merkle::from_hashes(tx_hashes.clone())
+        .get_root_hash()
+        .unwrap();
+    assert_eq!(epoch.ordered_tx_hashes, tx_hashes);
+    assert_eq!(epoch.header.pre_hash, Hash::digest(Bytes::from("genesis")));
+    assert_eq!(epoch.header.order_root, order_root.clone());
+
+    // The epoch carries its own execution results.
+    assert_eq!(epoch.header.exec_epoch_id, 1);
+    assert_eq!(epoch.header.confirm_root, vec![order_root]);
+    assert_eq!(
+        epoch.header.state_root,
+        Hash::digest(Bytes::from("state 1"))
+    );
+
+    // The proof only links the previous epoch.
+    let proofs = adapter.proofs.lock().clone();
+    assert_eq!(proofs.len(), 1);
+    assert_eq!(proofs[0].epoch_id, 0);
+    assert_eq!(proofs[0].epoch_hash, Hash::digest(Bytes::from("genesis")));
+
+    // The sealed transactions are flushed.
+    assert!(adapter.mempool.lock().is_empty());
+    assert_eq!(dev.consensus_status().epoch_id, 2);
+    assert!(!block_on(dev.seal(Context::new())).unwrap());
+}
+
+#[test]
+fn test_seal_on_timer() {
+    let (dev, adapter) = mock_dev(Some(10));
+
+    // An empty epoch is sealed on the timer.
+    assert!(block_on(dev.seal(Context::new())).unwrap());
+    assert!(block_on(dev.seal(Context::new())).unwrap());
+
+    let epochs = adapter.epochs.lock().clone();
+    assert_eq!(epochs.len(), 2);
+    assert!(epochs[&2].ordered_tx_hashes.is_empty());
+    assert_eq!(epochs[&2].header.order_root, Hash::from_empty());
+    assert_eq!(dev.consensus_status().epoch_id, 3);
+}
+
+#[test]
+fn test_seal_new_validators() {
+    let (dev, adapter) = mock_dev(Some(10));
+    let validator = mock_validator(1, &mock_bls_key(1));
+    let new_validator = mock_validator(2, &mock_bls_key(2));
+    *adapter.validator_set.lock() = Some(ValidatorSet {
+        version:           1,
+        activate_epoch_id: 2,
+        validators:        vec![new_validator.clone()],
+    });
+
+    // The new validator set takes effect at the next epoch.
+    block_on(dev.seal(Context::new())).unwrap();
+    block_on(dev.seal(Context::new())).unwrap();
+
+    let epochs = adapter.epochs.lock().clone();
+    assert_eq!(epochs[&1].header.validator_version, 0);
+    assert_eq!(epochs[&1].header.validators, vec![validator]);
+    assert_eq!(epochs[&2].header.validator_version, 1);
+    assert_eq!(epochs[&2].header.validators, vec![new_validator]);
+}
@@ -0,0 +1,356 @@
+use bytes::Bytes;
+use futures::executor::block_on;
+use overlord::types::{
+    AggregatedSignature, AggregatedVote, Proposal, SignedProposal, SignedVote, Vote, VoteType,
+};
+use overlord::{Consensus as Engine, Crypto};
+
+use common_merkle::Merkle;
+use protocol::fixed_codec::ProtocolFixedCodec;
+use protocol::traits::Context;
+use protocol::types::{Epoch, Hash, Pill};
+
+use crate::engine::time_now;
+use crate::fixed_types::FixedPill;
+use crate::util::OverlordCrypto;
+use crate::ConsensusError;
+
+use super::{
+    assert_consensus_err, mock_bls_key, mock_engine, mock_epoch, mock_validator, TestEngine,
+    EPOCH_ID,
+};
+
+impl TestEngine {
+    // A header that passes all the checks.
+    pub fn valid_epoch(&self) -> Epoch {
+        let prev_hash = Hash::digest(self.prev.encode_fixed().unwrap());
+        let mut epoch = mock_epoch(
+            &self.prev.header.chain_id,
+            EPOCH_ID,
+            prev_hash,
+            self.prev.header.timestamp + 3,
+        );
+        epoch.ordered_tx_hashes = vec![Hash::digest(Bytes::from("tx"))];
+        epoch.header.order_root = Merkle::from_hashes(epoch.ordered_tx_hashes.clone())
+            .get_root_hash()
+            .unwrap();
+        epoch.header.exec_epoch_id = self.executed.epoch_id;
+        epoch.header.confirm_root = vec![self.executed.order_root.clone()];
+        epoch.header.state_root = self.executed.state_root.clone();
+        epoch.header.receipt_root = vec![self.executed.receipt_root.clone()];
+        epoch.header.proposer = self.validator.address.clone();
+        epoch.header.validators = vec![self.validator.clone()];
+        epoch
+    }
+
+    fn sign_vote(&self, epoch_id: u64, epoch_hash: &str) -> SignedVote {
+        let vote = Vote {
+            epoch_id,
+            round: 0,
+            vote_type: VoteType::Prevote,
+            epoch_hash: Hash::digest(Bytes::from(epoch_hash)).as_bytes(),
+        };
+        let crypto = OverlordCrypto::new(self.validator.address.clone(), self.key.clone(), &[self
+            .validator
+            .clone()]);
+        let signature = crypto
+            .sign(crypto.hash(Bytes::from(rlp::encode(&vote))))
+            .unwrap();
+
+        SignedVote { signature, vote }
+    }
+
+    // Propose the epoch signed by the validator, then check it against the
+    // proposed hash.
+    fn check(&self, epoch: Epoch, hash: Option<Bytes>) -> Result<(), String> {
+        let epoch_hash = Hash::digest(epoch.encode_fixed().unwrap()).as_bytes();
+        let pill = FixedPill {
+            inner: Pill {
+                epoch,
+                propose_hashes: Vec::new(),
+            },
+        };
+        let proposal = Proposal {
+            epoch_id:   EPOCH_ID,
+            round:      0,
+            content:    pill.clone(),
+            epoch_hash: epoch_hash.clone(),
+            lock:       None,
+            proposer:   self.validator.address.as_bytes(),
+        };
+        let crypto = OverlordCrypto::new(self.validator.address.clone(), self.key.clone(), &[self
+            .validator
+            .clone()]);
+        let signature = crypto
+            .sign(crypto.hash(Bytes::from(rlp::encode(&proposal))))
+            .unwrap();
+        self.engine.record_proposal(&SignedProposal {
+            signature,
+            proposal,
+        });
+
+        let hash = hash.unwrap_or(epoch_hash);
+        block_on(
+            self.engine
+                .check_epoch(Context::new(), EPOCH_ID, hash, pill),
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+}
+
+#[test]
+fn test_check_valid_header() {
+    let test = mock_engine();
+    assert!(test.check(test.valid_epoch(), None).is_ok());
+}
+
+#[test]
+fn test_check_header_hash() {
+    let test = mock_engine();
+    let other = Hash::digest(Bytes::from("other")).as_bytes();
+
+    assert_consensus_err(
+        test.check(test.valid_epoch(), Some(other)),
+        ConsensusError::InvalidEpochHash { epoch_id: EPOCH_ID },
+    );
+}
+
+#[test]
+fn test_check_header_chain_id() {
+    let test = mock_engine();
+    let mut epoch = test.valid_epoch();
+    let chain_id = Hash::digest(Bytes::from("other chain"));
+    epoch.header.chain_id = chain_id.clone();
+
+    assert_consensus_err(test.check(epoch, None), ConsensusError::InvalidChainId {
+        epoch_id: EPOCH_ID,
+        chain_id,
+    });
+}
+
+#[test]
+fn test_check_header_epoch_id() {
+    let test = mock_engine();
+    let mut epoch = test.valid_epoch();
+    epoch.header.epoch_id = EPOCH_ID + 1;
+
+    assert_consensus_err(test.check(epoch, None), ConsensusError::InvalidEpochId {
+        expect: EPOCH_ID,
+        actual: EPOCH_ID + 1,
+    });
+}
+
+#[test]
+fn test_check_header_pre_hash() {
+    let test = mock_engine();
+    let mut epoch = test.valid_epoch();
+    let pre_hash = Hash::digest(Bytes::from("other epoch"));
+    epoch.header.pre_hash = pre_hash.clone();
+
+    assert_consensus_err(test.check(epoch, None), ConsensusError::InvalidPrevHash {
+        epoch_id: EPOCH_ID,
+        pre_hash,
+    });
+}
+
+#[test]
+fn test_check_header_timestamp() {
+    let test = mock_engine();
+
+    // Earlier than the previous epoch.
+    let mut epoch = test.valid_epoch();
+    epoch.header.timestamp = test.prev.header.timestamp - 1;
+    assert_consensus_err(test.check(epoch, None), ConsensusError::InvalidTimestamp {
+        epoch_id:  EPOCH_ID,
+        timestamp: test.prev.header.timestamp - 1,
+    });
+
+    // Drifts too far into the future.
+    let mut epoch = test.valid_epoch();
+    let timestamp = time_now() + 60;
+    epoch.header.timestamp = timestamp;
+    assert_consensus_err(test.check(epoch, None), ConsensusError::InvalidTimestamp {
+        epoch_id: EPOCH_ID,
+        timestamp,
+    });
+
+    // The same timestamp as the previous epoch is accepted.
+    let mut epoch = test.valid_epoch();
+    epoch.header.timestamp = test.prev.header.timestamp;
+    assert!(test.check(epoch, None).is_ok());
+}
+
+#[test]
+fn test_check_header_order_root() {
+    let test = mock_engine();
+    let mut epoch = test.valid_epoch();
+    epoch
+        .ordered_tx_hashes
+        .push(Hash::digest(Bytes::from("other tx")));
+
+    assert_consensus_err(
+        test.check(epoch.clone(), None),
+        ConsensusError::InvalidOrderRoot {
+            epoch_id:   EPOCH_ID,
+            order_root: epoch.header.order_root,
+        },
+    );
+}
+
+#[test]
+fn test_check_header_exec_roots() {
+    let test = mock_engine();
+
+    // The executed epoch can not be the current epoch.
+    let mut epoch = test.valid_epoch();
+    epoch.header.exec_epoch_id = EPOCH_ID;
+    assert_consensus_err(
+        test.check(epoch, None),
+        ConsensusError::InvalidExecEpochId {
+            epoch_id:      EPOCH_ID,
+            exec_epoch_id: EPOCH_ID,
+        },
+    );
+
+    let mut epoch = test.valid_epoch();
+    epoch.header.confirm_root = Vec::new();
+    assert_consensus_err(
+        test.check(epoch, None),
+        ConsensusError::InvalidConfirmRoot { epoch_id: EPOCH_ID },
+    );
+
+    let mut epoch = test.valid_epoch();
+    let state_root = Hash::digest(Bytes::from("other state"));
+    epoch.header.state_root = state_root.clone();
+    assert_consensus_err(test.check(epoch, None), ConsensusError::InvalidStateRoot {
+        epoch_id: EPOCH_ID,
+        state_root,
+    });
+
+    let mut epoch = test.valid_epoch();
+    epoch.header.receipt_root = vec![Hash::digest(Bytes::from("other receipt"))];
+    assert_consensus_err(
+        test.check(epoch, None),
+        ConsensusError::InvalidReceiptRoot { epoch_id: EPOCH_ID },
+    );
+}
+
+#[test]
+fn test_check_header_validators() {
+    let test = mock_engine();
+
+    let mut epoch = test.valid_epoch();
+    epoch.header.validator_version = 2;
+    assert_consensus_err(test.check(epoch, None), ConsensusError::InvalidValidators {
+        epoch_id: EPOCH_ID,
+    });
+
+    let mut epoch = test.valid_epoch();
+    epoch.header.validators[0].vote_weight = 2;
+    assert_consensus_err(test.check(epoch, None), ConsensusError::InvalidValidators {
+        epoch_id: EPOCH_ID,
+    });
+}
+
+#[test]
+fn test_check_header_proposer() {
+    let test = mock_engine();
+
+    // The proposal is signed by another validator than the header proposer.
+    let mut epoch = test.valid_epoch();
+    let proposer = mock_validator(2, &mock_bls_key(2)).address;
+    epoch.header.proposer = proposer.clone();
+    assert_consensus_err(test.check(epoch, None), ConsensusError::InvalidProposer {
+        epoch_id: EPOCH_ID,
+        proposer,
+    });
+}
+
+#[test]
+fn test_record_double_sign() {
+    let test = mock_engine();
+
+    assert!(test
+        .engine
+        .record_vote(&test.sign_vote(EPOCH_ID, "a"))
+        .is_none());
+    let evidence = test
+        .engine
+        .record_vote(&test.sign_vote(EPOCH_ID, "b"))
+        .unwrap();
+    block_on(test.engine.save_evidence(Context::new(), evidence.clone())).unwrap();
+    assert_eq!(
+        test.adapter.evidences.lock().clone(),
+        vec![evidence.clone()]
+    );
+
+    // The evidence of an unknown validator is dropped.
+    let mut forged = evidence;
+    forged.validator = mock_validator(2, &mock_bls_key(2)).address;
+    block_on(test.engine.save_evidence(Context::new(), forged)).unwrap();
+    assert_eq!(test.adapter.evidences.lock().len(), 1);
+}
+
+#[test]
+fn test_ignore_far_epoch_messages() {
+    let test = mock_engine();
+
+    // The messages of far epochs are not recorded.
+    for &epoch_id in [EPOCH_ID - 2, EPOCH_ID + 2].iter() {
+        assert!(test
+            .engine
+            .record_vote(&test.sign_vote(epoch_id, "a"))
+            .is_none());
+        assert!(test
+            .engine
+            .record_vote(&test.sign_vote(epoch_id, "b"))
+            .is_none());
+    }
+
+    // The next epoch is near.
+    assert!(test
+        .engine
+        .record_vote(&test.sign_vote(EPOCH_ID + 1, "a"))
+        .is_none());
+    assert!(test
+        .engine
+        .record_vote(&test.sign_vote(EPOCH_ID + 1, "b"))
+        .is_some());
+}
+
+#[test]
+fn test_record_verified_qc() {
+    let test = mock_engine();
+    let signed_vote = test.sign_vote(EPOCH_ID, "a");
+    let crypto = OverlordCrypto::new(test.validator.address.clone(), test.key.clone(), &[test
+        .validator
+        .clone()]);
+    let signature = crypto
+        .aggregate_signatures(vec![signed_vote.signature.clone()], vec![test
+            .validator
+            .address
+            .as_bytes()])
+        .unwrap();
+    let vote = signed_vote.vote;
+    let mut qc = AggregatedVote {
+        signature:  AggregatedSignature {
+            signature,
+            address_bitmap: Bytes::from(vec![0x80]),
+        },
+        vote_type:  VoteType::Prevote,
+        epoch_id:   vote.epoch_id,
+        round:      vote.round,
+        epoch_hash: vote.epoch_hash,
+        leader:     test.validator.address.as_bytes(),
+    };
+
+    // A forged QC does not lock the monitor.
+    qc.round = 1;
+    assert!(test.engine.record_qc(&qc).is_err());
+    assert!(test.engine.monitor().status(false).locked.is_none());
+
+    qc.round = 0;
+    test.engine.record_qc(&qc).unwrap();
+    assert!(test.engine.monitor().status(false).locked.is_some());
+}
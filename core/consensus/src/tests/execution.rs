@@ -0,0 +1,46 @@
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use futures::channel::mpsc::channel;
+use futures::executor::block_on;
+use futures::SinkExt;
+
+use crate::execution::{execute_epochs, ExecTask};
+use crate::ConsensusError;
+
+use super::{assert_consensus_err, mock_engine, EPOCH_ID};
+
+#[test]
+fn test_execute_committed_epoch_failed() {
+    let test = mock_engine();
+    test.adapter.exec_failed.store(true, Ordering::SeqCst);
+    let (mut sender, receiver) = channel(1);
+    let task = ExecTask {
+        epoch: test.valid_epoch(),
+        txs:   Vec::new(),
+    };
+
+    // The error is returned instead of waiting for the next epoch.
+    block_on(sender.send(task)).unwrap();
+    let (_, executed_root) = test.engine.exec_status().latest();
+    assert_consensus_err(
+        block_on(execute_epochs(Arc::clone(&test.engine), receiver, EPOCH_ID - 1)),
+        ConsensusError::Other("execute failed".to_owned()),
+    );
+    assert_eq!(test.engine.exec_status().latest(), (EPOCH_ID - 1, executed_root));
+}
+
+#[test]
+fn test_execute_stored_epoch_failed() {
+    let test = mock_engine();
+    test.adapter.exec_failed.store(true, Ordering::SeqCst);
+    let (_sender, receiver) = channel(1);
+
+    // The committed epoch not executed before the restart.
+    let epoch = test.valid_epoch();
+    test.adapter.epochs.lock().insert(EPOCH_ID, epoch);
+    assert_consensus_err(
+        block_on(execute_epochs(Arc::clone(&test.engine), receiver, EPOCH_ID)),
+        ConsensusError::Other("execute failed".to_owned()),
+    );
+}
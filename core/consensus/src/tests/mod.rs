@@ -0,0 +1,342 @@
+mod engine;
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use parking_lot::Mutex;
+
+use common_crypto::{BLS12381PrivateKey, PrivateKey, PublicKey};
+use protocol::traits::executor::{EpochReward, ExecutorExecResp};
+use protocol::traits::{ConsensusAdapter, Context, MessageTarget, MixedTxHashes, NodeInfo};
+use protocol::types::{
+    Address, Epoch, Evidence, Hash, MerkleRoot, Proof, Receipt, SignedTransaction,
+    TransactionTrace, UserAddress, Validator, ValidatorSet,
+};
+use protocol::{ProtocolError, ProtocolResult};
+
+use crate::ConsensusError;
+
+/// An adapter backed by the epochs and the transaction hashes in memory, the
+/// transactions are always valid. The other methods are not used by the
+/// tests.
+#[derive(Default)]
+pub struct MockAdapter {
+    pub epochs:        Mutex<HashMap<u64, Epoch>>,
+    pub proofs:        Mutex<Vec<Proof>>,
+    pub evidences:     Mutex<Vec<Evidence>>,
+    pub mempool:       Mutex<Vec<Hash>>,
+    pub validator_set: Mutex<Option<ValidatorSet>>,
+    pub exec_failed:   AtomicBool,
+}
+
+impl MockAdapter {
+    pub fn with_epochs(epochs: Vec<Epoch>) -> Self {
+        let epochs = epochs
+            .into_iter()
+            .map(|epoch| (epoch.header.epoch_id, epoch))
+            .collect();
+
+        MockAdapter {
+            epochs: Mutex::new(epochs),
+            ..MockAdapter::default()
+        }
+    }
+}
+
+#[async_trait]
+impl ConsensusAdapter for MockAdapter {
+    async fn get_txs_from_mempool(
+        &self,
+        _ctx: Context,
+        _epoch_id: u64,
+        _cycle_limit: u64,
+    ) -> ProtocolResult<MixedTxHashes> {
+        Ok(MixedTxHashes {
+            order_tx_hashes:   self.mempool.lock().clone(),
+            propose_tx_hashes: Vec::new(),
+        })
+    }
+
+    async fn check_txs(&self, _ctx: Context, _txs: Vec<Hash>) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn sync_txs(&self, _ctx: Context, _txs: Vec<Hash>) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn get_full_txs(
+        &self,
+        _ctx: Context,
+        _txs: Vec<Hash>,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        Ok(Vec::new())
+    }
+
+    async fn transmit(
+        &self,
+        _ctx: Context,
+        _msg: Vec<u8>,
+        _end: &str,
+        _target: MessageTarget,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn execute(
+        &self,
+        _node_info: NodeInfo,
+        _state_root: MerkleRoot,
+        epoch_id: u64,
+        _cycles_price: u64,
+        _coinbase: Address,
+        _signed_txs: Vec<SignedTransaction>,
+        _reward: EpochReward,
+    ) -> ProtocolResult<ExecutorExecResp> {
+        if self.exec_failed.load(Ordering::SeqCst) {
+            return Err(ConsensusError::Other("execute failed".to_owned()).into());
+        }
+
+        Ok(ExecutorExecResp {
+            receipts:        Vec::new(),
+            all_cycles_used: Vec::new(),
+            logs_bloom:      Default::default(),
+            state_root:      Hash::digest(Bytes::from(format!("state {}", epoch_id))),
+            traces:          Vec::new(),
+        })
+    }
+
+    async fn flush_mempool(&self, _ctx: Context, txs: Vec<Hash>) -> ProtocolResult<()> {
+        self.mempool.lock().retain(|tx| !txs.contains(tx));
+        Ok(())
+    }
+
+    async fn save_epoch(&self, _ctx: Context, epoch: Epoch) -> ProtocolResult<()> {
+        self.epochs.lock().insert(epoch.header.epoch_id, epoch);
+        Ok(())
+    }
+
+    async fn save_receipts(&self, _ctx: Context, _receipts: Vec<Receipt>) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn save_traces(
+        &self,
+        _ctx: Context,
+        _traces: Vec<TransactionTrace>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn save_proof(&self, _ctx: Context, proof: Proof) -> ProtocolResult<()> {
+        self.proofs.lock().push(proof);
+        Ok(())
+    }
+
+    async fn save_signed_txs(
+        &self,
+        _ctx: Context,
+        _signed_txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn save_evidence(&self, _ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        self.evidences.lock().push(evidence);
+        Ok(())
+    }
+
+    async fn get_last_validators(
+        &self,
+        _node_info: NodeInfo,
+        _state_root: MerkleRoot,
+        _epoch_id: u64,
+    ) -> ProtocolResult<Option<ValidatorSet>> {
+        Ok(self.validator_set.lock().clone())
+    }
+
+    async fn get_current_epoch_id(&self, _ctx: Context) -> ProtocolResult<u64> {
+        Ok(self.epochs.lock().keys().max().cloned().unwrap_or(0))
+    }
+
+    async fn pull_epoch(&self, _ctx: Context, _epoch_id: u64, _end: &str) -> ProtocolResult<Epoch> {
+        unimplemented!()
+    }
+
+    async fn pull_epochs(
+        &self,
+        _ctx: Context,
+        _begin: u64,
+        _count: u64,
+        _end: &str,
+    ) -> ProtocolResult<Vec<Epoch>> {
+        unimplemented!()
+    }
+
+    async fn pull_proof(&self, _ctx: Context, _epoch_id: u64, _end: &str) -> ProtocolResult<Proof> {
+        unimplemented!()
+    }
+
+    async fn pull_txs(
+        &self,
+        _ctx: Context,
+        _hashes: Vec<Hash>,
+        _end: &str,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        unimplemented!()
+    }
+
+    async fn get_epoch_by_id(&self, _ctx: Context, epoch_id: u64) -> ProtocolResult<Epoch> {
+        self.epochs
+            .lock()
+            .get(&epoch_id)
+            .cloned()
+            .ok_or_else(|| ConsensusError::MissingEpochHeader(epoch_id).into())
+    }
+
+    async fn get_signed_txs(
+        &self,
+        _ctx: Context,
+        _hashes: Vec<Hash>,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        Ok(Vec::new())
+    }
+}
+
+pub fn mock_bls_key(i: u8) -> BLS12381PrivateKey {
+    let mut key = [0u8; 48];
+    key[47] = i;
+    BLS12381PrivateKey::try_from(key.as_ref()).unwrap()
+}
+
+pub fn mock_validator(i: u8, key: &BLS12381PrivateKey) -> Validator {
+    Validator {
+        address:        UserAddress::from_hex(&format!(
+            "1000000000000000000000000000000000000000{:02}",
+            i
+        ))
+        .unwrap(),
+        bls_pub_key:    key.pub_key().to_bytes(),
+        propose_weight: 1,
+        vote_weight:    1,
+    }
+}
+
+pub fn mock_proof(epoch_id: u64) -> Proof {
+    Proof {
+        epoch_id,
+        round: 0,
+        epoch_hash: Hash::digest(Bytes::from(format!("epoch {}", epoch_id))),
+        signature: Bytes::new(),
+        bitmap: Bytes::new(),
+    }
+}
+
+// The engine is at epoch 3, epoch 1 is confirmed and epoch 2 is executed.
+pub const EPOCH_ID: u64 = 3;
+
+pub struct TestEngine {
+    pub engine:    Arc<ConsensusEngine<MockAdapter>>,
+    pub adapter:   Arc<MockAdapter>,
+    pub key:       BLS12381PrivateKey,
+    pub validator: Validator,
+    pub prev:      Epoch,
+    pub executed:  ExecutedInfo,
+}
+
+pub fn mock_epoch(chain_id: &Hash, epoch_id: u64, pre_hash: Hash, timestamp: u64) -> Epoch {
+    let nonce = Hash::digest(Bytes::from("XXXX"));
+    let header = EpochHeader {
+        chain_id: chain_id.clone(),
+        epoch_id,
+        pre_hash,
+        timestamp,
+        logs_bloom: Default::default(),
+        order_root: Hash::from_empty(),
+        confirm_root: Vec::new(),
+        exec_epoch_id: epoch_id - 1,
+        state_root: nonce.clone(),
+        receipt_root: Vec::new(),
+        cycles_used: 0,
+        proposer: UserAddress::from_hex("100000000000000000000000000000000000000000").unwrap(),
+        proof: mock_proof(epoch_id - 1),
+        validator_version: 1,
+        validators: Vec::new(),
+    };
+
+    Epoch {
+        header,
+        ordered_tx_hashes: Vec::new(),
+    }
+}
+
+pub fn mock_engine() -> TestEngine {
+    let key = mock_bls_key(1);
+    let validator = mock_validator(1, &key);
+    let chain_id = Hash::digest(Bytes::from("chain"));
+
+    let prev = mock_epoch(&chain_id, EPOCH_ID - 1, Hash::from_empty(), time_now() - 5);
+    let prev_hash = Hash::digest(prev.encode_fixed().unwrap());
+    let status = CurrentConsensusStatus {
+        cycles_price: 1,
+        cycles_limit: 300_000_000,
+        epoch_id: EPOCH_ID,
+        prev_hash,
+        logs_bloom: Default::default(),
+        exec_epoch_id: EPOCH_ID - 2,
+        state_root: Hash::digest(Bytes::from("state 1")),
+        cycles_used: 0,
+        proof: mock_proof(EPOCH_ID - 1),
+        validators: vec![validator.clone()],
+        validator_version: 1,
+        reward_schedule: RewardSchedule::default(),
+        consensus_interval: 3000,
+    };
+
+    let executed = ExecutedInfo {
+        epoch_id:     EPOCH_ID - 1,
+        order_root:   Hash::digest(Bytes::from("order 2")),
+        state_root:   Hash::digest(Bytes::from("state 2")),
+        receipt_root: Hash::digest(Bytes::from("receipt 2")),
+    };
+    let exec_status = ExecStatus::new(status.exec_epoch_id, status.state_root.clone());
+    exec_status.push(executed.clone());
+
+    let mut wal_path = std::env::temp_dir();
+    wal_path.push(format!("huobi_chain_engine_{}", random::<u64>()));
+    let node_info = NodeInfo {
+        chain_id,
+        self_address: validator.address.clone(),
+    };
+    let crypto = OverlordCrypto::new(validator.address.clone(), key.clone(), &status.validators);
+    let adapter = Arc::new(MockAdapter::with_epochs(vec![prev.clone()]));
+    let engine = Arc::new(ConsensusEngine::new(
+        Arc::new(RwLock::new(status)),
+        node_info,
+        Arc::clone(&adapter),
+        crypto,
+        Arc::new(ConsensusWal::new(wal_path).unwrap()),
+        Arc::new(exec_status),
+        channel(1).0,
+    ));
+
+    TestEngine {
+        engine,
+        adapter,
+        key,
+        validator,
+        prev,
+        executed,
+    }
+}
+
+pub fn assert_consensus_err<T: Debug, E: Display>(result: Result<T, E>, expect: ConsensusError) {
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        ProtocolError::from(expect).to_string()
+    );
+}
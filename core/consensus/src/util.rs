@@ -1,22 +1,38 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::error::Error;
+use std::sync::Arc;
 
 use bytes::Bytes;
-use overlord::{types::AggregatedSignature, Crypto};
+use overlord::types::{AggregatedSignature, AggregatedVote, Vote};
+use overlord::Crypto;
+use parking_lot::RwLock;
 
 use common_crypto::{
-    Crypto as Secp256k1Crypto, PrivateKey, PublicKey, Secp256k1, Secp256k1PrivateKey,
-    Secp256k1PublicKey, Signature,
+    BLS12381PrivateKey, BLS12381PublicKey, BLS12381Signature, Crypto as BlsCrypto, PrivateKey,
+    Signature, BLS12381,
 };
 
-use protocol::types::{Hash, UserAddress};
-use protocol::ProtocolError;
+use common_merkle::Merkle;
+use common_proof::verify_aggregated_vote;
+pub use common_proof::{check_proof, extract_voters};
+
+use protocol::fixed_codec::ProtocolFixedCodec;
+use protocol::types::{Hash, MerkleRoot, Receipt, UserAddress, Validator};
+use protocol::ProtocolResult;
 
 use crate::ConsensusError;
 
+const ADDRESS_LEN: usize = 21;
+
+/// Votes are signed by the BLS keys of validators, so that the signatures of
+/// a quorum can be aggregated into a compact proof.
 #[derive(Clone, Debug)]
 pub struct OverlordCrypto {
-    public_key:  Secp256k1PublicKey,
-    private_key: Secp256k1PrivateKey,
+    address:     UserAddress,
+    private_key: BLS12381PrivateKey,
+    // The BLS public keys of current validators indexed by address.
+    pub_keys: Arc<RwLock<HashMap<Bytes, Bytes>>>,
 }
 
 impl Crypto for OverlordCrypto {
@@ -24,52 +40,349 @@ impl Crypto for OverlordCrypto {
         Hash::digest(msg).as_bytes()
     }
 
+    /// The signature of a vote is the address of the voter followed by the
+    /// BLS signature.
     fn sign(&self, hash: Bytes) -> Result<Bytes, Box<dyn Error + Send>> {
-        let signature = Secp256k1::sign_message(&hash, &self.private_key.to_bytes())
-            .map_err(|e| ProtocolError::from(ConsensusError::CryptoErr(Box::new(e))))?
+        let signature = BLS12381::sign_message(&hash, &self.private_key.to_bytes())
+            .map_err(|e| ConsensusError::CryptoErr(Box::new(e)))?
             .to_bytes();
 
-        let mut res = self.public_key.to_bytes();
+        let mut res = self.address.as_bytes();
         res.extend_from_slice(&signature);
         Ok(res)
     }
 
     fn verify_signature(
         &self,
-        mut signature: Bytes,
+        signature: Bytes,
         hash: Bytes,
     ) -> Result<Bytes, Box<dyn Error + Send>> {
-        let tmp = signature.split_off(33);
-        let pub_key = signature;
-        let signature = tmp;
+        let (address, signature) = split_signature(signature)?;
+        let pub_key = self.get_pub_key(&address)?;
 
-        Secp256k1::verify_signature(&hash, &signature, &pub_key)
-            .map_err(|e| ProtocolError::from(ConsensusError::CryptoErr(Box::new(e))))?;
-        let address = UserAddress::from_pubkey_bytes(pub_key)?;
-        Ok(address.as_bytes())
+        BLS12381::verify_signature(&hash, &signature, &pub_key)
+            .map_err(|e| ConsensusError::CryptoErr(Box::new(e)))?;
+        Ok(address)
     }
 
     fn aggregate_signatures(
         &self,
-        _signatures: Vec<Bytes>,
-        _voters: Vec<Bytes>,
+        signatures: Vec<Bytes>,
+        voters: Vec<Bytes>,
     ) -> Result<Bytes, Box<dyn Error + Send>> {
-        Ok(Bytes::new())
+        let mut sigs_pub_keys = Vec::with_capacity(signatures.len());
+        for (signature, voter) in signatures.into_iter().zip(voters.iter()) {
+            let (_, signature) = split_signature(signature)?;
+            let signature = BLS12381Signature::try_from(signature.as_ref())
+                .map_err(|e| ConsensusError::CryptoErr(Box::new(e)))?;
+            let pub_key = BLS12381PublicKey::try_from(self.get_pub_key(voter)?.as_ref())
+                .map_err(|e| ConsensusError::CryptoErr(Box::new(e)))?;
+            sigs_pub_keys.push((signature, pub_key));
+        }
+
+        Ok(BLS12381Signature::combine(sigs_pub_keys).to_bytes())
     }
 
+    /// Overlord does not give the voted hash here, only the format is checked.
+    /// The received QCs are verified by `check_qc` before they are sent to
+    /// overlord, and the proofs by `check_proof`.
     fn verify_aggregated_signature(
         &self,
-        _aggregated_signature: AggregatedSignature,
+        aggregated_signature: AggregatedSignature,
     ) -> Result<(), Box<dyn Error + Send>> {
+        BLS12381Signature::try_from(aggregated_signature.signature.as_ref())
+            .map_err(|e| ConsensusError::CryptoErr(Box::new(e)))?;
         Ok(())
     }
 }
 
 impl OverlordCrypto {
-    pub fn new(public_key: Secp256k1PublicKey, private_key: Secp256k1PrivateKey) -> Self {
-        OverlordCrypto {
-            public_key,
+    pub fn new(
+        address: UserAddress,
+        private_key: BLS12381PrivateKey,
+        validators: &[Validator],
+    ) -> Self {
+        let crypto = OverlordCrypto {
+            address,
             private_key,
+            pub_keys: Arc::new(RwLock::new(HashMap::new())),
+        };
+        crypto.update_validators(validators);
+        crypto
+    }
+
+    /// Replace the BLS public keys when the validator set changes.
+    pub fn update_validators(&self, validators: &[Validator]) {
+        let pub_keys = validators
+            .iter()
+            .map(|v| (v.address.as_bytes(), v.bls_pub_key.clone()))
+            .collect();
+        *self.pub_keys.write() = pub_keys;
+    }
+
+    fn get_pub_key(&self, address: &Bytes) -> ProtocolResult<Bytes> {
+        let pub_key = self
+            .pub_keys
+            .read()
+            .get(address)
+            .cloned()
+            .ok_or_else(|| ConsensusError::Other(format!("unknown voter {:?}", address)))?;
+        Ok(pub_key)
+    }
+}
+
+/// Check that the QC is aggregated from the votes of more than 2/3 vote weight
+/// of the validators, the voted hash is rebuilt from the fields of the QC.
+pub fn check_qc(qc: &AggregatedVote, validators: &[Validator]) -> ProtocolResult<()> {
+    let vote = Vote {
+        epoch_id:   qc.epoch_id,
+        round:      qc.round,
+        vote_type:  qc.vote_type.clone(),
+        epoch_hash: qc.epoch_hash.clone(),
+    };
+
+    verify_aggregated_vote(
+        &vote,
+        &qc.signature.signature,
+        &qc.signature.address_bitmap,
+        validators,
+    )
+    .map_err(|reason| {
+        ConsensusError::InvalidQC {
+            epoch_id: qc.epoch_id,
+            round:    qc.round,
+            reason:   reason.to_owned(),
+        }
+        .into()
+    })
+}
+
+/// Split the signature of a vote into the address of the voter and the BLS
+/// signature.
+pub(crate) fn split_signature(mut signature: Bytes) -> ProtocolResult<(Bytes, Bytes)> {
+    if signature.len() <= ADDRESS_LEN {
+        return Err(ConsensusError::Other("vote signature too short".to_owned()).into());
+    }
+    let bls_signature = signature.split_off(ADDRESS_LEN);
+    Ok((signature, bls_signature))
+}
+
+/// The merkle root of the receipts after executing an epoch.
+pub fn receipt_root(receipts: &[Receipt]) -> ProtocolResult<MerkleRoot> {
+    let hashes = receipts
+        .iter()
+        .map(|receipt| Ok(Hash::digest(receipt.to_owned().encode_fixed()?)))
+        .collect::<ProtocolResult<Vec<_>>>()?;
+
+    Ok(Merkle::from_hashes(hashes)
+        .get_root_hash()
+        .unwrap_or_else(Hash::from_empty))
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryFrom;
+
+    use bytes::Bytes;
+    use overlord::types::{AggregatedSignature, AggregatedVote, Vote, VoteType};
+    use overlord::Crypto;
+
+    use common_crypto::{BLS12381PrivateKey, PrivateKey, PublicKey};
+    use protocol::fixed_codec::ProtocolFixedCodec;
+    use protocol::types::{Bloom, Epoch, EpochHeader, Hash, Proof, UserAddress, Validator};
+
+    use super::{check_proof, check_qc, extract_voters, OverlordCrypto};
+
+    fn mock_validator(address: &str) -> Validator {
+        Validator {
+            address:        UserAddress::from_hex(address).unwrap(),
+            bls_pub_key:    Bytes::new(),
+            propose_weight: 1,
+            vote_weight:    1,
+        }
+    }
+
+    #[test]
+    fn test_extract_voters() {
+        let validators = (1..=9)
+            .rev()
+            .map(|i| mock_validator(&format!("1000000000000000000000000000000000000000{:02}", i)))
+            .collect::<Vec<_>>();
+
+        // The 1st, 3rd and 9th validators of the sorted authority list.
+        let bitmap = Bytes::from(vec![0b1010_0000, 0b1000_0000]);
+        let voters = extract_voters(&validators, &bitmap);
+
+        assert_eq!(voters, vec![
+            mock_validator("100000000000000000000000000000000000000001"),
+            mock_validator("100000000000000000000000000000000000000003"),
+            mock_validator("100000000000000000000000000000000000000009"),
+        ]);
+        assert!(extract_voters(&validators, &Bytes::new()).is_empty());
+    }
+
+    #[test]
+    fn test_check_proof() {
+        let keys = (1..=4u8).map(mock_bls_key).collect::<Vec<_>>();
+        let validators = mock_validators(&keys);
+        let epoch = mock_epoch(1, validators.clone());
+        let epoch_hash = Hash::digest(epoch.encode_fixed().unwrap());
+
+        // 3 of 4 validators are more than 2/3 vote weight.
+        let proof = mock_proof(&keys, &validators, &[0, 1, 2], 1, epoch_hash.clone());
+        assert!(check_proof(&epoch, &proof, &validators).is_ok());
+
+        let proof = mock_proof(&keys, &validators, &[0, 1], 1, epoch_hash.clone());
+        assert!(check_proof(&epoch, &proof, &validators).is_err());
+
+        let proof = mock_proof(&keys, &validators, &[0, 1, 2, 3], 2, epoch_hash.clone());
+        assert!(check_proof(&epoch, &proof, &validators).is_err());
+
+        let proof = mock_proof(&keys, &validators, &[0, 1, 2, 3], 1, Hash::from_empty());
+        assert!(check_proof(&epoch, &proof, &validators).is_err());
+
+        // The signature must be aggregated from the voters of the bitmap.
+        let mut proof = mock_proof(&keys, &validators, &[0, 1, 2], 1, epoch_hash.clone());
+        proof.bitmap = mock_proof(&keys, &validators, &[1, 2, 3], 1, epoch_hash).bitmap;
+        assert!(check_proof(&epoch, &proof, &validators).is_err());
+    }
+
+    #[test]
+    fn test_check_qc() {
+        let keys = (1..=4u8).map(mock_bls_key).collect::<Vec<_>>();
+        let validators = mock_validators(&keys);
+        let epoch_hash = Hash::digest(Bytes::from("epoch"));
+
+        let mock_qc = |signers: &[usize]| {
+            let proof = mock_proof(&keys, &validators, signers, 1, epoch_hash.clone());
+            AggregatedVote {
+                signature:  AggregatedSignature {
+                    signature:      proof.signature,
+                    address_bitmap: proof.bitmap,
+                },
+                vote_type:  VoteType::Precommit,
+                epoch_id:   1,
+                round:      0,
+                epoch_hash: epoch_hash.as_bytes(),
+                leader:     validators[0].address.as_bytes(),
+            }
+        };
+
+        assert!(check_qc(&mock_qc(&[0, 1, 2]), &validators).is_ok());
+        assert!(check_qc(&mock_qc(&[0, 1]), &validators).is_err());
+
+        // The voted fields are covered by the aggregated signature.
+        let mut qc = mock_qc(&[0, 1, 2]);
+        qc.round = 1;
+        assert!(check_qc(&qc, &validators).is_err());
+
+        let mut qc = mock_qc(&[0, 1, 2]);
+        qc.vote_type = VoteType::Prevote;
+        assert!(check_qc(&qc, &validators).is_err());
+
+        let mut qc = mock_qc(&[0, 1, 2]);
+        qc.epoch_hash = Hash::from_empty().as_bytes();
+        assert!(check_qc(&qc, &validators).is_err());
+    }
+
+    fn mock_validators(keys: &[BLS12381PrivateKey]) -> Vec<Validator> {
+        keys.iter()
+            .enumerate()
+            .map(|(i, key)| Validator {
+                address:        UserAddress::from_hex(&format!(
+                    "1000000000000000000000000000000000000000{:02}",
+                    i
+                ))
+                .unwrap(),
+                bls_pub_key:    key.pub_key().to_bytes(),
+                propose_weight: 1,
+                vote_weight:    1,
+            })
+            .collect()
+    }
+
+    fn mock_bls_key(i: u8) -> BLS12381PrivateKey {
+        let mut key = [0u8; 48];
+        key[47] = i;
+        BLS12381PrivateKey::try_from(key.as_ref()).unwrap()
+    }
+
+    fn mock_proof(
+        keys: &[BLS12381PrivateKey],
+        validators: &[Validator],
+        signers: &[usize],
+        epoch_id: u64,
+        epoch_hash: Hash,
+    ) -> Proof {
+        let vote = Vote {
+            epoch_id,
+            round: 0,
+            vote_type: VoteType::Precommit,
+            epoch_hash: epoch_hash.as_bytes(),
+        };
+        let hash = Hash::digest(Bytes::from(rlp::encode(&vote))).as_bytes();
+
+        let mut signatures = vec![];
+        let mut voters = vec![];
+        for &i in signers.iter() {
+            let address = validators[i].address.clone();
+            let crypto = OverlordCrypto::new(address.clone(), keys[i].clone(), validators);
+            signatures.push(crypto.sign(hash.clone()).unwrap());
+            voters.push(address);
+        }
+        let crypto =
+            OverlordCrypto::new(validators[0].address.clone(), keys[0].clone(), validators);
+        let signature = crypto
+            .aggregate_signatures(
+                signatures,
+                voters.iter().map(|address| address.as_bytes()).collect(),
+            )
+            .unwrap();
+
+        let mut authority = validators.to_vec();
+        authority.sort_by_key(|v| v.address.as_bytes());
+        let mut bitmap = vec![0u8; (authority.len() + 7) / 8];
+        for (index, validator) in authority.iter().enumerate() {
+            if voters.contains(&validator.address) {
+                bitmap[index / 8] |= 0x80 >> (index % 8);
+            }
+        }
+
+        Proof {
+            epoch_id,
+            round: 0,
+            epoch_hash,
+            signature,
+            bitmap: Bytes::from(bitmap),
+        }
+    }
+
+    fn mock_epoch(epoch_id: u64, validators: Vec<Validator>) -> Epoch {
+        Epoch {
+            header:            EpochHeader {
+                chain_id: Hash::from_empty(),
+                epoch_id,
+                pre_hash: Hash::from_empty(),
+                timestamp: 0,
+                logs_bloom: Bloom::default(),
+                order_root: Hash::from_empty(),
+                confirm_root: vec![],
+                exec_epoch_id: 0,
+                state_root: Hash::from_empty(),
+                receipt_root: vec![],
+                cycles_used: 0,
+                proposer: validators[0].address.clone(),
+                proof: Proof {
+                    epoch_id:   0,
+                    round:      0,
+                    epoch_hash: Hash::from_empty(),
+                    signature:  Bytes::new(),
+                    bitmap:     Bytes::new(),
+                },
+                validator_version: 0,
+                validators,
+            },
+            ordered_tx_hashes: vec![],
         }
     }
 }
@@ -0,0 +1,504 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use bytes::Bytes;
+use futures::channel::oneshot;
+use log::{error, warn};
+use parking_lot::Mutex;
+
+use protocol::ProtocolResult;
+
+use crate::ConsensusError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WalMsgType {
+    Proposal,
+    Prevote,
+    Precommit,
+    PrevoteQC,
+}
+
+/// A consensus message kept in the WAL, `msg` is the rlp encoded overlord
+/// message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WalMessage {
+    pub epoch_id: u64,
+    pub round:    u64,
+    pub msg_type: WalMsgType,
+    pub hash:     Bytes,
+    pub msg:      Bytes,
+}
+
+/// The persisted consensus state of the current epoch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WalInfo {
+    pub epoch_id: u64,
+    pub round: u64,
+    /// The proposal of the latest precommit of this node.
+    pub lock: Option<WalMessage>,
+    /// The prevote QC of the locked proposal, overlord locks on it.
+    pub lock_qc: Option<WalMessage>,
+    /// The proposals and votes signed by this node.
+    pub signed: Vec<WalMessage>,
+}
+
+impl WalInfo {
+    /// The messages to replay into overlord when restarting at the epoch. The
+    /// locked proposal goes before its prevote QC, so that overlord knows the
+    /// content of the locked hash.
+    pub fn replay_msgs(&self, epoch_id: u64) -> Vec<WalMessage> {
+        if self.epoch_id != epoch_id {
+            return Vec::new();
+        }
+
+        self.lock
+            .iter()
+            .chain(self.lock_qc.iter())
+            .chain(self.signed.iter())
+            .cloned()
+            .collect()
+    }
+}
+
+type PersistTask = (WalInfo, oneshot::Sender<ProtocolResult<()>>);
+
+/// The write-ahead log of consensus. The messages signed by this node are
+/// persisted before being sent, so that a restarted validator keeps its lock
+/// and never signs two different messages in the same step.
+///
+/// The file is written and synced by a dedicated thread, the async callers
+/// only wait for the result.
+pub struct ConsensusWal {
+    inner: Mutex<WalState>,
+}
+
+struct WalState {
+    info: WalInfo,
+    // The proposals received in the current epoch indexed by hash, one of
+    // them becomes the lock when this node precommits.
+    proposals: HashMap<Bytes, WalMessage>,
+    // The latest prevote QC of each hash in the current epoch.
+    qcs:    HashMap<Bytes, WalMessage>,
+    writer: Sender<PersistTask>,
+}
+
+impl ConsensusWal {
+    pub fn new(path: PathBuf) -> ProtocolResult<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(wal_err)?;
+        }
+
+        let info = if path.exists() {
+            let bytes = fs::read(&path).map_err(wal_err)?;
+            rlp::decode(&bytes).map_err(wal_err)?
+        } else {
+            WalInfo::default()
+        };
+
+        let (writer, tasks) = mpsc::channel();
+        thread::Builder::new()
+            .name("consensus-wal".to_owned())
+            .spawn(move || write_loop(&path, tasks))
+            .map_err(wal_err)?;
+
+        Ok(ConsensusWal {
+            inner: Mutex::new(WalState {
+                info,
+                proposals: HashMap::new(),
+                qcs: HashMap::new(),
+                writer,
+            }),
+        })
+    }
+
+    pub fn info(&self) -> WalInfo {
+        self.inner.lock().info.clone()
+    }
+
+    /// Remember a received proposal, it is persisted only when this node
+    /// precommits its hash.
+    pub fn cache_proposal(&self, msg: WalMessage) {
+        let mut state = self.inner.lock();
+        if msg.epoch_id >= state.info.epoch_id {
+            state.proposals.insert(msg.hash.clone(), msg);
+        }
+    }
+
+    /// Remember a verified prevote QC, it is persisted with the lock when
+    /// this node precommits its hash.
+    pub fn cache_qc(&self, msg: WalMessage) {
+        let mut state = self.inner.lock();
+        if msg.msg_type != WalMsgType::PrevoteQC
+            || msg.hash.is_empty()
+            || msg.epoch_id < state.info.epoch_id
+        {
+            return;
+        }
+
+        let newer = state.qcs.get(&msg.hash).map_or(true, |qc| {
+            (qc.epoch_id, qc.round) < (msg.epoch_id, msg.round)
+        });
+        if newer {
+            state.qcs.insert(msg.hash.clone(), msg);
+        }
+    }
+
+    /// Persist a message signed by this node and return the message to send.
+    /// If a different message of the same step was signed before a restart,
+    /// the previous one is returned instead.
+    pub async fn save_signed(&self, msg: WalMessage) -> ProtocolResult<WalMessage> {
+        let persisted = {
+            let mut state = self.inner.lock();
+            if msg.epoch_id < state.info.epoch_id {
+                return Ok(msg);
+            }
+            if msg.epoch_id > state.info.epoch_id {
+                state.info = WalInfo {
+                    epoch_id: msg.epoch_id,
+                    ..WalInfo::default()
+                };
+                state.proposals.retain(|_, p| p.epoch_id >= msg.epoch_id);
+                state.qcs.retain(|_, qc| qc.epoch_id >= msg.epoch_id);
+            }
+
+            if let Some(signed) = state
+                .info
+                .signed
+                .iter()
+                .find(|signed| signed.round == msg.round && signed.msg_type == msg.msg_type)
+            {
+                if signed.hash != msg.hash {
+                    warn!(
+                        "consensus: refuse to sign conflicting {:?} of epoch {} round {}",
+                        msg.msg_type, msg.epoch_id, msg.round
+                    );
+                }
+                return Ok(signed.clone());
+            }
+
+            if msg.msg_type == WalMsgType::Precommit && !msg.hash.is_empty() {
+                let lock = state.proposals.get(&msg.hash).cloned().or_else(|| {
+                    state
+                        .info
+                        .signed
+                        .iter()
+                        .find(|p| p.msg_type == WalMsgType::Proposal && p.hash == msg.hash)
+                        .cloned()
+                });
+                if lock.is_some() {
+                    state.info.lock = lock;
+                    state.info.lock_qc = state.qcs.get(&msg.hash).cloned();
+                }
+            }
+            state.info.round = state.info.round.max(msg.round);
+            state.info.signed.push(msg.clone());
+
+            state.persist()
+        };
+
+        if let Err(e) = persisted.await {
+            // Forget the message, so that it is persisted again before it is
+            // sent.
+            self.inner
+                .lock()
+                .info
+                .signed
+                .retain(|signed| signed != &msg);
+            return Err(e);
+        }
+        Ok(msg)
+    }
+
+    /// Reset the log once the epoch is committed.
+    pub async fn commit(&self, epoch_id: u64) -> ProtocolResult<()> {
+        let persisted = {
+            let mut state = self.inner.lock();
+            state.info = WalInfo {
+                epoch_id: epoch_id + 1,
+                ..WalInfo::default()
+            };
+            state.proposals.clear();
+            state.qcs.clear();
+
+            state.persist()
+        };
+
+        persisted.await
+    }
+}
+
+impl WalState {
+    // Hand a snapshot to the writer thread. It is sent under the lock, so
+    // that the snapshots are written in order.
+    fn persist(&self) -> impl Future<Output = ProtocolResult<()>> {
+        let (done_tx, done_rx) = oneshot::channel();
+        let sent = self.writer.send((self.info.clone(), done_tx));
+
+        async move {
+            if sent.is_err() {
+                return Err(ConsensusError::WalErr("writer stopped".to_owned()).into());
+            }
+            done_rx
+                .await
+                .map_err(|_| ConsensusError::WalErr("writer stopped".to_owned()))?
+        }
+    }
+}
+
+fn write_loop(path: &Path, tasks: Receiver<PersistTask>) {
+    // Stop once the WAL is dropped.
+    for (info, done) in tasks.iter() {
+        let res = persist(path, &info);
+        if let Err(e) = &res {
+            error!("consensus: write WAL failed {:?}", e);
+        }
+        let _ = done.send(res);
+    }
+}
+
+// Write a temporary file then rename it, so that a crash never leaves a half
+// written log.
+fn persist(path: &Path, info: &WalInfo) -> ProtocolResult<()> {
+    let tmp_path = path.with_extension("tmp");
+
+    let mut file = File::create(&tmp_path).map_err(wal_err)?;
+    file.write_all(&rlp::encode(info)).map_err(wal_err)?;
+    file.sync_all().map_err(wal_err)?;
+    fs::rename(&tmp_path, path).map_err(wal_err)?;
+    Ok(())
+}
+
+fn wal_err<E: std::fmt::Debug>(e: E) -> ConsensusError {
+    ConsensusError::WalErr(format!("{:?}", e))
+}
+
+impl rlp::Encodable for WalMsgType {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let flag: u8 = match self {
+            WalMsgType::Proposal => 0,
+            WalMsgType::Prevote => 1,
+            WalMsgType::Precommit => 2,
+            WalMsgType::PrevoteQC => 3,
+        };
+        s.append(&flag);
+    }
+}
+
+impl rlp::Decodable for WalMsgType {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        match r.as_val::<u8>()? {
+            0 => Ok(WalMsgType::Proposal),
+            1 => Ok(WalMsgType::Prevote),
+            2 => Ok(WalMsgType::Precommit),
+            3 => Ok(WalMsgType::PrevoteQC),
+            _ => Err(rlp::DecoderError::Custom("invalid wal message type")),
+        }
+    }
+}
+
+impl rlp::Encodable for WalMessage {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(5)
+            .append(&self.epoch_id)
+            .append(&self.hash.to_vec())
+            .append(&self.msg.to_vec())
+            .append(&self.msg_type)
+            .append(&self.round);
+    }
+}
+
+impl rlp::Decodable for WalMessage {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() && r.size() != 5 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let epoch_id = r.at(0)?.as_val()?;
+        let hash = Bytes::from(r.at(1)?.data()?);
+        let msg = Bytes::from(r.at(2)?.data()?);
+        let msg_type = rlp::decode(r.at(3)?.as_raw())?;
+        let round = r.at(4)?.as_val()?;
+
+        Ok(WalMessage {
+            epoch_id,
+            round,
+            msg_type,
+            hash,
+            msg,
+        })
+    }
+}
+
+impl rlp::Encodable for WalInfo {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(5).append(&self.epoch_id);
+
+        // The lock and its QC are encoded as lists of zero or one message.
+        s.begin_list(self.lock.iter().count());
+        for lock in self.lock.iter() {
+            s.append(lock);
+        }
+
+        s.append(&self.round).append_list(&self.signed);
+
+        s.begin_list(self.lock_qc.iter().count());
+        for lock_qc in self.lock_qc.iter() {
+            s.append(lock_qc);
+        }
+    }
+}
+
+impl rlp::Decodable for WalInfo {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() && r.size() != 5 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let epoch_id = r.at(0)?.as_val()?;
+        let lock = r.at(1)?.as_list::<WalMessage>()?.into_iter().next();
+        let round = r.at(2)?.as_val()?;
+        let signed = r.at(3)?.as_list()?;
+        let lock_qc = r.at(4)?.as_list::<WalMessage>()?.into_iter().next();
+
+        Ok(WalInfo {
+            epoch_id,
+            round,
+            lock,
+            lock_qc,
+            signed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use rand::random;
+
+    use super::{ConsensusWal, WalInfo, WalMessage, WalMsgType};
+
+    fn temp_wal_path() -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("huobi_chain_wal_{}", random::<u64>()));
+        path
+    }
+
+    fn mock_msg(epoch_id: u64, round: u64, msg_type: WalMsgType, hash: &str) -> WalMessage {
+        WalMessage {
+            epoch_id,
+            round,
+            msg_type,
+            hash: Bytes::from(hash),
+            msg: Bytes::from(format!("{:?} {}", msg_type, hash)),
+        }
+    }
+
+    // Drop the WAL as a killed node and open it again.
+    fn restart(wal: ConsensusWal, path: &PathBuf) -> ConsensusWal {
+        drop(wal);
+        ConsensusWal::new(path.clone()).unwrap()
+    }
+
+    #[test]
+    fn test_restart_at_each_step() {
+        let path = temp_wal_path();
+        let wal = ConsensusWal::new(path.clone()).unwrap();
+        assert_eq!(wal.info(), WalInfo::default());
+
+        // Propose.
+        let proposal = mock_msg(1, 0, WalMsgType::Proposal, "a");
+        block_on(wal.save_signed(proposal.clone())).unwrap();
+        let wal = restart(wal, &path);
+        assert_eq!(wal.info().signed, vec![proposal.clone()]);
+
+        // Prevote.
+        let prevote = mock_msg(1, 0, WalMsgType::Prevote, "a");
+        block_on(wal.save_signed(prevote.clone())).unwrap();
+        let wal = restart(wal, &path);
+        assert_eq!(wal.info().signed, vec![proposal.clone(), prevote.clone()]);
+        assert_eq!(wal.info().lock, None);
+
+        // Precommit on the proposal of round 1 locks it with its prevote QC.
+        let other = mock_msg(1, 1, WalMsgType::Proposal, "b");
+        wal.cache_proposal(other.clone());
+        let stale_qc = mock_msg(1, 0, WalMsgType::PrevoteQC, "b");
+        let qc = mock_msg(1, 1, WalMsgType::PrevoteQC, "b");
+        wal.cache_qc(qc.clone());
+        wal.cache_qc(stale_qc);
+        let precommit = mock_msg(1, 1, WalMsgType::Precommit, "b");
+        block_on(wal.save_signed(precommit.clone())).unwrap();
+        let wal = restart(wal, &path);
+        let info = wal.info();
+        assert_eq!(info.round, 1);
+        assert_eq!(info.lock, Some(other.clone()));
+        assert_eq!(info.lock_qc, Some(qc.clone()));
+        assert_eq!(info.replay_msgs(1), vec![
+            other, qc, proposal, prevote, precommit
+        ]);
+        assert!(info.replay_msgs(2).is_empty());
+
+        // Commit.
+        block_on(wal.commit(1)).unwrap();
+        let wal = restart(wal, &path);
+        assert_eq!(wal.info(), WalInfo {
+            epoch_id: 2,
+            ..WalInfo::default()
+        });
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_no_conflicting_signature_after_restart() {
+        let path = temp_wal_path();
+        let wal = ConsensusWal::new(path.clone()).unwrap();
+
+        let prevote = mock_msg(1, 0, WalMsgType::Prevote, "a");
+        block_on(wal.save_signed(prevote.clone())).unwrap();
+        let wal = restart(wal, &path);
+
+        // The restarted node would vote another hash in the same round.
+        let conflict = mock_msg(1, 0, WalMsgType::Prevote, "b");
+        assert_eq!(block_on(wal.save_signed(conflict)).unwrap(), prevote);
+
+        let next_round = mock_msg(1, 1, WalMsgType::Prevote, "b");
+        assert_eq!(
+            block_on(wal.save_signed(next_round.clone())).unwrap(),
+            next_round
+        );
+
+        // A new epoch starts a new log.
+        let next_epoch = mock_msg(2, 0, WalMsgType::Prevote, "c");
+        block_on(wal.save_signed(next_epoch.clone())).unwrap();
+        let wal = restart(wal, &path);
+        assert_eq!(wal.info().signed, vec![next_epoch]);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_restart_during_persist() {
+        let path = temp_wal_path();
+        let wal = ConsensusWal::new(path.clone()).unwrap();
+
+        let prevote = mock_msg(1, 0, WalMsgType::Prevote, "a");
+        block_on(wal.save_signed(prevote.clone())).unwrap();
+
+        // Killed before the temporary file is renamed.
+        fs::write(path.with_extension("tmp"), b"half written").unwrap();
+        let wal = restart(wal, &path);
+        assert_eq!(wal.info().signed, vec![prevote]);
+
+        fs::remove_file(path.with_extension("tmp")).unwrap();
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -9,7 +9,7 @@ use derive_more::{Display, From};
 use protocol::fixed_codec::ProtocolFixedCodec;
 use protocol::traits::executor::contract::ContractStateAdapter;
 use protocol::traits::executor::{ContractSchema, TrieDB};
-use protocol::types::MerkleRoot;
+use protocol::types::{MerkleRoot, StateDiff};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::trie::MPTTrie;
@@ -23,6 +23,11 @@ pub struct GeneralContractStateAdapter<DB: TrieDB> {
     // serializations.
     cache_map: HashMap<Bytes, Bytes>,
     stash_map: HashMap<Bytes, Bytes>,
+
+    // The values of the keys in cache before they were inserted, it is only
+    // recorded when tracing is enabled.
+    trace_map: Option<HashMap<Bytes, Option<Bytes>>>,
+    diffs:     Vec<StateDiff>,
 }
 
 impl<DB: TrieDB> GeneralContractStateAdapter<DB> {
@@ -32,7 +37,40 @@ impl<DB: TrieDB> GeneralContractStateAdapter<DB> {
 
             cache_map: HashMap::new(),
             stash_map: HashMap::new(),
+
+            trace_map: None,
+            diffs: Vec::new(),
+        }
+    }
+
+    pub fn enable_trace(&mut self) {
+        self.trace_map = Some(HashMap::new());
+    }
+
+    // Take the state diffs stashed since the last call, sorted by key.
+    pub fn take_diffs(&mut self) -> Vec<StateDiff> {
+        let mut diffs = self.diffs.split_off(0);
+        diffs.sort_by(|a, b| a.key.cmp(&b.key));
+        diffs
+    }
+
+    fn trace_before(&mut self, key: &Bytes) -> ProtocolResult<()> {
+        let untraced = match &self.trace_map {
+            Some(trace_map) => !trace_map.contains_key(key),
+            None => false,
+        };
+        if !untraced {
+            return Ok(());
         }
+
+        let before = match self.stash_map.get(key) {
+            Some(value) => Some(value.clone()),
+            None => self.trie.get(key)?,
+        };
+        if let Some(trace_map) = self.trace_map.as_mut() {
+            trace_map.insert(key.clone(), before);
+        }
+        Ok(())
     }
 }
 
@@ -82,17 +120,34 @@ impl<DB: TrieDB> ContractStateAdapter for GeneralContractStateAdapter<DB> {
         key: <Schema as ContractSchema>::Key,
         value: <Schema as ContractSchema>::Value,
     ) -> ProtocolResult<()> {
-        self.cache_map
-            .insert(key.encode_fixed()?, value.encode_fixed()?);
+        let key = key.encode_fixed()?;
+        self.trace_before(&key)?;
+
+        self.cache_map.insert(key, value.encode_fixed()?);
         Ok(())
     }
 
     fn revert_cache(&mut self) -> ProtocolResult<()> {
         self.cache_map.clear();
+        if let Some(trace_map) = self.trace_map.as_mut() {
+            trace_map.clear();
+        }
         Ok(())
     }
 
     fn stash(&mut self) -> ProtocolResult<()> {
+        if let Some(trace_map) = self.trace_map.as_mut() {
+            for (key, before) in trace_map.drain() {
+                if let Some(after) = self.cache_map.get(&key) {
+                    self.diffs.push(StateDiff {
+                        key,
+                        before,
+                        after: after.clone(),
+                    });
+                }
+            }
+        }
+
         for (k, v) in self.cache_map.drain() {
             self.stash_map.insert(k, v);
         }
@@ -13,6 +13,8 @@ lazy_static! {
         let mut table = HashMap::new();
         table.insert(CyclesAction::AccountTransfer, NATIVE_BASE_CYCLES * 21);
         table.insert(CyclesAction::BankRegister, NATIVE_BASE_CYCLES * 210);
+        table.insert(CyclesAction::GovernancePropose, NATIVE_BASE_CYCLES * 210);
+        table.insert(CyclesAction::GovernanceApprove, NATIVE_BASE_CYCLES * 21);
         table
     };
 }
@@ -21,6 +23,8 @@ lazy_static! {
 pub enum CyclesAction {
     AccountTransfer,
     BankRegister,
+    GovernancePropose,
+    GovernanceApprove,
 }
 
 pub fn consume_cycles(
@@ -1,7 +1,7 @@
 use bytes::Bytes;
 
 use protocol::traits::executor::ContractSchema;
-use protocol::types::{Account, Address, Asset, AssetID};
+use protocol::types::{Account, Address, Asset, AssetID, Hash, ValidatorProposal, ValidatorSet};
 
 #[allow(dead_code)]
 pub struct FixedBytesSchema;
@@ -16,8 +16,26 @@ impl ContractSchema for FixedAssetSchema {
     type Value = Asset;
 }
 
+pub struct FixedAssetIDSchema;
+impl ContractSchema for FixedAssetIDSchema {
+    type Key = Hash;
+    type Value = AssetID;
+}
+
 pub struct FixedAccountSchema;
 impl ContractSchema for FixedAccountSchema {
     type Key = Address;
     type Value = Account;
 }
+
+pub struct FixedValidatorSetSchema;
+impl ContractSchema for FixedValidatorSetSchema {
+    type Key = Hash;
+    type Value = ValidatorSet;
+}
+
+pub struct FixedValidatorProposalSchema;
+impl ContractSchema for FixedValidatorProposalSchema {
+    type Key = Hash;
+    type Value = ValidatorProposal;
+}
@@ -7,6 +7,7 @@ mod tests;
 pub mod trie;
 
 use std::cell::RefCell;
+use std::cmp;
 use std::collections::HashMap;
 use std::error::Error;
 use std::num::ParseIntError;
@@ -17,19 +18,25 @@ use std::u64;
 use bytes::Bytes;
 use derive_more::{Display, From};
 
-use protocol::traits::executor::contract::{AccountContract, BankContract, ContractStateAdapter};
+use protocol::fixed_codec::ProtocolFixedCodec;
+use protocol::traits::executor::contract::{
+    AccountContract, BankContract, ContractStateAdapter, GovernanceContract,
+};
 use protocol::traits::executor::{
-    Executor, ExecutorExecResp, ExecutorFactory, InvokeContext, RcInvokeContext, TrieDB,
+    EpochReward, Executor, ExecutorExecResp, ExecutorFactory, InvokeContext, RcInvokeContext,
+    TrieDB,
 };
 use protocol::types::{
-    Address, AssetID, Balance, Bloom, ContractAddress, ContractType, Fee, Genesis, Hash,
-    MerkleRoot, Receipt, ReceiptResult, SignedTransaction, TransactionAction, UserAddress,
+    Address, AssetID, Balance, Bloom, ContractAddress, ContractTrace, ContractType, Fee, Genesis,
+    Hash, MerkleRoot, Receipt, ReceiptResult, Reward, SignedTransaction, TransactionAction,
+    TransactionTrace, UserAddress, Validator, ValidatorSet,
 };
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::adapter::{GeneralContractStateAdapter, RcGeneralContractStateAdapter};
 use crate::native_contract::{
-    NativeAccountContract, NativeBankContract, ACCOUNT_CONTRACT_ADDRESS, BANK_CONTRACT_ADDRESS,
+    NativeAccountContract, NativeBankContract, NativeGovernanceContract, ACCOUNT_CONTRACT_ADDRESS,
+    BANK_CONTRACT_ADDRESS, GOVERNANCE_CONTRACT_ADDRESS,
 };
 use crate::trie::MPTTrie;
 
@@ -38,15 +45,19 @@ pub struct TransactionExecutor<DB: TrieDB> {
     epoch_id:     u64,
     cycles_price: u64,
     coinbase:     Address,
+    trace:        bool,
 
     trie:              MPTTrie<DB>,
     account_contract:  NativeAccountContract<GeneralContractStateAdapter<DB>>,
     bank_account:      NativeBankContract<GeneralContractStateAdapter<DB>>,
+    governance:        NativeGovernanceContract<GeneralContractStateAdapter<DB>>,
     state_adapter_map: HashMap<Address, RcGeneralContractStateAdapter<DB>>,
 }
 
 impl<DB: TrieDB> Executor for TransactionExecutor<DB> {
     fn create_genesis(&mut self, genesis: &Genesis) -> ProtocolResult<MerkleRoot> {
+        genesis.validate()?;
+
         let ictx = InvokeContext {
             chain_id:       self.chain_id.clone(),
             cycles_price:   self.cycles_price,
@@ -60,34 +71,32 @@ impl<DB: TrieDB> Executor for TransactionExecutor<DB> {
         };
         let ictx = Rc::new(RefCell::new(ictx));
 
-        // create system token
-        let system_token = &genesis.system_token;
-        let code = Bytes::from(
-            hex::decode(system_token.code.clone()).map_err(TransactionExecutorError::from)?,
-        );
-        let token_contract_address = ContractAddress::from_code(code, 0, ContractType::Asset)?;
-
-        self.bank_account.register(
-            Rc::clone(&ictx),
-            &token_contract_address,
-            system_token.name.clone(),
-            system_token.symbol.clone(),
-            Balance::from(system_token.supply),
-        )?;
+        // create system token and other assets, the nonce of the manage contract
+        // is the position of the asset in genesis.
+        for (nonce, genesis_asset) in genesis.all_assets().enumerate() {
+            let asset = self.bank_account.register(
+                Rc::clone(&ictx),
+                &genesis_asset.manage_contract(nonce as u64)?,
+                genesis_asset.name.clone(),
+                genesis_asset.symbol.clone(),
+                Balance::from(genesis_asset.supply),
+            )?;
+            if nonce == 0 {
+                self.bank_account.set_native_asset(&asset.id)?;
+            }
+        }
+
+        self.governance.init_validators(genesis.validators()?)?;
 
         for alloc in &genesis.state_alloc {
-            let address = Address::from_hex(&alloc.address)?;
+            let address = alloc.address()?;
             self.account_contract.create_account(&address)?;
 
             for asset in &alloc.assets {
-                let asset_id = Hash::from_hex(&asset.asset_id)?;
-                let balance_byets =
-                    hex::decode(asset.balance.clone()).map_err(TransactionExecutorError::from)?;
-
                 self.account_contract.add_balance(
-                    &asset_id,
+                    &asset.asset_id()?,
                     &address,
-                    Balance::from_bytes_be(balance_byets.as_ref()),
+                    asset.balance()?,
                 )?;
             }
         }
@@ -96,8 +105,13 @@ impl<DB: TrieDB> Executor for TransactionExecutor<DB> {
         self.commit()
     }
 
-    fn exec(&mut self, signed_txs: Vec<SignedTransaction>) -> ProtocolResult<ExecutorExecResp> {
+    fn exec(
+        &mut self,
+        signed_txs: Vec<SignedTransaction>,
+        reward: EpochReward,
+    ) -> ProtocolResult<ExecutorExecResp> {
         let mut receipts = Vec::with_capacity(signed_txs.len());
+        let mut traces = vec![];
 
         for signed_tx in signed_txs.into_iter() {
             let tx_hash = signed_tx.tx_hash.clone();
@@ -111,10 +125,7 @@ impl<DB: TrieDB> Executor for TransactionExecutor<DB> {
             )?;
 
             let res = match self.dispatch(Rc::clone(&ictx), signed_tx) {
-                Ok(res) => {
-                    self.stash()?;
-                    res
-                }
+                Ok(res) => res,
                 Err(e) => {
                     self.revert()?;
                     ReceiptResult::Fail {
@@ -124,7 +135,14 @@ impl<DB: TrieDB> Executor for TransactionExecutor<DB> {
                 }
             };
 
+            // The nonce is increased even if the transaction fails, and stashed
+            // with its changes, so it is in the trace of the transaction.
             self.account_contract.inc_nonce(Rc::clone(&ictx))?;
+            self.stash()?;
+
+            if self.trace {
+                traces.push(self.take_trace(tx_hash.clone()));
+            }
 
             let receipt = Receipt {
                 state_root: Hash::from_empty(),
@@ -139,17 +157,30 @@ impl<DB: TrieDB> Executor for TransactionExecutor<DB> {
             receipts.push(receipt);
         }
 
-        //  Calculate the total fee and reward `coinbsae`
+        //  Calculate the total fee, then reward `coinbase` and the signers of the
+        //  previous epoch.
         let mut all_cycles_used: Vec<Fee> = vec![];
         for receipt in receipts.iter() {
             modify_all_cycles_used(&mut all_cycles_used, &receipt.cycles_used);
         }
-        for cycles_used in all_cycles_used.iter() {
-            self.account_contract.add_balance(
-                &cycles_used.asset_id,
-                &self.coinbase,
-                Balance::from(cycles_used.cycle),
-            )?;
+        let rewards = self.distribute_rewards(&all_cycles_used, reward)?;
+        self.stash()?;
+
+        if !rewards.is_empty() {
+            if self.trace {
+                traces.push(self.take_trace(reward_receipt_hash(self.epoch_id)));
+            }
+
+            receipts.push(Receipt {
+                state_root:  Hash::from_empty(),
+                epoch_id:    self.epoch_id,
+                tx_hash:     reward_receipt_hash(self.epoch_id),
+                cycles_used: Fee {
+                    asset_id: Hash::from_empty(),
+                    cycle:    0,
+                },
+                result:      ReceiptResult::Reward { rewards },
+            });
         }
 
         // commit state
@@ -163,15 +194,88 @@ impl<DB: TrieDB> Executor for TransactionExecutor<DB> {
             all_cycles_used,
             logs_bloom: Bloom::default(),
             state_root: state_root.clone(),
+            traces,
         })
     }
 
+    fn enable_trace(&mut self) {
+        for (_, state) in self.state_adapter_map.iter() {
+            state.borrow_mut().enable_trace();
+        }
+        self.trace = true;
+    }
+
     fn get_balance(&self, address: &Address, id: &AssetID) -> ProtocolResult<Balance> {
         self.account_contract.get_balance(id, address)
     }
+
+    fn get_validators(&self, epoch_id: u64) -> ProtocolResult<Option<ValidatorSet>> {
+        self.governance.get_validators(epoch_id)
+    }
 }
 
 impl<DB: TrieDB> TransactionExecutor<DB> {
+    fn from_root(
+        chain_id: Hash,
+        state_root: MerkleRoot,
+        db: Arc<DB>,
+        epoch_id: u64,
+        cycles_price: u64,
+        coinbase: Address,
+    ) -> ProtocolResult<Self> {
+        let trie = {
+            if state_root == Hash::from_empty() {
+                MPTTrie::new(Arc::clone(&db))
+            } else {
+                MPTTrie::from(state_root.clone(), Arc::clone(&db))?
+            }
+        };
+
+        let mut state_adapter_map = HashMap::new();
+
+        // gen account contract
+        let account_state_adapter =
+            gen_contract_state(&trie, &ACCOUNT_CONTRACT_ADDRESS, Arc::clone(&db))?;
+        let account_contract = NativeAccountContract::new(Rc::clone(&account_state_adapter));
+        state_adapter_map.insert(
+            ACCOUNT_CONTRACT_ADDRESS.clone(),
+            Rc::clone(&account_state_adapter),
+        );
+
+        // gen bank contract
+        let bank_state_adapter =
+            gen_contract_state(&trie, &BANK_CONTRACT_ADDRESS, Arc::clone(&db))?;
+        let bank_account =
+            NativeBankContract::new(chain_id.clone(), Rc::clone(&bank_state_adapter));
+        state_adapter_map.insert(
+            BANK_CONTRACT_ADDRESS.clone(),
+            Rc::clone(&bank_state_adapter),
+        );
+
+        // gen governance contract
+        let governance_state_adapter =
+            gen_contract_state(&trie, &GOVERNANCE_CONTRACT_ADDRESS, Arc::clone(&db))?;
+        let governance = NativeGovernanceContract::new(Rc::clone(&governance_state_adapter));
+        state_adapter_map.insert(
+            GOVERNANCE_CONTRACT_ADDRESS.clone(),
+            Rc::clone(&governance_state_adapter),
+        );
+
+        Ok(TransactionExecutor {
+            chain_id,
+            epoch_id,
+            cycles_price,
+            coinbase,
+            trace: false,
+
+            trie,
+            account_contract,
+            bank_account,
+            governance,
+            state_adapter_map,
+        })
+    }
+
     fn dispatch(
         &mut self,
         ictx: RcInvokeContext,
@@ -188,6 +292,14 @@ impl<DB: TrieDB> TransactionExecutor<DB> {
                 code,
                 contract_type,
             } => self.handle_deploy(Rc::clone(&ictx), code, contract_type)?,
+            TransactionAction::Call {
+                contract,
+                method,
+                args,
+                ..
+            } if Address::Contract(contract.clone()) == *GOVERNANCE_CONTRACT_ADDRESS => {
+                self.handle_governance(Rc::clone(&ictx), contract, method, args)?
+            }
             _ => panic!("Unsupported transaction"),
         };
 
@@ -258,6 +370,107 @@ impl<DB: TrieDB> TransactionExecutor<DB> {
         }
     }
 
+    // Methods of the governance contract:
+    // 1. `propose_validators`, args: [ValidatorSet, the BLS proofs of
+    // possession of the validators in order...], the `version` of the
+    // validator set is ignored. Return the proposal id.
+    // 2. `approve_validators`, args: [proposal id].
+    fn handle_governance(
+        &mut self,
+        ictx: RcInvokeContext,
+        contract: &ContractAddress,
+        method: &str,
+        args: &[Bytes],
+    ) -> ProtocolResult<ReceiptResult> {
+        let arg = args
+            .get(0)
+            .cloned()
+            .ok_or_else(|| TransactionExecutorError::InvalidArgs {
+                method: method.to_owned(),
+            })?;
+
+        let proposal = match method {
+            "propose_validators" => {
+                let validator_set = ValidatorSet::decode_fixed(arg)?;
+                self.governance.propose_validators(
+                    Rc::clone(&ictx),
+                    validator_set.validators,
+                    args[1..].to_vec(),
+                    validator_set.activate_epoch_id,
+                )?
+            }
+            "approve_validators" => {
+                let proposal_id = Hash::from_bytes(arg)?;
+                self.governance
+                    .approve_validators(Rc::clone(&ictx), &proposal_id)?
+            }
+            _ => {
+                return Err(TransactionExecutorError::UnsupportedMethod {
+                    method: method.to_owned(),
+                }
+                .into())
+            }
+        };
+
+        Ok(ReceiptResult::Call {
+            contract:     contract.clone(),
+            return_value: proposal.id.as_bytes(),
+            logs_bloom:   Box::new(Bloom::default()),
+        })
+    }
+
+    // The fees and the issuance of the native asset are split between the
+    // proposer and the signers.
+    fn distribute_rewards(
+        &mut self,
+        all_cycles_used: &[Fee],
+        reward: EpochReward,
+    ) -> ProtocolResult<Vec<Reward>> {
+        let mut totals = all_cycles_used
+            .iter()
+            .filter(|fee| fee.cycle > 0)
+            .map(|fee| (fee.asset_id.clone(), Balance::from(fee.cycle)))
+            .collect::<Vec<_>>();
+
+        if reward.schedule.issuance > 0 {
+            if let Some(asset_id) = self.bank_account.get_native_asset()? {
+                let issuance = Balance::from(reward.schedule.issuance);
+                self.bank_account.issue(&asset_id, issuance.clone())?;
+                totals.push((asset_id, issuance));
+            }
+        }
+
+        let proposer = UserAddress::from_bytes(self.coinbase.as_bytes())?;
+        let mut rewards = vec![];
+        for (asset_id, total) in totals.into_iter() {
+            let shares = split_reward(
+                &total,
+                &proposer,
+                &reward.signers,
+                reward.schedule.proposer_ratio,
+            );
+
+            for (receiver, amount) in shares.into_iter() {
+                if amount == Balance::from(0u64) {
+                    continue;
+                }
+
+                self.account_contract.add_balance(
+                    &asset_id,
+                    &Address::User(receiver.clone()),
+                    amount.clone(),
+                )?;
+                rewards.push(Reward {
+                    receiver,
+                    asset_id: asset_id.clone(),
+                    amount,
+                });
+            }
+        }
+
+        Ok(rewards)
+    }
+
     fn stash(&mut self) -> ProtocolResult<()> {
         for (_, state) in self.state_adapter_map.iter() {
             state.borrow_mut().stash()?;
@@ -272,6 +485,27 @@ impl<DB: TrieDB> TransactionExecutor<DB> {
         Ok(())
     }
 
+    // Collect the state diffs stashed by a transaction, its nonce increment
+    // included. Only the touched contracts are included.
+    fn take_trace(&mut self, tx_hash: Hash) -> TransactionTrace {
+        let mut contracts = self
+            .state_adapter_map
+            .iter()
+            .map(|(address, state)| ContractTrace {
+                address: address.clone(),
+                diffs:   state.borrow_mut().take_diffs(),
+            })
+            .filter(|contract| !contract.diffs.is_empty())
+            .collect::<Vec<_>>();
+        contracts.sort_by_key(|contract| contract.address.as_bytes());
+
+        TransactionTrace {
+            tx_hash,
+            epoch_id: self.epoch_id,
+            contracts,
+        }
+    }
+
     fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
         for (address, state) in self.state_adapter_map.iter() {
             let root = state.borrow_mut().commit()?;
@@ -294,46 +528,15 @@ impl<DB: 'static + TrieDB> ExecutorFactory<DB> for TransactionExecutorFactory {
         cycles_price: u64,
         coinbase: Address,
     ) -> ProtocolResult<Box<dyn Executor>> {
-        let trie = {
-            if state_root == Hash::from_empty() {
-                MPTTrie::new(Arc::clone(&db))
-            } else {
-                MPTTrie::from(state_root.clone(), Arc::clone(&db))?
-            }
-        };
-
-        let mut state_adapter_map = HashMap::new();
-
-        // gen account contract
-        let account_state_adapter =
-            gen_contract_state(&trie, &ACCOUNT_CONTRACT_ADDRESS, Arc::clone(&db))?;
-        let account_contract = NativeAccountContract::new(Rc::clone(&account_state_adapter));
-        state_adapter_map.insert(
-            ACCOUNT_CONTRACT_ADDRESS.clone(),
-            Rc::clone(&account_state_adapter),
-        );
-
-        // gen bank contract
-        let bank_state_adapter =
-            gen_contract_state(&trie, &BANK_CONTRACT_ADDRESS, Arc::clone(&db))?;
-        let bank_account =
-            NativeBankContract::new(chain_id.clone(), Rc::clone(&bank_state_adapter));
-        state_adapter_map.insert(
-            BANK_CONTRACT_ADDRESS.clone(),
-            Rc::clone(&bank_state_adapter),
-        );
-
-        Ok(Box::new(TransactionExecutor {
+        let executor = TransactionExecutor::from_root(
             chain_id,
+            state_root,
+            db,
             epoch_id,
             cycles_price,
             coinbase,
-
-            trie,
-            account_contract,
-            bank_account,
-            state_adapter_map,
-        }))
+        )?;
+        Ok(Box::new(executor))
     }
 }
 
@@ -371,6 +574,43 @@ fn modify_all_cycles_used(all_cycles_used: &mut Vec<Fee>, cycles_used: &Fee) {
     all_cycles_used.push(new_fee);
 }
 
+// Split the reward between the proposer and the signers. The signers share the
+// part which does not belong to the proposer in proportion to their vote
+// weights, and the remainder of the division goes to the proposer.
+fn split_reward(
+    total: &Balance,
+    proposer: &UserAddress,
+    signers: &[Validator],
+    proposer_ratio: u64,
+) -> Vec<(UserAddress, Balance)> {
+    let total_weight: u64 = signers.iter().map(|s| u64::from(s.vote_weight)).sum();
+    if total_weight == 0 {
+        return vec![(proposer.clone(), total.clone())];
+    }
+
+    let proposer_ratio = cmp::min(proposer_ratio, 100);
+    let signers_part = total * Balance::from(100 - proposer_ratio) / Balance::from(100u64);
+
+    let mut shares = Vec::with_capacity(signers.len() + 1);
+    let mut distributed = Balance::from(0u64);
+    for signer in signers.iter() {
+        let share = &signers_part * Balance::from(signer.vote_weight) / Balance::from(total_weight);
+        distributed += &share;
+        shares.push((signer.address.clone(), share));
+    }
+
+    shares.insert(0, (proposer.clone(), total - &distributed));
+    shares
+}
+
+// The system receipt of the epoch rewards has no transaction, so it is indexed
+// by a hash derived from the epoch id.
+fn reward_receipt_hash(epoch_id: u64) -> Hash {
+    Hash::digest(Bytes::from(
+        [b"epoch_reward".as_ref(), &epoch_id.to_be_bytes()].concat(),
+    ))
+}
+
 fn gen_invoke_ctx(
     epoch_id: u64,
     cycles_price: u64,
@@ -405,6 +645,16 @@ fn gen_invoke_ctx(
 pub enum TransactionExecutorError {
     FromHex(hex::FromHexError),
     ParseInt(ParseIntError),
+
+    #[display(fmt = "invalid args of method {}", method)]
+    InvalidArgs {
+        method: String,
+    },
+
+    #[display(fmt = "unsupported method {}", method)]
+    UnsupportedMethod {
+        method: String,
+    },
 }
 
 impl Error for TransactionExecutorError {}
@@ -4,6 +4,7 @@ use std::rc::Rc;
 
 use bytes::Bytes;
 use derive_more::{Display, From};
+use lazy_static::lazy_static;
 
 use protocol::traits::executor::contract::{BankContract, ContractStateAdapter};
 use protocol::traits::executor::RcInvokeContext;
@@ -11,7 +12,11 @@ use protocol::types::{Asset, AssetID, Balance, ContractAddress, ContractType, Ha
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::cycles::{consume_cycles, CyclesAction};
-use crate::fixed_types::FixedAssetSchema;
+use crate::fixed_types::{FixedAssetIDSchema, FixedAssetSchema};
+
+lazy_static! {
+    static ref NATIVE_ASSET_KEY: Hash = Hash::digest(Bytes::from("native_asset"));
+}
 
 /// Bank is the registration and query center for asset.
 ///
@@ -54,9 +59,7 @@ impl<StateAdapter: ContractStateAdapter> BankContract<StateAdapter>
             return Err(NativeBankContractError::InvalidAddress.into());
         }
 
-        let asset_id = Hash::digest(Bytes::from(
-            [self.chain_id.as_bytes(), address.as_bytes()].concat(),
-        ));
+        let asset_id = Asset::gen_id(&self.chain_id, address);
 
         // Although the probability of a collision is small, we should still check it.
         if self
@@ -98,6 +101,40 @@ impl<StateAdapter: ContractStateAdapter> BankContract<StateAdapter>
             .ok_or(NativeBankContractError::NotFound { id: id.clone() })?;
         Ok(fixed_asset)
     }
+
+    fn set_native_asset(&mut self, id: &AssetID) -> ProtocolResult<()> {
+        if !self
+            .state_adapter
+            .borrow()
+            .contains::<FixedAssetSchema>(id)?
+        {
+            return Err(NativeBankContractError::NotFound { id: id.clone() }.into());
+        }
+
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedAssetIDSchema>(NATIVE_ASSET_KEY.clone(), id.clone())
+    }
+
+    fn get_native_asset(&self) -> ProtocolResult<Option<AssetID>> {
+        self.state_adapter
+            .borrow()
+            .get::<FixedAssetIDSchema>(&NATIVE_ASSET_KEY)
+    }
+
+    fn issue(&mut self, id: &AssetID, amount: Balance) -> ProtocolResult<Asset> {
+        let mut asset: Asset = self
+            .state_adapter
+            .borrow()
+            .get::<FixedAssetSchema>(&id)?
+            .ok_or(NativeBankContractError::NotFound { id: id.clone() })?;
+        asset.supply += amount;
+
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedAssetSchema>(id.clone(), asset.clone())?;
+        Ok(asset)
+    }
 }
 
 #[derive(Debug, Display, From)]
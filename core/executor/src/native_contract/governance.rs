@@ -0,0 +1,387 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::error::Error;
+use std::rc::Rc;
+
+use bytes::Bytes;
+use derive_more::{Display, From};
+use lazy_static::lazy_static;
+
+use protocol::fixed_codec::ProtocolFixedCodec;
+use protocol::traits::executor::contract::{ContractStateAdapter, GovernanceContract};
+use protocol::traits::executor::RcInvokeContext;
+use protocol::types::{Address, Hash, UserAddress, Validator, ValidatorProposal, ValidatorSet};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::cycles::{consume_cycles, CyclesAction};
+use crate::fixed_types::{FixedValidatorProposalSchema, FixedValidatorSetSchema};
+
+lazy_static! {
+    static ref CURRENT_VALIDATOR_SET_KEY: Hash = Hash::digest(Bytes::from("current_validator_set"));
+    static ref SCHEDULED_VALIDATOR_SET_KEY: Hash =
+        Hash::digest(Bytes::from("scheduled_validator_set"));
+}
+
+/// Governance manages the validator set of the chain.
+///
+/// The world state keeps two validator sets, the current one and the scheduled
+/// one. A proposal is scheduled once the approvals reach more than 2/3 of the
+/// vote weight, and the scheduled set replaces the current one at its
+/// `activate_epoch_id`. Only one change can be scheduled at a time.
+pub struct NativeGovernanceContract<StateAdapter: ContractStateAdapter> {
+    state_adapter: Rc<RefCell<StateAdapter>>,
+}
+
+impl<StateAdapter: ContractStateAdapter> NativeGovernanceContract<StateAdapter> {
+    pub fn new(state_adapter: Rc<RefCell<StateAdapter>>) -> Self {
+        Self { state_adapter }
+    }
+}
+
+impl<StateAdapter: ContractStateAdapter> GovernanceContract<StateAdapter>
+    for NativeGovernanceContract<StateAdapter>
+{
+    fn init_validators(&mut self, validators: Vec<Validator>) -> ProtocolResult<ValidatorSet> {
+        check_validators(&validators)?;
+
+        let validator_set = ValidatorSet {
+            version: 0,
+            activate_epoch_id: 0,
+            validators,
+        };
+
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedValidatorSetSchema>(
+                CURRENT_VALIDATOR_SET_KEY.clone(),
+                validator_set.clone(),
+            )?;
+        Ok(validator_set)
+    }
+
+    fn propose_validators(
+        &mut self,
+        ictx: RcInvokeContext,
+        validators: Vec<Validator>,
+        bls_pops: Vec<Bytes>,
+        activate_epoch_id: u64,
+    ) -> ProtocolResult<ValidatorProposal> {
+        let cloned_ictx = { ictx.borrow().clone() };
+        let epoch_id = cloned_ictx.epoch_id;
+
+        let current = self.get_current_validators(epoch_id)?;
+        let proposer = check_validator(&current, &cloned_ictx.caller)?;
+        self.check_no_pending_change(epoch_id)?;
+
+        if activate_epoch_id <= epoch_id {
+            return Err(NativeGovernanceContractError::InvalidActivateEpoch {
+                activate_epoch_id,
+                epoch_id,
+            }
+            .into());
+        }
+        check_validators(&validators)?;
+        check_bls_pops(&validators, &bls_pops)?;
+
+        let validator_set = ValidatorSet {
+            version: current.version + 1,
+            activate_epoch_id,
+            validators,
+        };
+        let id = Hash::digest(validator_set.encode_fixed()?);
+
+        if self
+            .state_adapter
+            .borrow()
+            .contains::<FixedValidatorProposalSchema>(&id)?
+        {
+            return Err(NativeGovernanceContractError::ProposalExists { id }.into());
+        }
+
+        let proposal = ValidatorProposal {
+            id,
+            proposer: proposer.clone(),
+            base_version: current.version,
+            validator_set,
+            approvals: vec![proposer],
+        };
+        self.save_proposal(&current, &proposal, epoch_id)?;
+
+        let cycles_used = consume_cycles(
+            CyclesAction::GovernancePropose,
+            cloned_ictx.cycles_used,
+            cloned_ictx.cycles_limit,
+        )?;
+        ictx.borrow_mut().cycles_used = cycles_used;
+        Ok(proposal)
+    }
+
+    fn approve_validators(
+        &mut self,
+        ictx: RcInvokeContext,
+        proposal_id: &Hash,
+    ) -> ProtocolResult<ValidatorProposal> {
+        let cloned_ictx = { ictx.borrow().clone() };
+        let epoch_id = cloned_ictx.epoch_id;
+
+        let mut proposal = self.get_proposal(proposal_id)?;
+        let current = self.get_current_validators(epoch_id)?;
+        let approver = check_validator(&current, &cloned_ictx.caller)?;
+        self.check_no_pending_change(epoch_id)?;
+
+        if proposal.base_version != current.version {
+            return Err(NativeGovernanceContractError::StaleProposal {
+                id: proposal_id.clone(),
+            }
+            .into());
+        }
+        if proposal.validator_set.activate_epoch_id <= epoch_id {
+            return Err(NativeGovernanceContractError::InvalidActivateEpoch {
+                activate_epoch_id: proposal.validator_set.activate_epoch_id,
+                epoch_id,
+            }
+            .into());
+        }
+        if proposal.approvals.contains(&approver) {
+            return Err(
+                NativeGovernanceContractError::DuplicateApproval { address: approver }.into(),
+            );
+        }
+
+        proposal.approvals.push(approver);
+        self.save_proposal(&current, &proposal, epoch_id)?;
+
+        let cycles_used = consume_cycles(
+            CyclesAction::GovernanceApprove,
+            cloned_ictx.cycles_used,
+            cloned_ictx.cycles_limit,
+        )?;
+        ictx.borrow_mut().cycles_used = cycles_used;
+        Ok(proposal)
+    }
+
+    fn get_validators(&self, epoch_id: u64) -> ProtocolResult<Option<ValidatorSet>> {
+        if let Some(scheduled) = self.get_scheduled()? {
+            if scheduled.activate_epoch_id <= epoch_id {
+                return Ok(Some(scheduled));
+            }
+        }
+
+        self.state_adapter
+            .borrow()
+            .get::<FixedValidatorSetSchema>(&CURRENT_VALIDATOR_SET_KEY)
+    }
+
+    fn get_proposal(&self, proposal_id: &Hash) -> ProtocolResult<ValidatorProposal> {
+        let proposal = self
+            .state_adapter
+            .borrow()
+            .get::<FixedValidatorProposalSchema>(proposal_id)?
+            .ok_or(NativeGovernanceContractError::ProposalNotFound {
+                id: proposal_id.clone(),
+            })?;
+        Ok(proposal)
+    }
+}
+
+impl<StateAdapter: ContractStateAdapter> NativeGovernanceContract<StateAdapter> {
+    fn get_current_validators(&self, epoch_id: u64) -> ProtocolResult<ValidatorSet> {
+        let validator_set = self
+            .get_validators(epoch_id)?
+            .ok_or(NativeGovernanceContractError::Uninitialized)?;
+        Ok(validator_set)
+    }
+
+    fn get_scheduled(&self) -> ProtocolResult<Option<ValidatorSet>> {
+        self.state_adapter
+            .borrow()
+            .get::<FixedValidatorSetSchema>(&SCHEDULED_VALIDATOR_SET_KEY)
+    }
+
+    fn check_no_pending_change(&self, epoch_id: u64) -> ProtocolResult<()> {
+        match self.get_scheduled()? {
+            Some(ref scheduled) if scheduled.activate_epoch_id > epoch_id => {
+                Err(NativeGovernanceContractError::ChangePending {
+                    activate_epoch_id: scheduled.activate_epoch_id,
+                }
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Save the proposal, and schedule its validator set if the approvals reach
+    // the quorum of current validators.
+    fn save_proposal(
+        &mut self,
+        current: &ValidatorSet,
+        proposal: &ValidatorProposal,
+        epoch_id: u64,
+    ) -> ProtocolResult<()> {
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedValidatorProposalSchema>(proposal.id.clone(), proposal.clone())?;
+
+        if !reach_quorum(current, &proposal.approvals) {
+            return Ok(());
+        }
+
+        // The activated scheduled set must be promoted before it is replaced.
+        if let Some(scheduled) = self.get_scheduled()? {
+            if scheduled.activate_epoch_id <= epoch_id {
+                self.state_adapter
+                    .borrow_mut()
+                    .insert_cache::<FixedValidatorSetSchema>(
+                        CURRENT_VALIDATOR_SET_KEY.clone(),
+                        scheduled,
+                    )?;
+            }
+        }
+
+        self.state_adapter
+            .borrow_mut()
+            .insert_cache::<FixedValidatorSetSchema>(
+                SCHEDULED_VALIDATOR_SET_KEY.clone(),
+                proposal.validator_set.clone(),
+            )?;
+        Ok(())
+    }
+}
+
+fn check_validator(validator_set: &ValidatorSet, caller: &Address) -> ProtocolResult<UserAddress> {
+    if let Address::User(user) = caller {
+        if validator_set.validators.iter().any(|v| &v.address == user) {
+            return Ok(user.clone());
+        }
+    }
+
+    Err(NativeGovernanceContractError::NotValidator {
+        address: caller.clone(),
+    }
+    .into())
+}
+
+fn check_validators(validators: &[Validator]) -> ProtocolResult<()> {
+    if validators.is_empty() {
+        return Err(NativeGovernanceContractError::InvalidValidators(
+            "empty validator list".to_owned(),
+        )
+        .into());
+    }
+
+    let mut set = HashSet::with_capacity(validators.len());
+    for validator in validators.iter() {
+        if !set.insert(validator.address.clone()) {
+            return Err(NativeGovernanceContractError::InvalidValidators(format!(
+                "duplicate validator {:?}",
+                validator.address
+            ))
+            .into());
+        }
+        if !validator.is_valid_bls_pub_key() {
+            return Err(NativeGovernanceContractError::InvalidValidators(format!(
+                "validator {:?} has invalid BLS public key",
+                validator.address
+            ))
+            .into());
+        }
+    }
+
+    if validators.iter().all(|v| v.vote_weight == 0) {
+        return Err(NativeGovernanceContractError::InvalidValidators(
+            "total vote weight is zero".to_owned(),
+        )
+        .into());
+    }
+    if validators.iter().all(|v| v.propose_weight == 0) {
+        return Err(NativeGovernanceContractError::InvalidValidators(
+            "total propose weight is zero".to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// A quorum is more than 2/3 of the total vote weight.
+fn reach_quorum(validator_set: &ValidatorSet, approvals: &[UserAddress]) -> bool {
+    let total: u64 = validator_set
+        .validators
+        .iter()
+        .map(|v| u64::from(v.vote_weight))
+        .sum();
+    let approved: u64 = validator_set
+        .validators
+        .iter()
+        .filter(|v| approvals.contains(&v.address))
+        .map(|v| u64::from(v.vote_weight))
+        .sum();
+
+    approved * 3 > total * 2
+}
+
+fn check_bls_pops(validators: &[Validator], bls_pops: &[Bytes]) -> ProtocolResult<()> {
+    if validators.len() != bls_pops.len() {
+        return Err(NativeGovernanceContractError::InvalidValidators(format!(
+            "{} validators with {} BLS proofs of possession",
+            validators.len(),
+            bls_pops.len()
+        ))
+        .into());
+    }
+
+    for (validator, bls_pop) in validators.iter().zip(bls_pops.iter()) {
+        if !validator.verify_bls_pop(bls_pop) {
+            return Err(NativeGovernanceContractError::InvalidValidators(format!(
+                "validator {:?} has invalid BLS proof of possession",
+                validator.address
+            ))
+            .into());
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Display, From)]
+pub enum NativeGovernanceContractError {
+    #[display(fmt = "validator set uninitialized")]
+    Uninitialized,
+
+    #[display(fmt = "{:?} is not a validator", address)]
+    NotValidator { address: Address },
+
+    #[display(fmt = "invalid validators: {}", _0)]
+    InvalidValidators(String),
+
+    #[display(
+        fmt = "activate epoch {} must be greater than current epoch {}",
+        activate_epoch_id,
+        epoch_id
+    )]
+    InvalidActivateEpoch {
+        activate_epoch_id: u64,
+        epoch_id:          u64,
+    },
+
+    #[display(fmt = "a validator change at epoch {} is pending", activate_epoch_id)]
+    ChangePending { activate_epoch_id: u64 },
+
+    #[display(fmt = "proposal {:?} already exists", id)]
+    ProposalExists { id: Hash },
+
+    #[display(fmt = "proposal {:?} not found", id)]
+    ProposalNotFound { id: Hash },
+
+    #[display(fmt = "proposal {:?} is based on an outdated validator set", id)]
+    StaleProposal { id: Hash },
+
+    #[display(fmt = "{:?} has already approved", address)]
+    DuplicateApproval { address: UserAddress },
+}
+
+impl Error for NativeGovernanceContractError {}
+
+impl From<NativeGovernanceContractError> for ProtocolError {
+    fn from(err: NativeGovernanceContractError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Executor, Box::new(err))
+    }
+}
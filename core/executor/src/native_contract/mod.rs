@@ -1,5 +1,6 @@
 mod account;
 mod bank;
+mod governance;
 
 use lazy_static::lazy_static;
 
@@ -14,7 +15,12 @@ lazy_static! {
         "0x230000000000000000000000000000000000000002"
     )
     .expect("0x230000000000000000000000000000000000000001 is not a legal native contract address.");
+    pub static ref GOVERNANCE_CONTRACT_ADDRESS: Address = Address::from_hex(
+        "0x230000000000000000000000000000000000000003"
+    )
+    .expect("0x230000000000000000000000000000000000000003 is not a legal native contract address.");
 }
 
 pub use account::{NativeAccountContract, NativeAccountContractError};
 pub use bank::{NativeBankContract, NativeBankContractError};
+pub use governance::{NativeGovernanceContract, NativeGovernanceContractError};
@@ -53,4 +53,14 @@ fn test_bank_contract() {
         .get_asset(Rc::<RefCell<InvokeContext>>::clone(&ctx), &asset.id)
         .unwrap();
     assert_eq!(&asset, &asset_get);
+
+    // native asset
+    assert_eq!(bank.get_native_asset().unwrap(), None);
+    assert!(bank.set_native_asset(&fee_asset).is_err());
+    bank.set_native_asset(&asset.id).unwrap();
+    assert_eq!(bank.get_native_asset().unwrap(), Some(asset.id.clone()));
+
+    // issue
+    let issued = bank.issue(&asset.id, Balance::from(100u64)).unwrap();
+    assert_eq!(issued.supply, supply + Balance::from(100u64));
 }
@@ -63,3 +63,46 @@ fn revert() {
     let get_value = state_adapter.get::<FixedBytesSchema>(&key).unwrap();
     assert_eq!(get_value, None);
 }
+
+#[test]
+fn trace() {
+    let memdb = tests::create_empty_memdb();
+    let trie = tests::create_empty_trie(Arc::clone(&memdb));
+    let mut state_adapter = GeneralContractStateAdapter::new(trie);
+    state_adapter.enable_trace();
+
+    let key = Bytes::from(b"test-key".to_vec());
+    let value = Bytes::from(b"test-value".to_vec());
+    let new_value = Bytes::from(b"test-new-value".to_vec());
+    state_adapter
+        .insert_cache::<FixedBytesSchema>(key.clone(), value.clone())
+        .unwrap();
+    state_adapter.stash().unwrap();
+
+    let diffs = state_adapter.take_diffs();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].key, key);
+    assert_eq!(diffs[0].before, None);
+    assert_eq!(diffs[0].after, value);
+
+    // The reverted writes are not traced.
+    state_adapter
+        .insert_cache::<FixedBytesSchema>(key.clone(), Bytes::from(b"reverted".to_vec()))
+        .unwrap();
+    state_adapter.revert_cache().unwrap();
+
+    // Only the first value before a transaction is recorded.
+    state_adapter
+        .insert_cache::<FixedBytesSchema>(key.clone(), Bytes::from(b"overwritten".to_vec()))
+        .unwrap();
+    state_adapter
+        .insert_cache::<FixedBytesSchema>(key.clone(), new_value.clone())
+        .unwrap();
+    state_adapter.stash().unwrap();
+
+    let diffs = state_adapter.take_diffs();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].before, Some(value));
+    assert_eq!(diffs[0].after, new_value);
+    assert!(state_adapter.take_diffs().is_empty());
+}
@@ -0,0 +1,124 @@
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use bytes::Bytes;
+
+use common_crypto::{BLS12381PrivateKey, Crypto, PrivateKey, PublicKey, Signature, BLS12381};
+use protocol::traits::executor::contract::GovernanceContract;
+use protocol::types::{Address, AssetID, UserAddress, Validator};
+
+use crate::native_contract::NativeGovernanceContract;
+use crate::tests::{create_state_adapter, mock_invoke_context};
+
+// Return the validator with the proof of possession of its BLS key.
+fn mock_validator(address: &str) -> (Validator, Bytes) {
+    let mut key = [0u8; 48];
+    key[47] = address.as_bytes()[address.len() - 1];
+    let key = BLS12381PrivateKey::try_from(key.as_ref()).unwrap();
+
+    let validator = Validator {
+        address:        UserAddress::from_hex(address).unwrap(),
+        bls_pub_key:    key.pub_key().to_bytes(),
+        propose_weight: 1,
+        vote_weight:    1,
+    };
+    let message = Validator::bls_pop_message(&validator.bls_pub_key).as_bytes();
+    let bls_pop = BLS12381::sign_message(&message, &key.to_bytes())
+        .unwrap()
+        .to_bytes();
+    (validator, bls_pop)
+}
+
+#[test]
+fn test_governance_contract() {
+    let (validators, bls_pops): (Vec<_>, Vec<_>) = vec![
+        mock_validator("100000000000000000000000000000000000000001"),
+        mock_validator("100000000000000000000000000000000000000002"),
+        mock_validator("100000000000000000000000000000000000000003"),
+        mock_validator("100000000000000000000000000000000000000004"),
+    ]
+    .into_iter()
+    .unzip();
+    let state = Rc::new(RefCell::new(create_state_adapter()));
+    let mut governance = NativeGovernanceContract::new(state);
+    let fee_asset =
+        AssetID::from_hex("0000000000000000000000000000000000000000000000000000000000000000")
+            .unwrap();
+
+    let caller = |index: usize| {
+        let address = Address::User(validators[index].address.clone());
+        mock_invoke_context(address, None, 0, 1_000_000, fee_asset.clone())
+    };
+
+    assert_eq!(governance.get_validators(1).unwrap(), None);
+    let init_set = governance.init_validators(validators.clone()).unwrap();
+    assert_eq!(init_set.version, 0);
+
+    // non-validator is not allowed to propose
+    let outsider = Address::from_hex("100000000000000000000000000000000000000005").unwrap();
+    let ctx = mock_invoke_context(outsider, None, 0, 1_000_000, fee_asset.clone());
+    let new_validators = validators[1..].to_vec();
+    let new_pops = bls_pops[1..].to_vec();
+    assert!(governance
+        .propose_validators(ctx, new_validators.clone(), new_pops.clone(), 10)
+        .is_err());
+
+    // activate epoch must be in the future
+    assert!(governance
+        .propose_validators(caller(0), new_validators.clone(), new_pops.clone(), 1)
+        .is_err());
+
+    // every BLS key must come with its proof of possession
+    assert!(governance
+        .propose_validators(
+            caller(0),
+            new_validators.clone(),
+            bls_pops[..2].to_vec(),
+            10
+        )
+        .is_err());
+    let mut swapped_pops = new_pops.clone();
+    swapped_pops.swap(0, 1);
+    assert!(governance
+        .propose_validators(caller(0), new_validators.clone(), swapped_pops, 10)
+        .is_err());
+    let mut invalid_key = new_validators.clone();
+    invalid_key[0].bls_pub_key = Bytes::from("invalid key");
+    assert!(governance
+        .propose_validators(caller(0), invalid_key, new_pops.clone(), 10)
+        .is_err());
+
+    let proposal = governance
+        .propose_validators(caller(0), new_validators.clone(), new_pops, 10)
+        .unwrap();
+    assert_eq!(proposal.base_version, 0);
+    assert_eq!(proposal.approvals.len(), 1);
+
+    // duplicate approval
+    assert!(governance
+        .approve_validators(caller(0), &proposal.id)
+        .is_err());
+
+    // 2/4 of the vote weight does not reach the quorum
+    governance
+        .approve_validators(caller(1), &proposal.id)
+        .unwrap();
+    assert_eq!(governance.get_validators(10).unwrap().unwrap(), init_set);
+
+    // 3/4 of the vote weight reaches the quorum
+    governance
+        .approve_validators(caller(2), &proposal.id)
+        .unwrap();
+    assert_eq!(governance.get_validators(9).unwrap().unwrap(), init_set);
+
+    let scheduled = governance.get_validators(10).unwrap().unwrap();
+    assert_eq!(scheduled.version, 1);
+    assert_eq!(scheduled.activate_epoch_id, 10);
+    assert_eq!(scheduled.validators, new_validators);
+
+    // the scheduled change blocks new proposals until it takes effect
+    assert!(governance
+        .propose_validators(caller(1), validators.clone(), bls_pops, 20)
+        .is_err());
+}
@@ -1,6 +1,9 @@
 mod account_contract;
 mod bank_contract;
 mod general_state_adapter;
+mod governance_contract;
+mod reward;
+mod trace;
 mod trie;
 
 use std::cell::RefCell;
@@ -0,0 +1,52 @@
+use bytes::Bytes;
+
+use protocol::types::{Balance, UserAddress, Validator};
+
+use crate::split_reward;
+
+fn mock_validator(address: &str, vote_weight: u8) -> Validator {
+    Validator {
+        address: UserAddress::from_hex(address).unwrap(),
+        bls_pub_key: Bytes::new(),
+        propose_weight: 1,
+        vote_weight,
+    }
+}
+
+#[test]
+fn test_split_reward() {
+    let proposer = UserAddress::from_hex("100000000000000000000000000000000000000001").unwrap();
+    let signers = vec![
+        mock_validator("100000000000000000000000000000000000000002", 1),
+        mock_validator("100000000000000000000000000000000000000003", 2),
+    ];
+    let total = Balance::from(1000u64);
+
+    // no signers, the proposer takes all
+    let shares = split_reward(&total, &proposer, &[], 50);
+    assert_eq!(shares, vec![(proposer.clone(), total.clone())]);
+
+    let shares = split_reward(&total, &proposer, &signers, 40);
+    assert_eq!(shares.len(), 3);
+    assert_eq!(
+        shares[1],
+        (signers[0].address.clone(), Balance::from(200u64))
+    );
+    assert_eq!(
+        shares[2],
+        (signers[1].address.clone(), Balance::from(400u64))
+    );
+    assert_eq!(shares[0], (proposer.clone(), Balance::from(400u64)));
+
+    // the remainder of the division goes to the proposer
+    let total = Balance::from(100u64);
+    let shares = split_reward(&total, &proposer, &signers, 0);
+    assert_eq!(shares[1].1, Balance::from(33u64));
+    assert_eq!(shares[2].1, Balance::from(66u64));
+    assert_eq!(shares[0].1, Balance::from(1u64));
+
+    let sum = shares
+        .into_iter()
+        .fold(Balance::from(0u64), |acc, (_, amount)| acc + amount);
+    assert_eq!(sum, total);
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use protocol::fixed_codec::ProtocolFixedCodec;
+use protocol::traits::executor::contract::AccountContract;
+use protocol::traits::executor::{EpochReward, Executor, RewardSchedule};
+use protocol::types::{
+    Address, AssetID, CarryingAsset, Fee, Hash, MerkleRoot, RawTransaction, SignedTransaction,
+    TransactionAction, UserAddress,
+};
+
+use crate::native_contract::ACCOUNT_CONTRACT_ADDRESS;
+use crate::tests::create_empty_memdb;
+use crate::TransactionExecutor;
+
+fn mock_transfer_tx(pubkey: Bytes, asset_id: &AssetID) -> SignedTransaction {
+    let raw = RawTransaction {
+        chain_id: Hash::from_empty(),
+        nonce:    Hash::digest(pubkey.clone()),
+        timeout:  100,
+        fee:      Fee {
+            asset_id: asset_id.clone(),
+            cycle:    1_000,
+        },
+        action:   TransactionAction::Transfer {
+            receiver:       UserAddress::from_hex("100000000000000000000000000000000000000003")
+                .unwrap(),
+            carrying_asset: CarryingAsset {
+                asset_id: asset_id.clone(),
+                amount:   1u64.into(),
+            },
+        },
+    };
+
+    SignedTransaction {
+        raw,
+        tx_hash: Hash::digest(pubkey.clone()),
+        pubkey,
+        signature: Bytes::new(),
+    }
+}
+
+#[test]
+fn test_trace_nonce() {
+    let coinbase = Address::from_hex("100000000000000000000000000000000000000001").unwrap();
+    let mut executor = TransactionExecutor::from_root(
+        Hash::from_empty(),
+        MerkleRoot::from_empty(),
+        create_empty_memdb(),
+        1,
+        1,
+        coinbase,
+    )
+    .unwrap();
+
+    // The asset is not registered, so the transfers fail, but the nonces of
+    // the senders are still increased.
+    let asset_id = Hash::digest(Bytes::from("asset"));
+    let pubkeys = vec![Bytes::from("sender1"), Bytes::from("sender2")];
+    let senders = pubkeys
+        .iter()
+        .map(|pubkey| Address::User(UserAddress::from_pubkey_bytes(pubkey.clone()).unwrap()))
+        .collect::<Vec<_>>();
+    for sender in senders.iter() {
+        executor
+            .account_contract
+            .add_balance(&asset_id, sender, 10u64.into())
+            .unwrap();
+    }
+    executor.stash().unwrap();
+    executor.commit().unwrap();
+    executor.enable_trace();
+
+    let txs = pubkeys
+        .into_iter()
+        .map(|pubkey| mock_transfer_tx(pubkey, &asset_id))
+        .collect::<Vec<_>>();
+    let reward = EpochReward {
+        schedule: RewardSchedule::default(),
+        signers:  vec![],
+    };
+    let resp = executor.exec(txs.clone(), reward).unwrap();
+
+    // Each trace contains only the nonce change of its own sender.
+    assert_eq!(resp.traces.len(), txs.len());
+    for ((trace, tx), sender) in resp.traces.iter().zip(txs.iter()).zip(senders.iter()) {
+        assert_eq!(trace.tx_hash, tx.tx_hash);
+        assert_eq!(trace.contracts.len(), 1);
+        assert_eq!(trace.contracts[0].address, *ACCOUNT_CONTRACT_ADDRESS);
+
+        let diffs = &trace.contracts[0].diffs;
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].key, sender.encode_fixed().unwrap());
+        assert!(diffs[0].before.is_some());
+    }
+}
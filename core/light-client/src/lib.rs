@@ -0,0 +1,481 @@
+//! Follow the chain without a full node. Starting from a trusted checkpoint,
+//! every following epoch is verified by the precommit proof of its validators,
+//! then the transactions can be verified against the order roots of the
+//! verified epochs.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+use derive_more::{Display, From};
+
+use common_merkle::{Merkle, ProofNode};
+use common_proof::{check_proof, extract_voters};
+use protocol::fixed_codec::ProtocolFixedCodec;
+use protocol::types::{Epoch, Hash, MerkleRoot, Proof, Validator};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+#[derive(Debug)]
+pub struct LightClient {
+    latest_epoch_id:   u64,
+    latest_hash:       Hash,
+    validator_version: u64,
+    validators:        Vec<Validator>,
+    // The order roots and the numbers of ordered transactions of the verified
+    // epochs.
+    order_roots: BTreeMap<u64, (MerkleRoot, usize)>,
+}
+
+impl LightClient {
+    /// The checkpoint must come from a trusted source, such as the genesis
+    /// epoch of the chain.
+    pub fn new(checkpoint: &Epoch) -> ProtocolResult<Self> {
+        let header = &checkpoint.header;
+        let mut order_roots = BTreeMap::new();
+        order_roots.insert(
+            header.epoch_id,
+            (
+                header.order_root.clone(),
+                checkpoint.ordered_tx_hashes.len(),
+            ),
+        );
+
+        Ok(LightClient {
+            latest_epoch_id: header.epoch_id,
+            latest_hash: Hash::digest(checkpoint.encode_fixed()?),
+            validator_version: header.validator_version,
+            validators: header.validators.clone(),
+            order_roots,
+        })
+    }
+
+    pub fn latest_epoch_id(&self) -> u64 {
+        self.latest_epoch_id
+    }
+
+    pub fn latest_hash(&self) -> &Hash {
+        &self.latest_hash
+    }
+
+    pub fn validator_version(&self) -> u64 {
+        self.validator_version
+    }
+
+    pub fn validators(&self) -> &[Validator] {
+        &self.validators
+    }
+
+    /// Verify the epoch following the latest verified epoch. The proof of an
+    /// epoch is carried by the header of the next epoch.
+    ///
+    /// The validators of the epoch must be the trusted validators unless the
+    /// validator version increases. A new validator set is trusted only if the
+    /// trusted validators of more than 1/3 vote weight also signed the proof,
+    /// so that at least one honest trusted validator vouches for it.
+    pub fn verify_epoch(&mut self, epoch: &Epoch, proof: &Proof) -> ProtocolResult<()> {
+        let header = &epoch.header;
+        let epoch_id = header.epoch_id;
+
+        if epoch_id != self.latest_epoch_id + 1 {
+            return Err(LightClientError::InvalidEpochId {
+                expect: self.latest_epoch_id + 1,
+                actual: epoch_id,
+            }
+            .into());
+        }
+        if header.pre_hash != self.latest_hash {
+            return Err(LightClientError::InvalidPrevHash { epoch_id }.into());
+        }
+
+        let order_root = Merkle::from_hashes(epoch.ordered_tx_hashes.clone())
+            .get_root_hash()
+            .unwrap_or_else(Hash::from_empty);
+        if header.order_root != order_root {
+            return Err(LightClientError::InvalidOrderRoot { epoch_id }.into());
+        }
+
+        let version_changed = header.validator_version != self.validator_version;
+        if header.validator_version < self.validator_version
+            || (!version_changed && header.validators != self.validators)
+        {
+            return Err(LightClientError::InvalidValidators {
+                epoch_id,
+                validator_version: header.validator_version,
+            }
+            .into());
+        }
+
+        check_proof(epoch, proof, &header.validators)?;
+        if version_changed && !self.vouched(&header.validators, proof) {
+            return Err(LightClientError::UntrustedValidators { epoch_id }.into());
+        }
+
+        self.latest_epoch_id = epoch_id;
+        self.latest_hash = proof.epoch_hash.clone();
+        self.validator_version = header.validator_version;
+        self.validators = header.validators.clone();
+        self.order_roots
+            .insert(epoch_id, (order_root, epoch.ordered_tx_hashes.len()));
+        Ok(())
+    }
+
+    /// Verify the epochs following the latest verified epoch in order. The
+    /// proof of each epoch is carried by the header of the next one, and the
+    /// proof of the last epoch is given, such as the latest proof of a node.
+    pub fn verify_epochs(&mut self, epochs: &[Epoch], last_proof: &Proof) -> ProtocolResult<()> {
+        for (index, epoch) in epochs.iter().enumerate() {
+            let proof = epochs
+                .get(index + 1)
+                .map_or(last_proof, |next| &next.header.proof);
+            self.verify_epoch(epoch, proof)?;
+        }
+        Ok(())
+    }
+
+    /// Verify that the transaction is ordered in a verified epoch by the
+    /// merkle proof of the order root.
+    ///
+    /// An empty proof only proves the single transaction of an epoch, otherwise
+    /// the order root itself would pass as a transaction hash.
+    pub fn verify_transaction(
+        &self,
+        epoch_id: u64,
+        tx_hash: Hash,
+        proof: &[ProofNode],
+    ) -> ProtocolResult<()> {
+        let (order_root, tx_count) = self
+            .order_roots
+            .get(&epoch_id)
+            .ok_or(LightClientError::UnverifiedEpoch(epoch_id))?;
+
+        if (proof.is_empty() && *tx_count != 1)
+            || !Merkle::verify_proof(order_root, tx_hash.clone(), proof)
+        {
+            return Err(LightClientError::InvalidTxProof { epoch_id, tx_hash }.into());
+        }
+        Ok(())
+    }
+
+    // Whether the trusted validators of more than 1/3 vote weight are among
+    // the voters of the proof.
+    fn vouched(&self, validators: &[Validator], proof: &Proof) -> bool {
+        let trusted = self
+            .validators
+            .iter()
+            .map(|v| (v.address.clone(), u64::from(v.vote_weight)))
+            .collect::<HashMap<_, _>>();
+
+        let total: u64 = trusted.values().sum();
+        let vouched: u64 = extract_voters(validators, &proof.bitmap)
+            .iter()
+            .filter_map(|v| trusted.get(&v.address))
+            .sum();
+        vouched * 3 > total
+    }
+}
+
+#[derive(Debug, Display, From)]
+pub enum LightClientError {
+    /// The epoch does not follow the latest verified epoch.
+    #[display(fmt = "Invalid epoch ID {}, expect {}", actual, expect)]
+    InvalidEpochId { expect: u64, actual: u64 },
+
+    /// The previous hash is not the hash of the latest verified epoch.
+    #[display(fmt = "Epoch {} has invalid previous hash", epoch_id)]
+    InvalidPrevHash { epoch_id: u64 },
+
+    /// The order root is not the merkle root of the ordered transactions.
+    #[display(fmt = "Epoch {} has invalid order root", epoch_id)]
+    InvalidOrderRoot { epoch_id: u64 },
+
+    /// The validators change without a new validator version.
+    #[display(
+        fmt = "Epoch {} has invalid validators of version {}",
+        epoch_id,
+        validator_version
+    )]
+    InvalidValidators {
+        epoch_id:          u64,
+        validator_version: u64,
+    },
+
+    /// The new validators are not vouched by the trusted validators.
+    #[display(fmt = "Epoch {} has untrusted validators", epoch_id)]
+    UntrustedValidators { epoch_id: u64 },
+
+    /// The epoch has not been verified.
+    #[display(fmt = "Epoch {} is not verified", _0)]
+    UnverifiedEpoch(u64),
+
+    /// The transaction is not in the order root.
+    #[display(fmt = "Transaction {:?} is not ordered in epoch {}", tx_hash, epoch_id)]
+    InvalidTxProof { epoch_id: u64, tx_hash: Hash },
+}
+
+impl Error for LightClientError {}
+
+impl From<LightClientError> for ProtocolError {
+    fn from(err: LightClientError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Consensus, Box::new(err))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryFrom;
+
+    use bytes::Bytes;
+    use overlord::types::{Vote, VoteType};
+
+    use common_crypto::{
+        BLS12381PrivateKey, BLS12381PublicKey, BLS12381Signature, Crypto, PrivateKey, PublicKey,
+        Signature, BLS12381,
+    };
+    use common_merkle::Merkle;
+    use protocol::fixed_codec::ProtocolFixedCodec;
+    use protocol::types::{Bloom, Epoch, EpochHeader, Hash, Proof, UserAddress, Validator};
+
+    use super::LightClient;
+
+    struct MockChain {
+        keys:       Vec<BLS12381PrivateKey>,
+        validators: Vec<Validator>,
+        epochs:     Vec<Epoch>,
+        proof:      Proof,
+    }
+
+    impl MockChain {
+        fn new() -> Self {
+            let keys = (1..=6u8).map(mock_bls_key).collect::<Vec<_>>();
+            let validators = keys
+                .iter()
+                .enumerate()
+                .map(|(i, key)| Validator {
+                    address:        UserAddress::from_hex(&format!(
+                        "1000000000000000000000000000000000000000{:02}",
+                        i
+                    ))
+                    .unwrap(),
+                    bls_pub_key:    key.pub_key().to_bytes(),
+                    propose_weight: 1,
+                    vote_weight:    1,
+                })
+                .collect::<Vec<_>>();
+            let genesis = mock_epoch(0, Hash::from_empty(), 0, validators[..4].to_vec(), vec![]);
+            let proof = genesis.header.proof.clone();
+
+            MockChain {
+                keys,
+                validators,
+                epochs: vec![genesis],
+                proof,
+            }
+        }
+
+        // Append an epoch signed by the validators of the indexes.
+        fn push(&mut self, version: u64, validators: &[usize], signers: &[usize]) -> Proof {
+            let prev = self.epochs.last().unwrap();
+            let prev_hash = Hash::digest(prev.encode_fixed().unwrap());
+            let epoch_id = prev.header.epoch_id + 1;
+            let tx_hashes = (0..5)
+                .map(|i| Hash::digest(Bytes::from(vec![epoch_id as u8, i])))
+                .collect();
+            let validators = validators
+                .iter()
+                .map(|&i| self.validators[i].clone())
+                .collect::<Vec<_>>();
+
+            // The proof of an epoch is carried by the next epoch.
+            let mut epoch = mock_epoch(epoch_id, prev_hash, version, validators, tx_hashes);
+            epoch.header.proof = self.proof.clone();
+            let proof = self.sign(&epoch, signers);
+
+            self.epochs.push(epoch);
+            self.proof = proof.clone();
+            proof
+        }
+
+        fn sign(&self, epoch: &Epoch, signers: &[usize]) -> Proof {
+            let epoch_hash = Hash::digest(epoch.encode_fixed().unwrap());
+            let vote = Vote {
+                epoch_id:   epoch.header.epoch_id,
+                round:      0,
+                vote_type:  VoteType::Precommit,
+                epoch_hash: epoch_hash.as_bytes(),
+            };
+            let hash = Hash::digest(Bytes::from(rlp::encode(&vote))).as_bytes();
+            let validators = &epoch.header.validators;
+
+            let mut sigs_pub_keys = vec![];
+            let mut voters = vec![];
+            for &i in signers.iter() {
+                let validator = &self.validators[i];
+                let signature = BLS12381::sign_message(&hash, &self.keys[i].to_bytes()).unwrap();
+                let pub_key = BLS12381PublicKey::try_from(validator.bls_pub_key.as_ref()).unwrap();
+                sigs_pub_keys.push((signature, pub_key));
+                voters.push(validator.address.clone());
+            }
+            let signature = BLS12381Signature::combine(sigs_pub_keys).to_bytes();
+
+            let mut authority = validators.to_vec();
+            authority.sort_by_key(|v| v.address.as_bytes());
+            let mut bitmap = vec![0u8; (authority.len() + 7) / 8];
+            for (index, validator) in authority.iter().enumerate() {
+                if voters.contains(&validator.address) {
+                    bitmap[index / 8] |= 0x80 >> (index % 8);
+                }
+            }
+
+            Proof {
+                epoch_id: epoch.header.epoch_id,
+                round: 0,
+                epoch_hash,
+                signature,
+                bitmap: Bytes::from(bitmap),
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_epochs() {
+        let mut chain = MockChain::new();
+        let mut client = LightClient::new(&chain.epochs[0]).unwrap();
+
+        chain.push(0, &[0, 1, 2, 3], &[0, 1, 2]);
+        chain.push(0, &[0, 1, 2, 3], &[0, 1, 2]);
+        let proof = chain.push(0, &[0, 1, 2, 3], &[1, 2, 3]);
+        client.verify_epochs(&chain.epochs[1..], &proof).unwrap();
+        assert_eq!(client.latest_epoch_id(), 3);
+        assert_eq!(
+            client.latest_hash(),
+            &Hash::digest(chain.epochs[3].encode_fixed().unwrap())
+        );
+
+        // Not enough vote weight.
+        let proof = chain.push(0, &[0, 1, 2, 3], &[0, 1]);
+        assert!(client.verify_epoch(&chain.epochs[4], &proof).is_err());
+
+        // The epoch must follow the latest verified epoch.
+        let mut forked = chain.epochs[4].clone();
+        forked.header.pre_hash = Hash::from_empty();
+        let proof = chain.sign(&forked, &[0, 1, 2]);
+        assert!(client.verify_epoch(&forked, &proof).is_err());
+
+        let proof = chain.sign(&chain.epochs[4], &[0, 1, 2]);
+        assert!(client.verify_epoch(&chain.epochs[3], &proof).is_err());
+        client.verify_epoch(&chain.epochs[4], &proof).unwrap();
+    }
+
+    #[test]
+    fn test_validator_change() {
+        let mut chain = MockChain::new();
+        let mut client = LightClient::new(&chain.epochs[0]).unwrap();
+
+        // The validators can not change without a new version.
+        let proof = chain.push(0, &[2, 3, 4, 5], &[2, 3, 4, 5]);
+        assert!(client.verify_epoch(&chain.epochs[1], &proof).is_err());
+
+        // The new validators are vouched by the 2 trusted validators.
+        let mut chain = MockChain::new();
+        let proof = chain.push(1, &[2, 3, 4, 5], &[2, 3, 4, 5]);
+        client.verify_epoch(&chain.epochs[1], &proof).unwrap();
+        assert_eq!(client.validator_version(), 1);
+        assert_eq!(client.validators(), &chain.epochs[1].header.validators[..]);
+
+        // Only 1 of 4 trusted validators signs for the new validators.
+        let mut chain = MockChain::new();
+        let mut client = LightClient::new(&chain.epochs[0]).unwrap();
+        let proof = chain.push(1, &[3, 4, 5], &[3, 4, 5]);
+        assert!(client.verify_epoch(&chain.epochs[1], &proof).is_err());
+    }
+
+    #[test]
+    fn test_verify_transaction() {
+        let mut chain = MockChain::new();
+        let mut client = LightClient::new(&chain.epochs[0]).unwrap();
+        let proof = chain.push(0, &[0, 1, 2, 3], &[0, 1, 2]);
+
+        let tx_hashes = chain.epochs[1].ordered_tx_hashes.clone();
+        let merkle = Merkle::from_hashes(tx_hashes.clone());
+        let tx_proof = merkle.get_proof_by_input_index(3).unwrap();
+        assert!(client
+            .verify_transaction(1, tx_hashes[3].clone(), &tx_proof)
+            .is_err());
+
+        client.verify_epoch(&chain.epochs[1], &proof).unwrap();
+        for (index, tx_hash) in tx_hashes.iter().enumerate() {
+            let tx_proof = merkle.get_proof_by_input_index(index).unwrap();
+            client
+                .verify_transaction(1, tx_hash.clone(), &tx_proof)
+                .unwrap();
+        }
+        assert!(client
+            .verify_transaction(1, tx_hashes[2].clone(), &tx_proof)
+            .is_err());
+
+        // The order root is not a transaction of the epoch.
+        let order_root = chain.epochs[1].header.order_root.clone();
+        assert!(client.verify_transaction(1, order_root, &[]).is_err());
+    }
+
+    #[test]
+    fn test_verify_single_transaction() {
+        let chain = MockChain::new();
+        let tx_hash = Hash::digest(Bytes::from("tx"));
+        let mut checkpoint = chain.epochs[0].clone();
+        checkpoint.ordered_tx_hashes = vec![tx_hash.clone()];
+        checkpoint.header.order_root = Merkle::from_hashes(vec![tx_hash.clone()])
+            .get_root_hash()
+            .unwrap();
+        let client = LightClient::new(&checkpoint).unwrap();
+
+        let tx_proof = Merkle::from_hashes(vec![tx_hash.clone()])
+            .get_proof_by_input_index(0)
+            .unwrap();
+        client.verify_transaction(0, tx_hash, &tx_proof).unwrap();
+    }
+
+    fn mock_bls_key(i: u8) -> BLS12381PrivateKey {
+        let mut key = [0u8; 48];
+        key[47] = i;
+        BLS12381PrivateKey::try_from(key.as_ref()).unwrap()
+    }
+
+    fn mock_epoch(
+        epoch_id: u64,
+        pre_hash: Hash,
+        validator_version: u64,
+        validators: Vec<Validator>,
+        ordered_tx_hashes: Vec<Hash>,
+    ) -> Epoch {
+        let order_root = Merkle::from_hashes(ordered_tx_hashes.clone())
+            .get_root_hash()
+            .unwrap_or_else(Hash::from_empty);
+
+        Epoch {
+            header: EpochHeader {
+                chain_id: Hash::from_empty(),
+                epoch_id,
+                pre_hash,
+                timestamp: 0,
+                logs_bloom: Bloom::default(),
+                order_root,
+                confirm_root: vec![],
+                exec_epoch_id: 0,
+                state_root: Hash::from_empty(),
+                receipt_root: vec![],
+                cycles_used: 0,
+                proposer: validators[0].address.clone(),
+                proof: Proof {
+                    epoch_id:   0,
+                    round:      0,
+                    epoch_hash: Hash::from_empty(),
+                    signature:  Bytes::new(),
+                    bitmap:     Bytes::new(),
+                },
+                validator_version,
+                validators,
+            },
+            ordered_tx_hashes,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, HashMap};
+
+use core_network::SessionId;
+use protocol::types::Hash;
+
+/// The peers which announced the transaction hashes, the first announcer of a
+/// hash is asked for the transaction, and all of them are excluded when the
+/// hash is announced again.
+pub struct Announcers {
+    capacity: usize,
+    seq:      u64,
+    sessions: HashMap<Hash, (u64, Vec<SessionId>)>,
+    // The announced hashes by the sequence of their first announcement, the
+    // earliest ones are forgotten once the capacity is reached.
+    order: BTreeMap<u64, Hash>,
+}
+
+impl Announcers {
+    pub fn new(capacity: usize) -> Self {
+        Announcers {
+            capacity,
+            seq: 0,
+            sessions: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    /// Record the session as an announcer of the hashes, return the hashes
+    /// announced for the first time.
+    pub fn record(&mut self, sid: SessionId, tx_hashes: Vec<Hash>) -> Vec<Hash> {
+        let mut first = Vec::new();
+
+        for tx_hash in tx_hashes.into_iter() {
+            if let Some((_, sessions)) = self.sessions.get_mut(&tx_hash) {
+                if !sessions.contains(&sid) {
+                    sessions.push(sid);
+                }
+                continue;
+            }
+
+            while self.sessions.len() >= self.capacity {
+                let earliest = match self.order.keys().next() {
+                    Some(seq) => *seq,
+                    None => break,
+                };
+                if let Some(tx_hash) = self.order.remove(&earliest) {
+                    self.sessions.remove(&tx_hash);
+                }
+            }
+
+            self.seq += 1;
+            self.sessions.insert(tx_hash.clone(), (self.seq, vec![sid]));
+            self.order.insert(self.seq, tx_hash.clone());
+            first.push(tx_hash);
+        }
+
+        first
+    }
+
+    /// Forget the announcer which failed to serve the hash, return the next
+    /// announcer to ask.
+    pub fn forget(&mut self, tx_hash: &Hash, sid: SessionId) -> Option<SessionId> {
+        let (_, sessions) = self.sessions.get_mut(tx_hash)?;
+        sessions.retain(|announcer| *announcer != sid);
+
+        let next = sessions.first().cloned();
+        if next.is_none() {
+            self.remove(tx_hash);
+        }
+        next
+    }
+
+    /// Remove the announcers of the hash.
+    pub fn remove(&mut self, tx_hash: &Hash) -> Vec<SessionId> {
+        match self.sessions.remove(tx_hash) {
+            Some((seq, sessions)) => {
+                self.order.remove(&seq);
+                sessions
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use core_network::SessionId;
+    use protocol::types::Hash;
+
+    use super::Announcers;
+
+    fn mock_hash(n: u8) -> Hash {
+        Hash::digest(Bytes::from(vec![n]))
+    }
+
+    #[test]
+    fn test_record() {
+        let mut announcers = Announcers::new(10);
+        let (a, b) = (SessionId::new(1), SessionId::new(2));
+
+        let first = announcers.record(a, vec![mock_hash(1), mock_hash(2)]);
+        assert_eq!(first, vec![mock_hash(1), mock_hash(2)]);
+
+        let first = announcers.record(b, vec![mock_hash(2), mock_hash(3)]);
+        assert_eq!(first, vec![mock_hash(3)]);
+
+        assert_eq!(announcers.remove(&mock_hash(2)), vec![a, b]);
+        assert!(announcers.remove(&mock_hash(2)).is_empty());
+    }
+
+    #[test]
+    fn test_forget() {
+        let mut announcers = Announcers::new(10);
+        let (a, b) = (SessionId::new(1), SessionId::new(2));
+
+        announcers.record(a, vec![mock_hash(1)]);
+        announcers.record(b, vec![mock_hash(1)]);
+
+        // Only the failed announcer is forgotten.
+        assert_eq!(announcers.forget(&mock_hash(1), a), Some(b));
+        assert_eq!(announcers.forget(&mock_hash(1), b), None);
+
+        // The hash is announced for the first time again.
+        assert_eq!(announcers.record(a, vec![mock_hash(1)]), vec![mock_hash(1)]);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut announcers = Announcers::new(2);
+        let sid = SessionId::new(1);
+
+        announcers.record(sid, vec![mock_hash(1), mock_hash(2), mock_hash(3)]);
+
+        assert!(announcers.remove(&mock_hash(1)).is_empty());
+        assert_eq!(announcers.remove(&mock_hash(2)), vec![sid]);
+        assert_eq!(announcers.remove(&mock_hash(3)), vec![sid]);
+    }
+
+    #[test]
+    fn test_remove_bounded() {
+        let mut announcers = Announcers::new(2);
+        let sid = SessionId::new(1);
+
+        for n in 0..10 {
+            announcers.record(sid, vec![mock_hash(n)]);
+            announcers.remove(&mock_hash(n));
+            announcers.record(sid, vec![mock_hash(n)]);
+            announcers.forget(&mock_hash(n), sid);
+        }
+        assert!(announcers.sessions.is_empty());
+        assert!(announcers.order.is_empty());
+
+        // A removed hash announced again is forgotten by its new order.
+        announcers.record(sid, vec![mock_hash(1)]);
+        announcers.remove(&mock_hash(1));
+        announcers.record(sid, vec![mock_hash(2), mock_hash(1), mock_hash(3)]);
+        assert!(announcers.remove(&mock_hash(2)).is_empty());
+        assert_eq!(announcers.remove(&mock_hash(1)), vec![sid]);
+        assert_eq!(announcers.remove(&mock_hash(3)), vec![sid]);
+    }
+}
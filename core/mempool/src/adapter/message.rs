@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use futures::future::try_join_all;
+use futures::future::join_all;
 use protocol::{
     traits::{Context, MemPool, MessageHandler, Priority, Rpc},
     types::{Hash, SignedTransaction},
@@ -11,55 +11,41 @@ use serde_derive::{Deserialize, Serialize};
 
 use crate::context::TxContext;
 
-pub const END_GOSSIP_NEW_TXS: &str = "/gossip/mempool/new_txs";
+pub const END_GOSSIP_NEW_TX_HASHES: &str = "/gossip/mempool/new_tx_hashes";
 pub const END_RPC_PULL_TXS: &str = "/rpc_call/mempool/pull_txs";
 pub const END_RESP_PULL_TXS: &str = "/rpc_resp/mempool/pull_txs";
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct MsgNewTxs {
+pub struct MsgNewTxHashes {
     #[serde(with = "core_network::serde_multi")]
-    pub batch_stxs: Vec<SignedTransaction>,
+    pub hashes: Vec<Hash>,
 }
 
-pub struct NewTxsHandler<M> {
+pub struct NewTxHashesHandler<M> {
     mem_pool: Arc<M>,
 }
 
-impl<M> NewTxsHandler<M>
+impl<M> NewTxHashesHandler<M>
 where
     M: MemPool,
 {
     pub fn new(mem_pool: Arc<M>) -> Self {
-        NewTxsHandler { mem_pool }
+        NewTxHashesHandler { mem_pool }
     }
 }
 
 #[async_trait]
-impl<M> MessageHandler for NewTxsHandler<M>
+impl<M> MessageHandler for NewTxHashesHandler<M>
 where
     M: MemPool + 'static,
 {
-    type Message = MsgNewTxs;
+    type Message = MsgNewTxHashes;
 
     async fn process(&self, ctx: Context, msg: Self::Message) -> ProtocolResult<()> {
         let ctx = ctx.mark_network_origin_new_txs();
 
-        let insert_stx = |stx| -> _ {
-            let mem_pool = Arc::clone(&self.mem_pool);
-            let ctx = ctx.clone();
-
-            runtime::spawn(async move { mem_pool.insert(ctx, stx).await })
-        };
-
-        // Concurrently insert them
-        try_join_all(
-            msg.batch_stxs
-                .into_iter()
-                .map(insert_stx)
-                .collect::<Vec<_>>(),
-        )
-        .await
-        .map(|_| ())
+        // Pull the unknown transactions from the announcer
+        self.mem_pool.receive_tx_hashes(ctx, msg.hashes).await
     }
 }
 
@@ -99,7 +85,15 @@ where
     type Message = MsgPullTxs;
 
     async fn process(&self, ctx: Context, msg: Self::Message) -> ProtocolResult<()> {
-        let sig_txs = self.mem_pool.get_full_txs(ctx.clone(), msg.hashes).await?;
+        // Respond with the transactions found, the missing ones are pulled from
+        // the next announcer, and the proposal ones are checked by length.
+        let found = join_all(
+            msg.hashes
+                .into_iter()
+                .map(|tx_hash| self.mem_pool.get_full_txs(ctx.clone(), vec![tx_hash])),
+        )
+        .await;
+        let sig_txs = found.into_iter().filter_map(Result::ok).flatten().collect();
         let resp_msg = MsgPushTxs { sig_txs };
 
         self.network
@@ -1,6 +1,8 @@
+mod announcer;
 pub mod message;
 
 use std::{
+    collections::HashMap,
     error::Error,
     marker::PhantomData,
     sync::atomic::{AtomicU64, Ordering},
@@ -14,34 +16,68 @@ use futures::{
     channel::mpsc::{
         channel, unbounded, Receiver, Sender, TrySendError, UnboundedReceiver, UnboundedSender,
     },
+    channel::oneshot,
+    future::{self, Either},
     lock::Mutex,
-    select,
+    pin_mut, select,
     stream::StreamExt,
 };
 use futures_timer::Delay;
 use log::{debug, error};
+use parking_lot::Mutex as SyncMutex;
+use rayon::{
+    iter::{IntoParallelIterator, ParallelIterator},
+    ThreadPool, ThreadPoolBuilder,
+};
 
 use common_crypto::Crypto;
+use core_network::{NetworkContext, SessionId};
 use protocol::{
     fixed_codec::ProtocolFixedCodec,
-    traits::{Context, Gossip, MemPoolAdapter, Priority, Rpc, Storage},
-    types::{Hash, SignedTransaction},
+    traits::executor::{Executor, ExecutorFactory, TrieDB},
+    traits::{Context, Gossip, MemPoolAdapter, PendingSpend, Priority, Rpc, Storage},
+    types::{
+        Address, AssetID, Balance, EpochHeader, Hash, MerkleRoot, SignedTransaction, UserAddress,
+    },
     ProtocolError, ProtocolErrorKind, ProtocolResult,
 };
 
+use crate::adapter::announcer::Announcers;
 use crate::adapter::message::{
-    MsgNewTxs, MsgPullTxs, MsgPushTxs, END_GOSSIP_NEW_TXS, END_RPC_PULL_TXS,
+    MsgNewTxHashes, MsgPullTxs, MsgPushTxs, END_GOSSIP_NEW_TX_HASHES, END_RPC_PULL_TXS,
 };
+use crate::context::TxContext;
+use crate::tx_cache::carrying_asset;
 use crate::MemPoolError;
 
 pub const DEFAULT_BROADCAST_TXS_SIZE: usize = 200;
 pub const DEFAULT_BROADCAST_TXS_INTERVAL: u64 = 200; // milliseconds
 
+// The number of announced hashes whose announcers are remembered
+const ANNOUNCERS_CAPACITY: usize = 100_000;
+
+// The timeout of pulling transactions from a peer
+const PULL_TXS_TIMEOUT: Duration = Duration::from_secs(10);
+
+// The number of balances cached for the latest state root
+const BALANCES_CAPACITY: usize = 100_000;
+
+// A hash to announce and the sessions which already know it
+type Announcement = (Hash, Vec<SessionId>);
+
+// The balances read from the latest state root, so that an executor isn't
+// built for every transaction. They are dropped when the state root changes.
+#[derive(Default)]
+struct BalanceCache {
+    state_root: MerkleRoot,
+    balances:   HashMap<(Address, AssetID), Balance>,
+}
+
 struct IntervalTxsBroadcaster;
 
 impl IntervalTxsBroadcaster {
     pub async fn broadcast<G>(
-        stx_rx: UnboundedReceiver<SignedTransaction>,
+        hash_rx: UnboundedReceiver<Announcement>,
         interval_reached: Receiver<()>,
         tx_size: usize,
         gossip: G,
@@ -49,19 +85,19 @@ impl IntervalTxsBroadcaster {
     ) where
         G: Gossip + Clone + Unpin + 'static,
     {
-        let mut stx_rx = stx_rx.fuse();
+        let mut hash_rx = hash_rx.fuse();
         let mut interval_rx = interval_reached.fuse();
 
-        let mut txs_cache = Vec::with_capacity(tx_size);
+        let mut hashes_cache = Vec::with_capacity(tx_size);
 
         loop {
             select! {
-                opt_stx = stx_rx.next() => {
-                    if let Some(stx) = opt_stx {
-                        txs_cache.push(stx);
+                opt_hash = hash_rx.next() => {
+                    if let Some(announcement) = opt_hash {
+                        hashes_cache.push(announcement);
 
-                        if txs_cache.len() == tx_size {
-                            Self::do_broadcast(&mut txs_cache, &gossip, err_tx.clone()).await
+                        if hashes_cache.len() == tx_size {
+                            Self::do_broadcast(&mut hashes_cache, &gossip, err_tx.clone()).await
                         }
                     } else {
                         debug!("mempool: default mempool adapter dropped")
@@ -69,7 +105,7 @@ impl IntervalTxsBroadcaster {
                 },
                 signal = interval_rx.next() => {
                     if signal.is_some() {
-                        Self::do_broadcast(&mut txs_cache, &gossip, err_tx.clone()).await
+                        Self::do_broadcast(&mut hashes_cache, &gossip, err_tx.clone()).await
                     }
                 },
                 complete => break,
@@ -98,21 +134,21 @@ impl IntervalTxsBroadcaster {
     }
 
     async fn do_broadcast<G>(
-        txs_cache: &mut Vec<SignedTransaction>,
+        hashes_cache: &mut Vec<Announcement>,
         gossip: &G,
         err_tx: UnboundedSender<ProtocolError>,
     ) where
         G: Gossip + Unpin,
     {
-        if txs_cache.is_empty() {
+        if hashes_cache.is_empty() {
             return;
         }
 
-        let batch_stxs = txs_cache.drain(..).collect::<Vec<_>>();
-        let gossip_msg = MsgNewTxs { batch_stxs };
-
-        let ctx = Context::new();
-        let end = END_GOSSIP_NEW_TXS;
+        // Hashes known by the same sessions are announced together
+        let mut batches: HashMap<Vec<SessionId>, Vec<Hash>> = HashMap::new();
+        for (hash, excluded) in hashes_cache.drain(..) {
+            batches.entry(excluded).or_default().push(hash);
+        }
 
         let report_if_err = move |ret: ProtocolResult<()>| {
             if let Err(err) = ret {
@@ -122,40 +158,61 @@ impl IntervalTxsBroadcaster {
             }
         };
 
-        report_if_err(
-            gossip
-                .broadcast(ctx, end, gossip_msg, Priority::Normal)
-                .await,
-        )
+        for (excluded, hashes) in batches.into_iter() {
+            let ctx = Context::new().set_excluded_sessions(excluded);
+            let gossip_msg = MsgNewTxHashes { hashes };
+            let end = END_GOSSIP_NEW_TX_HASHES;
+
+            report_if_err(
+                gossip
+                    .broadcast(ctx, end, gossip_msg, Priority::Normal)
+                    .await,
+            )
+        }
     }
 }
 
-pub struct DefaultMemPoolAdapter<C, N, S> {
+pub struct DefaultMemPoolAdapter<EF, C, N, S, DB> {
     network: N,
     storage: Arc<S>,
+    trie_db: Arc<DB>,
 
-    timeout_gap: AtomicU64,
+    timeout_gap:  AtomicU64,
+    cycles_price: u64,
+    cycles_limit: u64,
 
-    stx_tx: UnboundedSender<SignedTransaction>,
-    err_rx: Mutex<UnboundedReceiver<ProtocolError>>,
+    hash_tx:    UnboundedSender<Announcement>,
+    err_rx:     Mutex<UnboundedReceiver<ProtocolError>>,
+    announcers: SyncMutex<Announcers>,
+    balances:   SyncMutex<BalanceCache>,
 
-    pin_c: PhantomData<C>,
+    // Workers to verify the signatures of a batch in parallel
+    verify_pool: ThreadPool,
+
+    pin_c:  PhantomData<C>,
+    pin_ef: PhantomData<EF>,
 }
 
-impl<C, N, S> DefaultMemPoolAdapter<C, N, S>
+impl<EF, C, N, S, DB> DefaultMemPoolAdapter<EF, C, N, S, DB>
 where
+    EF: ExecutorFactory<DB>,
     C: Crypto,
     N: Rpc + Gossip + Clone + Unpin + 'static,
     S: Storage,
+    DB: TrieDB,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         network: N,
         storage: Arc<S>,
+        trie_db: Arc<DB>,
         timeout_gap: u64,
+        cycles_price: u64,
+        cycles_limit: u64,
         broadcast_txs_size: usize,
         broadcast_txs_interval: u64,
     ) -> Self {
-        let (stx_tx, stx_rx) = unbounded();
+        let (hash_tx, hash_rx) = unbounded();
         let (err_tx, err_rx) = unbounded();
         let (signal_tx, interval_reached) = channel(1);
 
@@ -165,33 +222,93 @@ where
         ));
 
         runtime::spawn(IntervalTxsBroadcaster::broadcast(
-            stx_rx,
+            hash_rx,
             interval_reached,
             broadcast_txs_size,
             network.clone(),
             err_tx,
         ));
 
+        let verify_pool = build_verify_pool();
+
         DefaultMemPoolAdapter {
             network,
             storage,
+            trie_db,
 
             timeout_gap: AtomicU64::new(timeout_gap),
+            cycles_price,
+            cycles_limit,
 
-            stx_tx,
+            hash_tx,
             err_rx: Mutex::new(err_rx),
+            announcers: SyncMutex::new(Announcers::new(ANNOUNCERS_CAPACITY)),
+            balances: SyncMutex::new(BalanceCache::default()),
+
+            verify_pool,
 
             pin_c: PhantomData,
+            pin_ef: PhantomData,
         }
     }
+
+    // Read the balance from the cache, or from the state of the header. The
+    // executor is built once for all the misses of a transaction.
+    fn get_balance(
+        &self,
+        header: &EpochHeader,
+        executor: &mut Option<Box<dyn Executor>>,
+        address: &Address,
+        asset_id: &AssetID,
+    ) -> ProtocolResult<Balance> {
+        let key = (address.clone(), asset_id.clone());
+        {
+            let mut cache = self.balances.lock();
+            if cache.state_root != header.state_root {
+                cache.state_root = header.state_root.clone();
+                cache.balances.clear();
+            }
+            if let Some(balance) = cache.balances.get(&key) {
+                return Ok(balance.clone());
+            }
+        }
+
+        let balance = match executor {
+            Some(executor) => executor.get_balance(address, asset_id)?,
+            None => {
+                let built = EF::from_root(
+                    header.chain_id.clone(),
+                    header.state_root.clone(),
+                    Arc::clone(&self.trie_db),
+                    header.epoch_id,
+                    self.cycles_price,
+                    Address::User(header.proposer.clone()),
+                )?;
+                let balance = built.get_balance(address, asset_id)?;
+                *executor = Some(built);
+                balance
+            }
+        };
+
+        let mut cache = self.balances.lock();
+        if cache.state_root == header.state_root {
+            if cache.balances.len() >= BALANCES_CAPACITY {
+                cache.balances.clear();
+            }
+            cache.balances.insert(key, balance.clone());
+        }
+        Ok(balance)
+    }
 }
 
 #[async_trait]
-impl<C, N, S> MemPoolAdapter for DefaultMemPoolAdapter<C, N, S>
+impl<EF, C, N, S, DB> MemPoolAdapter for DefaultMemPoolAdapter<EF, C, N, S, DB>
 where
+    EF: ExecutorFactory<DB> + 'static,
     C: Crypto + Send + Sync + 'static,
     N: Rpc + Gossip + Clone + Unpin + 'static,
     S: Storage + 'static,
+    DB: TrieDB + 'static,
 {
     async fn pull_txs(
         &self,
@@ -200,17 +317,34 @@ where
     ) -> ProtocolResult<Vec<SignedTransaction>> {
         let pull_msg = MsgPullTxs { hashes: tx_hashes };
 
-        let resp_msg = self
-            .network
-            .call::<MsgPullTxs, MsgPushTxs>(ctx, END_RPC_PULL_TXS, pull_msg, Priority::High)
-            .await?;
+        let call = self.network.call::<MsgPullTxs, MsgPushTxs>(
+            ctx,
+            END_RPC_PULL_TXS,
+            pull_msg,
+            Priority::High,
+        );
+        let delay = Delay::new(PULL_TXS_TIMEOUT);
+        pin_mut!(call);
+        pin_mut!(delay);
 
-        Ok(resp_msg.sig_txs)
+        match future::select(call, delay).await {
+            Either::Left((resp_msg, _)) => Ok(resp_msg?.sig_txs),
+            Either::Right(_) => Err(AdapterError::PullTxsTimeout.into()),
+        }
     }
 
-    async fn broadcast_tx(&self, _ctx: Context, stx: SignedTransaction) -> ProtocolResult<()> {
-        self.stx_tx
-            .unbounded_send(stx)
+    async fn broadcast_tx(&self, ctx: Context, stx: SignedTransaction) -> ProtocolResult<()> {
+        let mut excluded = self.announcers.lock().remove(&stx.tx_hash);
+        if ctx.is_network_origin_txs() {
+            if let Ok(sid) = ctx.session_id() {
+                if !excluded.contains(&sid) {
+                    excluded.push(sid);
+                }
+            }
+        }
+
+        self.hash_tx
+            .unbounded_send((stx.tx_hash, excluded))
             .map_err(AdapterError::from)?;
 
         if let Some(mut err_rx) = self.err_rx.try_lock() {
@@ -224,23 +358,76 @@ where
         Ok(())
     }
 
+    async fn record_announcers(
+        &self,
+        ctx: Context,
+        tx_hashes: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Hash>> {
+        match ctx.session_id() {
+            Ok(sid) => Ok(self.announcers.lock().record(sid, tx_hashes)),
+            // Not from a peer, nothing to record
+            Err(_) => Ok(tx_hashes),
+        }
+    }
+
+    async fn forget_announcers(
+        &self,
+        ctx: Context,
+        tx_hashes: Vec<Hash>,
+    ) -> ProtocolResult<Vec<(Context, Vec<Hash>)>> {
+        let sid = match ctx.session_id() {
+            Ok(sid) => sid,
+            // Not from a peer, nothing recorded
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let mut next: HashMap<SessionId, Vec<Hash>> = HashMap::new();
+        {
+            let mut announcers = self.announcers.lock();
+            for tx_hash in tx_hashes.into_iter() {
+                if let Some(next_sid) = announcers.forget(&tx_hash, sid) {
+                    next.entry(next_sid).or_default().push(tx_hash);
+                }
+            }
+        }
+
+        Ok(next
+            .into_iter()
+            .map(|(sid, tx_hashes)| (ctx.clone().set_session_id(sid), tx_hashes))
+            .collect())
+    }
+
     async fn check_signature(&self, _ctx: Context, tx: SignedTransaction) -> ProtocolResult<()> {
-        let hash = tx.tx_hash.as_bytes();
-        let pub_key = tx.pubkey.as_ref();
-        let sig = tx.signature.as_ref();
+        verify_signature::<C>(&tx)
+    }
+
+    async fn check_signatures(
+        &self,
+        _ctx: Context,
+        txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        let (verified_tx, verified_rx) = oneshot::channel();
 
-        C::verify_signature(hash.as_ref(), sig, pub_key).map_err(|_| {
-            MemPoolError::CheckSig {
-                tx_hash: tx.tx_hash,
+        self.verify_pool.spawn(move || {
+            let verified = verify_signatures::<C>(txs);
+            if verified_tx.send(verified).is_err() {
+                debug!("mempool: batch verification dropped");
             }
-            .into()
-        })
+        });
+
+        let verified = verified_rx
+            .await
+            .map_err(|_| AdapterError::VerifyPoolDrop)?;
+        Ok(verified)
     }
 
-    // TODO: Verify Fee?
     // TODO: Verify Nonce?
-    // TODO: Cycle limit?
-    async fn check_transaction(&self, _ctx: Context, stx: SignedTransaction) -> ProtocolResult<()> {
+    async fn check_transaction(
+        &self,
+        _ctx: Context,
+        stx: SignedTransaction,
+        pending: PendingSpend,
+    ) -> ProtocolResult<()> {
         // Verify transaction hash
         let fixed_bytes = stx.raw.encode_fixed()?;
         let tx_hash = Hash::digest(fixed_bytes);
@@ -285,7 +472,28 @@ where
             return Err(timeout.into());
         }
 
-        Ok(())
+        // Verify cycles limit
+        if stx.raw.fee.cycle > self.cycles_limit {
+            let exceed_cycles_limit = MemPoolError::ExceedCyclesLimit {
+                tx_hash:      stx.tx_hash,
+                cycles_limit: self.cycles_limit,
+            };
+
+            return Err(exceed_cycles_limit.into());
+        }
+
+        // Verify balance on the state root of the latest epoch. The epochs are
+        // executed in background, so the state can be up to `MAX_EXEC_LAG` of
+        // the consensus epochs stale, and the check is best-effort.
+        let header = latest_epoch.header;
+        let sender = Address::User(UserAddress::from_pubkey_bytes(stx.pubkey.clone())?);
+        let mut executor = None;
+        check_balance(
+            |asset_id| self.get_balance(&header, &mut executor, &sender, asset_id),
+            &stx,
+            self.cycles_price,
+            &pending,
+        )
     }
 
     async fn check_storage_exist(&self, _ctx: Context, tx_hash: Hash) -> ProtocolResult<()> {
@@ -306,12 +514,110 @@ where
         let epoch_id = self.storage.get_latest_epoch().await?.header.epoch_id;
         Ok(epoch_id)
     }
+
+    async fn journal_txs(&self, _ctx: Context, txs: Vec<SignedTransaction>) -> ProtocolResult<()> {
+        self.storage.insert_pending_transactions(txs).await
+    }
+
+    async fn remove_journal_txs(&self, _ctx: Context, tx_hashes: Vec<Hash>) -> ProtocolResult<()> {
+        self.storage.remove_pending_transactions(tx_hashes).await
+    }
+
+    async fn load_journal_txs(&self, _ctx: Context) -> ProtocolResult<Vec<SignedTransaction>> {
+        self.storage.get_pending_transactions().await
+    }
+}
+
+pub(crate) fn build_verify_pool() -> ThreadPool {
+    ThreadPoolBuilder::new()
+        .thread_name(|index| format!("mempool_verify_{}", index))
+        .build()
+        .expect("build verify pool")
+}
+
+// Verify the signatures in parallel on the current thread pool, the
+// transactions of invalid signatures are dropped.
+pub(crate) fn verify_signatures<C: Crypto>(txs: Vec<SignedTransaction>) -> Vec<SignedTransaction> {
+    txs.into_par_iter()
+        .filter(|tx| match verify_signature::<C>(tx) {
+            Ok(()) => true,
+            Err(err) => {
+                debug!("mempool: {}", err);
+                false
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn verify_signature<C: Crypto>(tx: &SignedTransaction) -> ProtocolResult<()> {
+    let hash = tx.tx_hash.as_bytes();
+    let pub_key = tx.pubkey.as_ref();
+    let sig = tx.signature.as_ref();
+
+    C::verify_signature(hash.as_ref(), sig, pub_key).map_err(|_| {
+        MemPoolError::CheckSig {
+            tx_hash: tx.tx_hash.clone(),
+        }
+        .into()
+    })
+}
+
+// The sender must afford the fee of all the declared cycles and the carried
+// asset, which are summed up if they are the same asset, besides what its
+// pending transactions can spend.
+fn check_balance<F>(
+    mut get_balance: F,
+    stx: &SignedTransaction,
+    cycles_price: u64,
+    pending: &PendingSpend,
+) -> ProtocolResult<()>
+where
+    F: FnMut(&AssetID) -> ProtocolResult<Balance>,
+{
+    let mut required: HashMap<AssetID, Balance> = HashMap::new();
+    let fee = &stx.raw.fee;
+    let fee_cycles = pending
+        .fee_cycles
+        .get(&fee.asset_id)
+        .map_or(0, |cycles| *cycles);
+    *required.entry(fee.asset_id.clone()).or_default() +=
+        (Balance::from(fee.cycle) + Balance::from(fee_cycles)) * Balance::from(cycles_price);
+
+    if let Some(carrying_asset) = carrying_asset(stx) {
+        *required.entry(carrying_asset.asset_id.clone()).or_default() += &carrying_asset.amount;
+    }
+    for (asset_id, amount) in pending.carried.iter() {
+        *required.entry(asset_id.clone()).or_default() += amount;
+    }
+
+    for (asset_id, amount) in required.into_iter() {
+        if amount == Balance::from(0u64) {
+            continue;
+        }
+
+        let balance = get_balance(&asset_id)?;
+        if balance < amount {
+            return Err(MemPoolError::InsufficientBalance {
+                tx_hash: stx.tx_hash.clone(),
+                asset_id,
+            }
+            .into());
+        }
+    }
+
+    Ok(())
 }
 
 #[derive(Debug, Display)]
 pub enum AdapterError {
     #[display(fmt = "adapter: interval broadcaster drop")]
     IntervalBroadcasterDrop,
+
+    #[display(fmt = "adapter: verify pool drop")]
+    VerifyPoolDrop,
+
+    #[display(fmt = "adapter: pull txs timeout")]
+    PullTxsTimeout,
 }
 
 impl Error for AdapterError {}
@@ -330,13 +636,15 @@ impl From<AdapterError> for ProtocolError {
 
 #[cfg(test)]
 mod tests {
-    use super::IntervalTxsBroadcaster;
+    use super::{check_balance, IntervalTxsBroadcaster};
+
+    use crate::{adapter::message::MsgNewTxHashes, tests::default_mock_txs};
 
-    use crate::{adapter::message::MsgNewTxs, tests::default_mock_txs};
+    use core_network::{NetworkContext, SessionId};
 
     use protocol::{
-        traits::{Context, Gossip, MessageCodec, Priority},
-        types::UserAddress,
+        traits::{Context, Gossip, MessageCodec, PendingSpend, Priority},
+        types::{AssetID, Balance, UserAddress},
         ProtocolResult,
     };
 
@@ -357,6 +665,7 @@ mod tests {
     #[derive(Clone)]
     struct MockGossip {
         msgs:      Arc<Mutex<Vec<Bytes>>>,
+        excluded:  Arc<Mutex<Vec<Vec<SessionId>>>>,
         signal_tx: UnboundedSender<()>,
     }
 
@@ -364,6 +673,7 @@ mod tests {
         pub fn new(signal_tx: UnboundedSender<()>) -> Self {
             MockGossip {
                 msgs: Default::default(),
+                excluded: Default::default(),
                 signal_tx,
             }
         }
@@ -373,7 +683,7 @@ mod tests {
     impl Gossip for MockGossip {
         async fn broadcast<M>(
             &self,
-            _: Context,
+            ctx: Context,
             _: &str,
             mut msg: M,
             _: Priority,
@@ -383,6 +693,7 @@ mod tests {
         {
             let bytes = msg.encode().await.expect("encode message fail");
             self.msgs.lock().push(bytes);
+            self.excluded.lock().push(ctx.excluded_sessions());
 
             self.signal_tx
                 .unbounded_send(())
@@ -409,7 +720,9 @@ mod tests {
     macro_rules! pop_msg {
         ($msgs:expr) => {{
             let msg = $msgs.pop().expect("should have one message");
-            MsgNewTxs::decode(msg).await.expect("decode MsgNewTxs fail")
+            MsgNewTxHashes::decode(msg)
+                .await
+                .expect("decode MsgNewTxHashes fail")
         }};
     }
 
@@ -427,7 +740,7 @@ mod tests {
 
     #[runtime::test(runtime_tokio::Tokio)]
     async fn test_interval_broadcast_reach_cache_size() {
-        let (stx_tx, stx_rx) = unbounded();
+        let (hash_tx, hash_rx) = unbounded();
         let (err_tx, _err_rx) = unbounded();
         let (_signal_tx, interval_reached) = channel(1);
         let tx_size = 10;
@@ -435,7 +748,7 @@ mod tests {
         let gossip = MockGossip::new(broadcast_signal_tx);
 
         runtime::spawn(IntervalTxsBroadcaster::broadcast(
-            stx_rx,
+            hash_rx,
             interval_reached,
             tx_size,
             gossip.clone(),
@@ -443,7 +756,9 @@ mod tests {
         ));
 
         for stx in default_mock_txs(11).into_iter() {
-            stx_tx.unbounded_send(stx).expect("send stx fail");
+            hash_tx
+                .unbounded_send((stx.tx_hash, vec![]))
+                .expect("send hash fail");
         }
 
         broadcast_signal_rx.next().await;
@@ -451,12 +766,12 @@ mod tests {
         assert_eq!(msgs.len(), 1, "should only have one message");
 
         let msg = pop_msg!(msgs);
-        assert_eq!(msg.batch_stxs.len(), 10, "should only have 10 stx");
+        assert_eq!(msg.hashes.len(), 10, "should only have 10 hashes");
     }
 
     #[runtime::test(runtime_tokio::Tokio)]
     async fn test_interval_broadcast_reach_interval() {
-        let (stx_tx, stx_rx) = unbounded();
+        let (hash_tx, hash_rx) = unbounded();
         let (err_tx, _err_rx) = unbounded();
         let (signal_tx, interval_reached) = channel(1);
         let tx_size = 10;
@@ -465,7 +780,7 @@ mod tests {
 
         runtime::spawn(IntervalTxsBroadcaster::timer(signal_tx, 200));
         runtime::spawn(IntervalTxsBroadcaster::broadcast(
-            stx_rx,
+            hash_rx,
             interval_reached,
             tx_size,
             gossip.clone(),
@@ -473,7 +788,9 @@ mod tests {
         ));
 
         for stx in default_mock_txs(9).into_iter() {
-            stx_tx.unbounded_send(stx).expect("send stx fail");
+            hash_tx
+                .unbounded_send((stx.tx_hash, vec![]))
+                .expect("send hash fail");
         }
 
         broadcast_signal_rx.next().await;
@@ -481,12 +798,12 @@ mod tests {
         assert_eq!(msgs.len(), 1, "should only have one message");
 
         let msg = pop_msg!(msgs);
-        assert_eq!(msg.batch_stxs.len(), 9, "should only have 9 stx");
+        assert_eq!(msg.hashes.len(), 9, "should only have 9 hashes");
     }
 
     #[runtime::test(runtime_tokio::Tokio)]
     async fn test_interval_broadcast() {
-        let (stx_tx, stx_rx) = unbounded();
+        let (hash_tx, hash_rx) = unbounded();
         let (err_tx, _err_rx) = unbounded();
         let (signal_tx, interval_reached) = channel(1);
         let tx_size = 10;
@@ -495,7 +812,7 @@ mod tests {
 
         runtime::spawn(IntervalTxsBroadcaster::timer(signal_tx, 200));
         runtime::spawn(IntervalTxsBroadcaster::broadcast(
-            stx_rx,
+            hash_rx,
             interval_reached,
             tx_size,
             gossip.clone(),
@@ -503,7 +820,9 @@ mod tests {
         ));
 
         for stx in default_mock_txs(19).into_iter() {
-            stx_tx.unbounded_send(stx).expect("send stx fail");
+            hash_tx
+                .unbounded_send((stx.tx_hash, vec![]))
+                .expect("send hash fail");
         }
 
         // Should got two broadcast
@@ -515,16 +834,86 @@ mod tests {
 
         let msg = pop_msg!(msgs);
         assert_eq!(
-            msg.batch_stxs.len(),
+            msg.hashes.len(),
             9,
-            "last message should only have 9 stx"
+            "last message should only have 9 hashes"
         );
 
         let msg = pop_msg!(msgs);
         assert_eq!(
-            msg.batch_stxs.len(),
+            msg.hashes.len(),
             10,
-            "first message should only have 10 stx"
+            "first message should only have 10 hashes"
         );
     }
+
+    #[runtime::test(runtime_tokio::Tokio)]
+    async fn test_interval_broadcast_exclude_announcers() {
+        let (hash_tx, hash_rx) = unbounded();
+        let (err_tx, _err_rx) = unbounded();
+        let (_signal_tx, interval_reached) = channel(1);
+        let tx_size = 3;
+        let (broadcast_signal_tx, mut broadcast_signal_rx) = unbounded();
+        let gossip = MockGossip::new(broadcast_signal_tx);
+
+        runtime::spawn(IntervalTxsBroadcaster::broadcast(
+            hash_rx,
+            interval_reached,
+            tx_size,
+            gossip.clone(),
+            err_tx,
+        ));
+
+        let announcer = SessionId::new(1);
+        for (i, stx) in default_mock_txs(3).into_iter().enumerate() {
+            let excluded = if i == 0 { vec![] } else { vec![announcer] };
+            hash_tx
+                .unbounded_send((stx.tx_hash, excluded))
+                .expect("send hash fail");
+        }
+
+        // Hashes known by different sessions are announced separately
+        broadcast_signal_rx.next().await;
+        broadcast_signal_rx.next().await;
+
+        let msgs = gossip.msgs.lock().drain(..).collect::<Vec<_>>();
+        let excluded = gossip.excluded.lock().drain(..).collect::<Vec<_>>();
+        assert_eq!(msgs.len(), 2, "should only have two messages");
+
+        for (msg, excluded) in msgs.into_iter().zip(excluded.into_iter()) {
+            let msg = MsgNewTxHashes::decode(msg)
+                .await
+                .expect("decode MsgNewTxHashes fail");
+
+            if excluded.is_empty() {
+                assert_eq!(msg.hashes.len(), 1);
+            } else {
+                assert_eq!(excluded, vec![announcer]);
+                assert_eq!(msg.hashes.len(), 2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_balance() {
+        // The fee and the carried amount of mock transactions are the same asset,
+        // the fee cycle is 1 and the carried amount is 42.
+        let stx = default_mock_txs(1).pop().unwrap();
+        let cycles_price = 2;
+        let balance = |amount: u64| {
+            move |_: &AssetID| -> ProtocolResult<Balance> { Ok(Balance::from(amount)) }
+        };
+
+        let pending = PendingSpend::default();
+        assert!(check_balance(balance(44), &stx, cycles_price, &pending).is_ok());
+        assert!(check_balance(balance(43), &stx, cycles_price, &pending).is_err());
+
+        // The pending transactions of the sender spend the balance too.
+        let mut pending = PendingSpend::default();
+        let asset_id = stx.raw.fee.asset_id.clone();
+        pending.fee_cycles.insert(asset_id.clone(), 1);
+        pending.carried.insert(asset_id, Balance::from(42u64));
+        assert!(check_balance(balance(87), &stx, cycles_price, &pending).is_err());
+        assert!(check_balance(balance(88), &stx, cycles_price, &pending).is_ok());
+    }
 }
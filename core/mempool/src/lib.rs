@@ -8,24 +8,36 @@ mod tests;
 mod tx_cache;
 
 pub use adapter::message::{
-    MsgPushTxs, NewTxsHandler, PullTxsHandler, END_GOSSIP_NEW_TXS, END_RESP_PULL_TXS,
+    MsgPushTxs, NewTxHashesHandler, PullTxsHandler, END_GOSSIP_NEW_TX_HASHES, END_RESP_PULL_TXS,
     END_RPC_PULL_TXS,
 };
 pub use adapter::DefaultMemPoolAdapter;
 pub use adapter::{DEFAULT_BROADCAST_TXS_INTERVAL, DEFAULT_BROADCAST_TXS_SIZE};
 
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 
 use async_trait::async_trait;
 use derive_more::{Display, From};
+use futures::future::join_all;
+use log::{debug, warn};
 
-use protocol::traits::{Context, MemPool, MemPoolAdapter, MixedTxHashes};
-use protocol::types::{Hash, SignedTransaction};
+use protocol::traits::{Context, MemPool, MemPoolAdapter, MemPoolStats, MixedTxHashes, TxStatus};
+use protocol::types::{AssetID, Hash, SignedTransaction, UserAddress};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::context::TxContext;
 use crate::map::Map;
-use crate::tx_cache::TxCache;
+use crate::tx_cache::{InsertLimits, TxCache};
+
+/// The default percentage of the cycle limit reserved for the earliest
+/// transactions whatever their fees are.
+pub const DEFAULT_RESERVED_RATIO: u64 = 10;
+/// The default percentage by which a replacement transaction must raise the
+/// fee of the pending one. The replacement is best-effort, not a cancel.
+pub const DEFAULT_REPLACE_RATIO: u64 = 10;
+/// The default limit of pending transactions of a sender.
+pub const DEFAULT_SENDER_LIMIT: usize = 2000;
 
 /// Memory pool for caching transactions.
 pub struct HashMemPool<Adapter: MemPoolAdapter> {
@@ -33,6 +45,15 @@ pub struct HashMemPool<Adapter: MemPoolAdapter> {
     pool_size: usize,
     /// A system param limits the life time of an off-chain transaction.
     timeout_gap: u64,
+    /// The percentage of the cycle limit reserved for low fee transactions
+    /// while packaging.
+    reserved_ratio: u64,
+    /// The percentage by which a transaction must raise the fee to replace a
+    /// pending one of the same sender and nonce.
+    replace_ratio: u64,
+    /// The limit of pending transactions of a sender, so that one can't fill
+    /// the pool.
+    sender_limit: usize,
     /// A structure for caching new transactions and responsible transactions of
     /// propose-sync.
     tx_cache: TxCache,
@@ -46,16 +67,183 @@ impl<Adapter> HashMemPool<Adapter>
 where
     Adapter: MemPoolAdapter,
 {
-    pub fn new(pool_size: usize, timeout_gap: u64, adapter: Adapter) -> Self {
+    pub fn new(
+        pool_size: usize,
+        timeout_gap: u64,
+        reserved_ratio: u64,
+        replace_ratio: u64,
+        sender_limit: usize,
+        adapter: Adapter,
+    ) -> Self {
         HashMemPool {
             pool_size,
             timeout_gap,
+            reserved_ratio,
+            replace_ratio,
+            sender_limit,
             tx_cache: TxCache::new(pool_size * 2),
             callback_cache: Map::new(pool_size),
             adapter,
         }
     }
 
+    /// Reload the transactions journaled before restart, the committed and the
+    /// timeout ones are dropped.
+    pub async fn load_journal(&self, ctx: Context) -> ProtocolResult<()> {
+        let txs = self.adapter.load_journal_txs(ctx.clone()).await?;
+        let current_epoch_id = self.adapter.get_latest_epoch_id(ctx.clone()).await?;
+        let timeout = current_epoch_id + self.timeout_gap;
+
+        let mut dropped = Vec::new();
+        for tx in txs.into_iter() {
+            let tx_hash = tx.tx_hash.clone();
+            let is_timeout = tx.raw.timeout <= current_epoch_id || tx.raw.timeout > timeout;
+
+            if is_timeout
+                || self
+                    .adapter
+                    .check_storage_exist(ctx.clone(), tx_hash.clone())
+                    .await
+                    .is_err()
+                || self.tx_cache.insert_new_tx(tx).is_err()
+            {
+                dropped.push(tx_hash);
+            }
+        }
+
+        if !dropped.is_empty() {
+            self.adapter.remove_journal_txs(ctx, dropped).await?;
+        }
+        Ok(())
+    }
+
+    // Check a new transaction, it is checked against the replacement, the
+    // sender limit and the pool size again on insertion.
+    async fn check_tx(
+        &self,
+        ctx: Context,
+        tx: &SignedTransaction,
+        sig_verified: bool,
+    ) -> ProtocolResult<()> {
+        let tx_hash = &tx.tx_hash;
+
+        self.tx_cache.check_exist(tx_hash)?;
+        // A replacement takes the place of the pending transaction, otherwise a
+        // transaction of lower fee is evicted if the pool is full.
+        let replaced = self.tx_cache.check_replace(tx, self.replace_ratio)?;
+        if replaced.is_none() {
+            self.tx_cache.check_sender_limit(tx, self.sender_limit)?;
+            self.tx_cache.check_evict(tx, self.pool_size)?;
+        }
+        if !sig_verified {
+            self.adapter
+                .check_signature(ctx.clone(), tx.clone())
+                .await?;
+        }
+        let pending = self.tx_cache.pending_spend(tx, replaced.as_ref());
+        self.adapter
+            .check_transaction(ctx.clone(), tx.clone(), pending)
+            .await?;
+        self.adapter
+            .check_storage_exist(ctx.clone(), tx_hash.clone())
+            .await?;
+        Ok(())
+    }
+
+    // Check and insert the transactions. The ones of a sender are checked and
+    // inserted one after another, so that each sees the previous ones. The
+    // inserted ones are journaled in one write. Return the result of each
+    // transaction.
+    async fn insert_txs(
+        &self,
+        ctx: Context,
+        txs: Vec<SignedTransaction>,
+        sig_verified: bool,
+    ) -> ProtocolResult<Vec<ProtocolResult<()>>> {
+        let txs_len = txs.len();
+        let mut groups: Vec<Vec<(usize, SignedTransaction)>> = Vec::new();
+        let mut senders = HashMap::new();
+        for (i, tx) in txs.into_iter().enumerate() {
+            let group = *senders.entry(tx.pubkey.clone()).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push((i, tx));
+        }
+
+        let mut inserted = join_all(
+            groups
+                .into_iter()
+                .map(|group| self.insert_sender_txs(ctx.clone(), group, sig_verified)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        inserted.sort_by_key(|(i, ..)| *i);
+
+        let mut valid = Vec::new();
+        let mut removed = Vec::new();
+        let mut results = Vec::with_capacity(txs_len);
+        for (_, tx, result) in inserted.into_iter() {
+            match result {
+                Ok(replaced_or_evicted) => {
+                    removed.extend(replaced_or_evicted);
+                    valid.push((results.len(), tx));
+                    results.push(Ok(()));
+                }
+                Err(err) => results.push(Err(err)),
+            }
+        }
+
+        if !valid.is_empty() {
+            let journaled = valid.iter().map(|(_, tx)| tx.clone()).collect();
+            self.adapter.journal_txs(ctx.clone(), journaled).await?;
+        }
+        if !removed.is_empty() {
+            self.adapter
+                .remove_journal_txs(ctx.clone(), removed)
+                .await?;
+        }
+
+        // Transactions from the network are relayed, except to the announcers
+        for (i, tx) in valid.into_iter() {
+            if self.tx_cache.contain(&tx.tx_hash) {
+                results[i] = self.adapter.broadcast_tx(ctx.clone(), tx).await;
+            }
+        }
+        Ok(results)
+    }
+
+    // Check and insert the transactions of a sender in order. Return the
+    // result of each with its position in the batch.
+    async fn insert_sender_txs(
+        &self,
+        ctx: Context,
+        txs: Vec<(usize, SignedTransaction)>,
+        sig_verified: bool,
+    ) -> Vec<(usize, SignedTransaction, ProtocolResult<Option<Hash>>)> {
+        let mut inserted = Vec::with_capacity(txs.len());
+        for (i, tx) in txs.into_iter() {
+            let result = match self.check_tx(ctx.clone(), &tx, sig_verified).await {
+                Ok(()) => self
+                    .tx_cache
+                    .insert_evict_tx(tx.clone(), self.insert_limits()),
+                Err(err) => Err(err),
+            };
+            inserted.push((i, tx, result));
+        }
+        inserted
+    }
+
+    fn insert_limits(&self) -> InsertLimits {
+        InsertLimits {
+            pool_size:     self.pool_size,
+            sender_limit:  self.sender_limit,
+            replace_ratio: self.replace_ratio,
+        }
+    }
+
     pub fn get_tx_cache(&self) -> &TxCache {
         &self.tx_cache
     }
@@ -75,42 +263,106 @@ where
     Adapter: MemPoolAdapter,
 {
     async fn insert(&self, ctx: Context, tx: SignedTransaction) -> ProtocolResult<()> {
-        let tx_hash = &tx.tx_hash;
-
-        self.tx_cache.check_reach_limit(self.pool_size)?;
-        self.tx_cache.check_exist(tx_hash)?;
-        self.adapter
-            .check_signature(ctx.clone(), tx.clone())
-            .await?;
-        self.adapter
-            .check_transaction(ctx.clone(), tx.clone())
-            .await?;
-        self.adapter
-            .check_storage_exist(ctx.clone(), tx_hash.clone())
-            .await?;
-        self.tx_cache.insert_new_tx(tx.clone())?;
+        let results = self.insert_txs(ctx, vec![tx], false).await?;
+        results.into_iter().collect::<ProtocolResult<Vec<_>>>()?;
+        Ok(())
+    }
 
-        if !ctx.is_network_origin_txs() {
-            self.adapter.broadcast_tx(ctx, tx).await?;
+    async fn insert_batch(&self, ctx: Context, txs: Vec<SignedTransaction>) -> ProtocolResult<()> {
+        // Skip the known transactions before the verification
+        let txs = txs
+            .into_iter()
+            .filter(|tx| !self.tx_cache.contain(&tx.tx_hash))
+            .collect::<Vec<_>>();
+        let txs_len = txs.len();
+
+        let verified = self.adapter.check_signatures(ctx.clone(), txs).await?;
+        let verified_len = verified.len();
+        if verified_len < txs_len {
+            warn!(
+                "mempool: drop {} of {} txs with invalid signatures",
+                txs_len - verified_len,
+                txs_len
+            );
         }
 
+        let results = self.insert_txs(ctx.clone(), verified, true).await?;
+        // A batch from the network may contain transactions already known or
+        // rejected, they don't fail the others.
+        if ctx.is_network_origin_txs() {
+            for err in results.into_iter().filter_map(Result::err) {
+                debug!("mempool: drop tx of batch {}", err);
+            }
+            return Ok(());
+        }
+        results.into_iter().collect::<ProtocolResult<Vec<_>>>()?;
         Ok(())
     }
 
+    async fn receive_tx_hashes(&self, ctx: Context, tx_hashes: Vec<Hash>) -> ProtocolResult<()> {
+        let unknown = tx_hashes
+            .into_iter()
+            .filter(|tx_hash| {
+                !self.tx_cache.contain(tx_hash) && !self.callback_cache.contains_key(tx_hash)
+            })
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+
+        // Only the first announcer of a hash is asked for the transaction
+        let unknown = self.adapter.record_announcers(ctx.clone(), unknown).await?;
+        if unknown.is_empty() {
+            return Ok(());
+        }
+
+        // The hashes an announcer fails to serve are pulled from the next
+        // announcer, until no announcer is left.
+        let mut txs = Vec::new();
+        let mut pulls = vec![(ctx.clone(), unknown)];
+        while let Some((pull_ctx, tx_hashes)) = pulls.pop() {
+            let mut missed = tx_hashes.iter().cloned().collect::<HashSet<_>>();
+            match self.adapter.pull_txs(pull_ctx.clone(), tx_hashes).await {
+                // Ignore the transactions not asked for
+                Ok(pulled) => {
+                    txs.extend(pulled.into_iter().filter(|tx| missed.remove(&tx.tx_hash)))
+                }
+                Err(err) => debug!("mempool: pull txs failed {:?}", err),
+            }
+
+            if !missed.is_empty() {
+                let next = self
+                    .adapter
+                    .forget_announcers(pull_ctx, missed.into_iter().collect())
+                    .await?;
+                pulls.extend(next);
+            }
+        }
+
+        self.insert_batch(ctx, txs).await
+    }
+
     async fn package(&self, ctx: Context, cycle_limit: u64) -> ProtocolResult<MixedTxHashes> {
         let current_epoch_id = self.adapter.get_latest_epoch_id(ctx.clone()).await?;
 
-        self.tx_cache.package(
+        let mixed_tx_hashes = self.tx_cache.package(
             cycle_limit,
             current_epoch_id,
             current_epoch_id + self.timeout_gap,
-        )
+            self.reserved_ratio,
+        )?;
+
+        let expired = self.tx_cache.take_expired();
+        if !expired.is_empty() {
+            self.adapter.remove_journal_txs(ctx, expired).await?;
+        }
+        Ok(mixed_tx_hashes)
     }
 
-    async fn flush(&self, _ctx: Context, tx_hashes: Vec<Hash>) -> ProtocolResult<()> {
+    async fn flush(&self, ctx: Context, tx_hashes: Vec<Hash>) -> ProtocolResult<()> {
         self.tx_cache.flush(&tx_hashes);
         self.callback_cache.clear();
-        Ok(())
+        self.adapter.remove_journal_txs(ctx, tx_hashes).await
     }
 
     async fn get_full_txs(
@@ -145,6 +397,7 @@ where
         ctx: Context,
         order_tx_hashes: Vec<Hash>,
     ) -> ProtocolResult<()> {
+        self.tx_cache.set_packaged(&order_tx_hashes);
         let unknown_hashes = self.tx_cache.show_unknown(order_tx_hashes);
         if !unknown_hashes.is_empty() {
             let unknown_len = unknown_hashes.len();
@@ -181,6 +434,39 @@ where
         }
         Ok(())
     }
+
+    async fn get_tx_status(&self, _ctx: Context, tx_hash: Hash) -> ProtocolResult<TxStatus> {
+        let status = self.tx_cache.get_status(&tx_hash);
+        if status == TxStatus::Unknown && self.callback_cache.contains_key(&tx_hash) {
+            return Ok(TxStatus::Ordered);
+        }
+        Ok(status)
+    }
+
+    async fn get_pending_txs(
+        &self,
+        _ctx: Context,
+        sender: UserAddress,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        let txs = self.tx_cache.filter_txs(|tx| {
+            UserAddress::from_pubkey_bytes(tx.pubkey.clone())
+                .map(|address| address == sender)
+                .unwrap_or(false)
+        });
+        Ok(txs)
+    }
+
+    async fn get_stats(&self, _ctx: Context) -> ProtocolResult<MemPoolStats> {
+        let (incumbent_queue_len, candidate_queue_len) = self.tx_cache.queue_lens();
+
+        Ok(MemPoolStats {
+            pool_size: self.tx_cache.len(),
+            callback_size: self.callback_cache.len(),
+            incumbent_queue_len,
+            candidate_queue_len,
+            oldest_timeout: self.tx_cache.oldest_timeout(),
+        })
+    }
 }
 
 #[derive(Debug, Display, From)]
@@ -220,6 +506,40 @@ pub enum MemPoolError {
 
     #[display(fmt = "Tx: {:?} invalid timeout", tx_hash)]
     InvalidTimeout { tx_hash: Hash },
+
+    #[display(
+        fmt = "Tx: {:?} replaces {:?}, fee cycle is lower than {}",
+        tx_hash,
+        replaced,
+        min_fee_rate
+    )]
+    Underpriced {
+        tx_hash:      Hash,
+        replaced:     Hash,
+        min_fee_rate: u64,
+    },
+
+    #[display(fmt = "Tx: {:?} can't replace {:?} packaged", tx_hash, replaced)]
+    ReplacePackaged { tx_hash: Hash, replaced: Hash },
+
+    #[display(fmt = "Tx: {:?} sender reaches limit: {}", tx_hash, sender_limit)]
+    SenderReachLimit {
+        tx_hash:      Hash,
+        sender_limit: usize,
+    },
+
+    #[display(
+        fmt = "Tx: {:?} fee cycle exceeds cycles limit: {}",
+        tx_hash,
+        cycles_limit
+    )]
+    ExceedCyclesLimit {
+        tx_hash:      Hash,
+        cycles_limit: u64,
+    },
+
+    #[display(fmt = "Tx: {:?} sender can't afford asset: {:?}", tx_hash, asset_id)]
+    InsufficientBalance { tx_hash: Hash, asset_id: AssetID },
 }
 
 impl Error for MemPoolError {}
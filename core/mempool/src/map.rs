@@ -62,6 +62,14 @@ where
         self.buckets[index].remove(tx_hash);
     }
 
+    pub fn values(&self) -> Vec<V> {
+        let mut values = Vec::new();
+        for bucket in self.buckets.iter() {
+            values.extend(bucket.values());
+        }
+        values
+    }
+
     pub fn len(&self) -> usize {
         let mut len = 0;
         for bucket in self.buckets.iter() {
@@ -123,6 +131,10 @@ where
         store.remove(tx_hash);
     }
 
+    fn values(&self) -> Vec<V> {
+        self.store.read().values().cloned().collect()
+    }
+
     fn len(&self) -> usize {
         self.store.read().len()
     }
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use test::Bencher;
 
+use protocol::traits::{MemPoolStats, TxStatus};
 use protocol::types::Hash;
 
 use super::*;
@@ -180,6 +181,277 @@ fn test_sync_propose_txs() {
     assert_eq!(mempool.get_tx_cache().len(), 50);
 }
 
+#[test]
+fn test_replace_by_fee() {
+    let mempool = Arc::new(default_mempool());
+    let mut rng = OsRng::new().expect("OsRng");
+    let (priv_key, pub_key) = Secp256k1::generate_keypair(&mut rng);
+    let address = pub_key_to_address(&pub_key).unwrap();
+    let tx = mock_signed_tx(&priv_key, &pub_key, &address, TIMEOUT, true);
+    let pending = mock_replace_tx(&priv_key, &tx, 20);
+    let insert =
+        |tx: &SignedTransaction| executor::block_on(mempool.insert(Context::new(), tx.clone()));
+
+    insert(&pending).unwrap();
+
+    // The fee must be higher by REPLACE_RATIO percent.
+    let underpriced = mock_replace_tx(&priv_key, &tx, 21);
+    assert!(insert(&underpriced).is_err());
+    assert!(mempool.get_tx_cache().contain(&pending.tx_hash));
+
+    let replacement = mock_replace_tx(&priv_key, &tx, 22);
+    insert(&replacement).unwrap();
+    assert_eq!(mempool.get_tx_cache().len(), 1);
+    assert!(!mempool.get_tx_cache().contain(&pending.tx_hash));
+    assert!(mempool
+        .get_adapter()
+        .network_txs
+        .contains_key(&replacement.tx_hash));
+
+    let mixed_tx_hashes = exec_package(Arc::clone(&mempool), CYCLE_LIMIT);
+    assert_eq!(mixed_tx_hashes.order_tx_hashes, vec![replacement
+        .tx_hash
+        .clone()]);
+
+    // A packaged transaction can't be replaced.
+    assert!(insert(&mock_replace_tx(&priv_key, &tx, 100)).is_err());
+    assert!(mempool.get_tx_cache().contain(&replacement.tx_hash));
+}
+
+#[test]
+fn test_evict() {
+    let mempool = Arc::new(new_mempool(2, TIMEOUT_GAP));
+    let mut rng = OsRng::new().expect("OsRng");
+    let (priv_key, pub_key) = Secp256k1::generate_keypair(&mut rng);
+    let address = pub_key_to_address(&pub_key).unwrap();
+    let mock_tx = |cycle: u64| {
+        let tx = mock_signed_tx(&priv_key, &pub_key, &address, TIMEOUT, true);
+        mock_replace_tx(&priv_key, &tx, cycle)
+    };
+    let insert =
+        |tx: &SignedTransaction| executor::block_on(mempool.insert(Context::new(), tx.clone()));
+
+    let (oldest, older) = (mock_tx(2), mock_tx(2));
+    insert(&oldest).unwrap();
+    insert(&older).unwrap();
+
+    // A transaction can't evict the ones of the same fee.
+    assert!(insert(&mock_tx(2)).is_err());
+
+    // The oldest one of the lowest fee is evicted.
+    let better = mock_tx(3);
+    insert(&better).unwrap();
+    assert_eq!(mempool.get_tx_cache().len(), 2);
+    assert!(!mempool.get_tx_cache().contain(&oldest.tx_hash));
+    assert!(mempool.get_tx_cache().contain(&older.tx_hash));
+
+    let mixed_tx_hashes = exec_package(Arc::clone(&mempool), CYCLE_LIMIT);
+    assert_eq!(mixed_tx_hashes.order_tx_hashes, vec![
+        older.tx_hash,
+        better.tx_hash
+    ]);
+}
+
+#[test]
+fn test_sender_limit() {
+    let adapter = HashMemPoolAdapter::new();
+    let mempool = Arc::new(HashMemPool::new(
+        POOL_SIZE,
+        TIMEOUT_GAP,
+        RESERVED_RATIO,
+        REPLACE_RATIO,
+        1,
+        adapter,
+    ));
+    let insert =
+        |tx: &SignedTransaction| executor::block_on(mempool.insert(Context::new(), tx.clone()));
+
+    let txs = default_mock_txs(2);
+    insert(&txs[0]).unwrap();
+    assert!(insert(&txs[1]).is_err());
+    // Other senders are not limited.
+    insert(&default_mock_txs(1)[0]).unwrap();
+    assert_eq!(mempool.get_tx_cache().len(), 2);
+
+    // The transactions of a sender can be inserted again after flush.
+    exec_flush(vec![txs[0].tx_hash.clone()], Arc::clone(&mempool));
+    insert(&txs[1]).unwrap();
+}
+
+#[test]
+fn test_sender_limit_batch() {
+    let sender_limit = 3;
+    let adapter = HashMemPoolAdapter::new();
+    let mempool = Arc::new(HashMemPool::new(
+        POOL_SIZE,
+        TIMEOUT_GAP,
+        RESERVED_RATIO,
+        REPLACE_RATIO,
+        sender_limit,
+        adapter,
+    ));
+
+    // The transactions of a sender in one batch are limited as well.
+    let txs = default_mock_txs(sender_limit + 1);
+    // The rejected one doesn't fail a batch from the network.
+    assert!(exec_insert_batch(txs, Arc::clone(&mempool)).is_ok());
+    assert_eq!(mempool.get_tx_cache().len(), sender_limit);
+    assert_eq!(mempool.get_adapter().journal.lock().len(), sender_limit);
+}
+
+#[test]
+fn test_replace_batch() {
+    let mempool = Arc::new(default_mempool());
+    let mut rng = OsRng::new().expect("OsRng");
+    let (priv_key, pub_key) = Secp256k1::generate_keypair(&mut rng);
+    let address = pub_key_to_address(&pub_key).unwrap();
+    let tx = mock_signed_tx(&priv_key, &pub_key, &address, TIMEOUT, true);
+    let pending = mock_replace_tx(&priv_key, &tx, 20);
+    executor::block_on(mempool.insert(Context::new(), pending.clone())).unwrap();
+
+    // The second replacement is checked against the first one.
+    let replacement = mock_replace_tx(&priv_key, &tx, 22);
+    let underpriced = mock_replace_tx(&priv_key, &tx, 23);
+    assert!(exec_insert_batch(
+        vec![replacement.clone(), underpriced.clone()],
+        Arc::clone(&mempool),
+    )
+    .is_ok());
+
+    let tx_cache = mempool.get_tx_cache();
+    assert_eq!(tx_cache.len(), 1);
+    assert!(tx_cache.contain(&replacement.tx_hash));
+    let journal = mempool.get_adapter().journal.lock();
+    assert_eq!(journal.len(), 1);
+    assert!(journal.contains_key(&replacement.tx_hash));
+}
+
+#[test]
+fn test_load_journal() {
+    let mempool = Arc::new(default_mempool());
+    let txs = default_mock_txs(10);
+    concurrent_insert(txs.clone(), Arc::clone(&mempool));
+
+    let journal = Arc::clone(&mempool.get_adapter().journal);
+    assert_eq!(journal.lock().len(), 10);
+
+    let committed: Vec<Hash> = txs[..3].iter().map(|tx| tx.tx_hash.clone()).collect();
+    exec_flush(committed, Arc::clone(&mempool));
+    assert_eq!(journal.lock().len(), 7);
+
+    // The transaction timed out while the node was down.
+    let expired = mock_txs(1, 0, CURRENT_EPOCH_ID).remove(0);
+    journal
+        .lock()
+        .insert(expired.tx_hash.clone(), expired.clone());
+
+    let mut adapter = HashMemPoolAdapter::new();
+    adapter.journal = Arc::clone(&journal);
+    let reloaded = HashMemPool::new(
+        POOL_SIZE,
+        TIMEOUT_GAP,
+        RESERVED_RATIO,
+        REPLACE_RATIO,
+        SENDER_LIMIT,
+        adapter,
+    );
+    executor::block_on(reloaded.load_journal(Context::new())).unwrap();
+
+    assert_eq!(reloaded.get_tx_cache().len(), 7);
+    assert!(txs[3..]
+        .iter()
+        .all(|tx| reloaded.get_tx_cache().contain(&tx.tx_hash)));
+    assert!(!journal.lock().contains_key(&expired.tx_hash));
+}
+
+#[test]
+fn test_inspection() {
+    let mempool = Arc::new(default_mempool());
+    let txs = default_mock_txs(5);
+    txs.iter()
+        .for_each(|tx| exec_insert(tx, Arc::clone(&mempool)));
+
+    let others = default_mock_txs(2);
+    concurrent_broadcast(others.clone(), Arc::clone(&mempool));
+    exec_sync_propose_txs(vec![others[0].tx_hash.clone()], Arc::clone(&mempool));
+    exec_ensure_order_txs(vec![others[1].tx_hash.clone()], Arc::clone(&mempool));
+
+    let status = |tx_hash: &Hash| {
+        executor::block_on(mempool.get_tx_status(Context::new(), tx_hash.clone())).unwrap()
+    };
+    assert_eq!(status(&txs[0].tx_hash), TxStatus::Pending);
+    assert_eq!(status(&others[0].tx_hash), TxStatus::Proposed);
+    assert_eq!(status(&others[1].tx_hash), TxStatus::Ordered);
+    assert_eq!(
+        status(&Hash::digest(Bytes::from(get_random_bytes(10)))),
+        TxStatus::Unknown
+    );
+
+    let sender = Address::from_pubkey_bytes(txs[0].pubkey.clone()).unwrap();
+    let pending = executor::block_on(mempool.get_pending_txs(Context::new(), sender)).unwrap();
+    assert_eq!(pending, txs);
+
+    let stats = executor::block_on(mempool.get_stats(Context::new())).unwrap();
+    assert_eq!(stats, MemPoolStats {
+        pool_size:           6,
+        callback_size:       1,
+        incumbent_queue_len: 6,
+        candidate_queue_len: 0,
+        oldest_timeout:      Some(TIMEOUT),
+    });
+}
+
+#[test]
+fn test_insert_batch() {
+    let mempool = Arc::new(default_mempool());
+    let txs = mock_txs(80, 10, TIMEOUT);
+
+    // The invalid signatures are dropped, and the valid ones are inserted.
+    assert!(exec_insert_batch(txs.clone(), Arc::clone(&mempool)).is_ok());
+    assert_eq!(mempool.get_tx_cache().len(), 80);
+
+    // The batch is journaled in one write.
+    let adapter = mempool.get_adapter();
+    assert_eq!(adapter.journal_writes.load(Ordering::SeqCst), 1);
+    assert_eq!(adapter.journal.lock().len(), 80);
+
+    // The known transactions are skipped.
+    assert!(exec_insert_batch(txs[..80].to_vec(), Arc::clone(&mempool)).is_ok());
+    assert_eq!(mempool.get_tx_cache().len(), 80);
+
+    // Transactions from the network are relayed.
+    let tx_hashes = txs.iter().map(|tx| tx.tx_hash.clone()).collect::<Vec<_>>();
+    let pulled = executor::block_on(mempool.get_adapter().pull_txs(Context::new(), tx_hashes));
+    assert_eq!(pulled.unwrap().len(), 80);
+}
+
+#[test]
+fn test_receive_tx_hashes() {
+    let mempool = Arc::new(default_mempool());
+    let txs = default_mock_txs(10);
+    let tx_hashes = txs.iter().map(|tx| tx.tx_hash.clone()).collect::<Vec<_>>();
+
+    // The announcer misses two of the transactions.
+    concurrent_broadcast(txs[..8].to_vec(), Arc::clone(&mempool));
+    exec_receive_tx_hashes(tx_hashes.clone(), Arc::clone(&mempool)).unwrap();
+    assert_eq!(mempool.get_tx_cache().len(), 8);
+
+    // The missed ones are pulled when they are announced again.
+    concurrent_broadcast(txs[8..].to_vec(), Arc::clone(&mempool));
+    exec_receive_tx_hashes(tx_hashes.clone(), Arc::clone(&mempool)).unwrap();
+    assert_eq!(mempool.get_tx_cache().len(), 10);
+
+    // The next announcer is asked after the first one fails.
+    let mempool = Arc::new(default_mempool());
+    concurrent_broadcast(txs, Arc::clone(&mempool));
+    mempool
+        .get_adapter()
+        .pull_failures
+        .store(1, Ordering::SeqCst);
+    exec_receive_tx_hashes(tx_hashes, Arc::clone(&mempool)).unwrap();
+    assert_eq!(mempool.get_tx_cache().len(), 10);
+}
+
 #[bench]
 fn bench_insert(b: &mut Bencher) {
     let mempool = &Arc::new(default_mempool());
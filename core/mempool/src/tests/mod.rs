@@ -2,7 +2,9 @@ extern crate test;
 
 mod mempool;
 
+use std::collections::{HashMap, HashSet};
 use std::convert::{From, TryFrom};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -10,6 +12,7 @@ use bytes::Bytes;
 use chashmap::CHashMap;
 use futures::executor;
 use num_traits::FromPrimitive;
+use parking_lot::Mutex;
 use rand::random;
 use rand::rngs::OsRng;
 use rayon::iter::IntoParallelRefIterator;
@@ -20,31 +23,46 @@ use common_crypto::{
     Secp256k1Signature, Signature,
 };
 use protocol::codec::ProtocolCodec;
-use protocol::traits::{Context, MemPool, MemPoolAdapter, MixedTxHashes};
+use protocol::traits::{Context, MemPool, MemPoolAdapter, MixedTxHashes, PendingSpend};
 use protocol::types::{
     CarryingAsset, Fee, Hash, RawTransaction, SignedTransaction, TransactionAction,
     UserAddress as Address,
 };
 use protocol::ProtocolResult;
 
+use crate::context::TxContext;
 use crate::{HashMemPool, MemPoolError};
 
 const AMOUNT: i32 = 42;
 const CYCLE_LIMIT: u64 = 10_000;
 const CURRENT_EPOCH_ID: u64 = 999;
 const POOL_SIZE: usize = 100_000;
+const RESERVED_RATIO: u64 = 10;
+const REPLACE_RATIO: u64 = 10;
+const SENDER_LIMIT: usize = POOL_SIZE;
 const TIMEOUT: u64 = 1000;
 const TIMEOUT_GAP: u64 = 100;
 const TX_CYCLE: u64 = 1;
 
 pub struct HashMemPoolAdapter {
-    network_txs: CHashMap<Hash, SignedTransaction>,
+    network_txs:    CHashMap<Hash, SignedTransaction>,
+    journal:        Arc<Mutex<HashMap<Hash, SignedTransaction>>>,
+    journal_writes: AtomicUsize,
+    announced:      Mutex<HashSet<Hash>>,
+    // Every hash has a second announcer, which is asked after the first fails
+    retried:       Mutex<HashSet<Hash>>,
+    pull_failures: AtomicUsize,
 }
 
 impl HashMemPoolAdapter {
     fn new() -> HashMemPoolAdapter {
         HashMemPoolAdapter {
-            network_txs: CHashMap::new(),
+            network_txs:    CHashMap::new(),
+            journal:        Arc::new(Mutex::new(HashMap::new())),
+            journal_writes: AtomicUsize::new(0),
+            announced:      Mutex::new(HashSet::new()),
+            retried:        Mutex::new(HashSet::new()),
+            pull_failures:  AtomicUsize::new(0),
         }
     }
 }
@@ -56,6 +74,15 @@ impl MemPoolAdapter for HashMemPoolAdapter {
         _ctx: Context,
         tx_hashes: Vec<Hash>,
     ) -> ProtocolResult<Vec<SignedTransaction>> {
+        if self.pull_failures.load(Ordering::SeqCst) > 0 {
+            self.pull_failures.fetch_sub(1, Ordering::SeqCst);
+            return Err(MemPoolError::EnsureBreak {
+                require:  tx_hashes.len(),
+                response: 0,
+            }
+            .into());
+        }
+
         let mut vec = Vec::new();
         for hash in tx_hashes {
             if let Some(tx) = self.network_txs.get(&hash) {
@@ -70,12 +97,65 @@ impl MemPoolAdapter for HashMemPoolAdapter {
         Ok(())
     }
 
+    async fn record_announcers(
+        &self,
+        _ctx: Context,
+        tx_hashes: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Hash>> {
+        let mut announced = self.announced.lock();
+        Ok(tx_hashes
+            .into_iter()
+            .filter(|tx_hash| announced.insert(tx_hash.clone()))
+            .collect())
+    }
+
+    async fn forget_announcers(
+        &self,
+        ctx: Context,
+        tx_hashes: Vec<Hash>,
+    ) -> ProtocolResult<Vec<(Context, Vec<Hash>)>> {
+        let mut announced = self.announced.lock();
+        let mut retried = self.retried.lock();
+        let mut next = Vec::new();
+        for tx_hash in tx_hashes.into_iter() {
+            announced.remove(&tx_hash);
+            if retried.insert(tx_hash.clone()) {
+                next.push(tx_hash);
+            }
+        }
+
+        if next.is_empty() {
+            Ok(Vec::new())
+        } else {
+            Ok(vec![(ctx, next)])
+        }
+    }
+
     async fn check_signature(&self, _ctx: Context, tx: SignedTransaction) -> ProtocolResult<()> {
         check_hash(tx.clone()).await?;
         check_sig(&tx)
     }
 
-    async fn check_transaction(&self, _ctx: Context, _tx: SignedTransaction) -> ProtocolResult<()> {
+    async fn check_signatures(
+        &self,
+        _ctx: Context,
+        txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        let verified = txs
+            .into_par_iter()
+            .filter(|tx| {
+                executor::block_on(check_hash(tx.clone())).is_ok() && check_sig(tx).is_ok()
+            })
+            .collect();
+        Ok(verified)
+    }
+
+    async fn check_transaction(
+        &self,
+        _ctx: Context,
+        _tx: SignedTransaction,
+        _pending: PendingSpend,
+    ) -> ProtocolResult<()> {
         Ok(())
     }
 
@@ -86,6 +166,27 @@ impl MemPoolAdapter for HashMemPoolAdapter {
     async fn get_latest_epoch_id(&self, _ctx: Context) -> ProtocolResult<u64> {
         Ok(CURRENT_EPOCH_ID)
     }
+
+    async fn journal_txs(&self, _ctx: Context, txs: Vec<SignedTransaction>) -> ProtocolResult<()> {
+        self.journal_writes.fetch_add(1, Ordering::SeqCst);
+        let mut journal = self.journal.lock();
+        for tx in txs.into_iter() {
+            journal.insert(tx.tx_hash.clone(), tx);
+        }
+        Ok(())
+    }
+
+    async fn remove_journal_txs(&self, _ctx: Context, tx_hashes: Vec<Hash>) -> ProtocolResult<()> {
+        let mut journal = self.journal.lock();
+        for tx_hash in tx_hashes.iter() {
+            journal.remove(tx_hash);
+        }
+        Ok(())
+    }
+
+    async fn load_journal_txs(&self, _ctx: Context) -> ProtocolResult<Vec<SignedTransaction>> {
+        Ok(self.journal.lock().values().cloned().collect())
+    }
 }
 
 pub fn default_mock_txs(size: usize) -> Vec<SignedTransaction> {
@@ -109,13 +210,20 @@ fn mock_txs(valid_size: usize, invalid_size: usize, timeout: u64) -> Vec<SignedT
     vec
 }
 
-fn default_mempool() -> HashMemPool<HashMemPoolAdapter> {
+pub fn default_mempool() -> HashMemPool<HashMemPoolAdapter> {
     new_mempool(POOL_SIZE, TIMEOUT_GAP)
 }
 
 fn new_mempool(pool_size: usize, timeout_gap: u64) -> HashMemPool<HashMemPoolAdapter> {
     let adapter = HashMemPoolAdapter::new();
-    HashMemPool::new(pool_size, timeout_gap, adapter)
+    HashMemPool::new(
+        pool_size,
+        timeout_gap,
+        RESERVED_RATIO,
+        REPLACE_RATIO,
+        SENDER_LIMIT,
+        adapter,
+    )
 }
 
 fn pub_key_to_address(pub_key: &Secp256k1PublicKey) -> ProtocolResult<Address> {
@@ -181,7 +289,27 @@ fn exec_insert(signed_tx: &SignedTransaction, mempool: Arc<HashMemPool<HashMemPo
     });
 }
 
-fn exec_flush(remove_hashes: Vec<Hash>, mempool: Arc<HashMemPool<HashMemPoolAdapter>>) {
+pub fn exec_insert_batch(
+    txs: Vec<SignedTransaction>,
+    mempool: Arc<HashMemPool<HashMemPoolAdapter>>,
+) -> ProtocolResult<()> {
+    executor::block_on(async {
+        let ctx = Context::new().mark_network_origin_new_txs();
+        mempool.insert_batch(ctx, txs).await
+    })
+}
+
+fn exec_receive_tx_hashes(
+    tx_hashes: Vec<Hash>,
+    mempool: Arc<HashMemPool<HashMemPoolAdapter>>,
+) -> ProtocolResult<()> {
+    executor::block_on(async {
+        let ctx = Context::new().mark_network_origin_new_txs();
+        mempool.receive_tx_hashes(ctx, tx_hashes).await
+    })
+}
+
+pub fn exec_flush(remove_hashes: Vec<Hash>, mempool: Arc<HashMemPool<HashMemPoolAdapter>>) {
     executor::block_on(async {
         mempool.flush(Context::new(), remove_hashes).await.unwrap();
     });
@@ -265,6 +393,27 @@ fn mock_signed_tx(
     }
 }
 
+// Sign the transaction again with another fee cycle.
+fn mock_replace_tx(
+    priv_key: &Secp256k1PrivateKey,
+    tx: &SignedTransaction,
+    cycle: u64,
+) -> SignedTransaction {
+    let mut raw = tx.raw.clone();
+    raw.fee.cycle = cycle;
+
+    let raw_bytes = executor::block_on(async { raw.encode().await.unwrap() });
+    let tx_hash = Hash::digest(raw_bytes);
+    let signature = Secp256k1::sign_message(&tx_hash.as_bytes(), &priv_key.to_bytes()).unwrap();
+
+    SignedTransaction {
+        raw,
+        tx_hash,
+        pubkey: tx.pubkey.clone(),
+        signature: signature.to_bytes(),
+    }
+}
+
 fn get_random_bytes(len: usize) -> Vec<u8> {
     (0..len).map(|_| random::<u8>()).collect()
 }
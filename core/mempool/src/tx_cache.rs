@@ -1,10 +1,14 @@
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 
+use bytes::Bytes;
 use crossbeam_queue::ArrayQueue;
+use parking_lot::Mutex;
 
-use protocol::traits::MixedTxHashes;
-use protocol::types::{Hash, SignedTransaction};
+use protocol::traits::{MixedTxHashes, PendingSpend, TxStatus};
+use protocol::types::{CarryingAsset, Hash, SignedTransaction, TransactionAction};
 use protocol::ProtocolResult;
 
 use crate::map::Map;
@@ -17,7 +21,8 @@ use crate::MemPoolError;
 /// `proposed` true. When shared transaction in `TxCache` removed from map,
 /// it will set `removed` true. The `removed` and `proposed` marks will remind
 /// queue in `TxCache` to appropriately process elements while packaging
-/// transaction hashes for consensus.
+/// transaction hashes for consensus. A transaction ordered in a proposal is
+/// marked `packaged`, it can't be replaced until the next package or flush.
 pub struct TxWrapper {
     /// Content.
     tx: SignedTransaction,
@@ -30,23 +35,32 @@ pub struct TxWrapper {
     /// While collecting propose_tx_hashes during package,
     /// it will skips transactions which marks 'proposed` true.
     proposed: AtomicBool,
+    /// The transactions in the order transaction hashes of a proposal,
+    /// packaged by this node or ensured from others, are marked `packaged`.
+    packaged: AtomicBool,
+    /// The insertion sequence in `TxCache`, the smaller the older.
+    seq: u64,
 }
 
 impl TxWrapper {
     #[allow(dead_code)]
-    pub(crate) fn new(tx: SignedTransaction) -> Self {
+    pub(crate) fn new(tx: SignedTransaction, seq: u64) -> Self {
         TxWrapper {
             tx,
             removed: AtomicBool::new(false),
             proposed: AtomicBool::new(false),
+            packaged: AtomicBool::new(false),
+            seq,
         }
     }
 
-    pub(crate) fn propose(tx: SignedTransaction) -> Self {
+    pub(crate) fn propose(tx: SignedTransaction, seq: u64) -> Self {
         TxWrapper {
             tx,
             removed: AtomicBool::new(false),
             proposed: AtomicBool::new(true),
+            packaged: AtomicBool::new(false),
+            seq,
         }
     }
 
@@ -64,6 +78,39 @@ impl TxWrapper {
         self.proposed.load(Ordering::SeqCst)
     }
 
+    fn set_packaged(&self) {
+        self.packaged.store(true, Ordering::SeqCst);
+    }
+
+    fn unset_packaged(&self) {
+        self.packaged.store(false, Ordering::SeqCst);
+    }
+
+    #[inline]
+    fn is_packaged(&self) -> bool {
+        self.packaged.load(Ordering::SeqCst)
+    }
+
+    /// The transactions of a sender are identified by the public key.
+    #[inline]
+    fn sender(&self) -> &Bytes {
+        &self.tx.pubkey
+    }
+
+    /// The price of cycles is the same for every transaction of an epoch, so
+    /// the fee cycles rank transactions as their fees do.
+    #[inline]
+    fn fee_rate(&self) -> u64 {
+        self.tx.raw.fee.cycle
+    }
+
+    /// Transactions are evicted by the lowest fee first, then the nearest to
+    /// timeout, then the oldest.
+    #[inline]
+    fn evict_key(&self) -> EvictKey {
+        (self.fee_rate(), self.tx.raw.timeout, self.seq)
+    }
+
     #[inline]
     fn is_timeout(&self, current_epoch_id: u64, timeout: u64) -> bool {
         let tx_timeout = self.tx.raw.timeout;
@@ -71,27 +118,216 @@ impl TxWrapper {
     }
 }
 
+/// The key of a transaction in `nonces`, a sender can't have two pending
+/// transactions of the same nonce.
+fn nonce_key(signed_tx: &SignedTransaction) -> Hash {
+    let nonce = signed_tx.raw.nonce.as_bytes();
+    let mut key = Vec::with_capacity(signed_tx.pubkey.len() + nonce.len());
+    key.extend_from_slice(&signed_tx.pubkey);
+    key.extend_from_slice(&nonce);
+    Hash::digest(Bytes::from(key))
+}
+
+/// The asset carried by a transaction besides the fee.
+pub(crate) fn carrying_asset(signed_tx: &SignedTransaction) -> Option<&CarryingAsset> {
+    match &signed_tx.raw.action {
+        TransactionAction::Transfer { carrying_asset, .. } => Some(carrying_asset),
+        TransactionAction::Call { carrying_asset, .. } => carrying_asset.as_ref(),
+        _ => None,
+    }
+}
+
+fn add_spend(spend: &mut PendingSpend, signed_tx: &SignedTransaction) {
+    let fee = &signed_tx.raw.fee;
+    let cycles = spend.fee_cycles.entry(fee.asset_id.clone()).or_insert(0);
+    *cycles = cycles.saturating_add(fee.cycle);
+
+    if let Some(carrying_asset) = carrying_asset(signed_tx) {
+        *spend
+            .carried
+            .entry(carrying_asset.asset_id.clone())
+            .or_default() += &carrying_asset.amount;
+    }
+}
+
+fn sub_spend(spend: &mut PendingSpend, signed_tx: &SignedTransaction) {
+    let fee = &signed_tx.raw.fee;
+    if let Some(cycles) = spend.fee_cycles.get_mut(&fee.asset_id) {
+        *cycles = cycles.saturating_sub(fee.cycle);
+        if *cycles == 0 {
+            spend.fee_cycles.remove(&fee.asset_id);
+        }
+    }
+
+    if let Some(carrying_asset) = carrying_asset(signed_tx) {
+        let asset_id = &carrying_asset.asset_id;
+        if let Some(amount) = spend.carried.get_mut(asset_id) {
+            if *amount > carrying_asset.amount {
+                *amount -= &carrying_asset.amount;
+            } else {
+                spend.carried.remove(asset_id);
+            }
+        }
+    }
+}
+
 /// Share `TxWrapper` for collections in `TxCache`.
 pub type SharedTx = Arc<TxWrapper>;
 
-/// An enum stands for package stage
-#[derive(PartialEq, Eq)]
-enum Stage {
-    /// Packing order_tx_hashes
-    OrderTxs,
-    /// Packing propose_tx_hashes
-    ProposeTxs,
-    /// Packing finished. Only insert transactions into temp queue.
-    Finished,
+type EvictKey = (u64, u64, u64);
+
+/// The limits a new transaction is checked against again when it is inserted,
+/// since other transactions may be inserted after it is checked.
+#[derive(Clone, Copy, Debug)]
+pub struct InsertLimits {
+    pub pool_size:     usize,
+    pub sender_limit:  usize,
+    pub replace_ratio: u64,
+}
+
+/// The number of pending transactions of a sender, and what they can spend.
+#[derive(Default)]
+struct SenderIndex {
+    count: usize,
+    spend: PendingSpend,
+}
+
+/// Pending transactions in eviction order, and the pending transactions of
+/// each sender. The packaged transactions are moved out of the eviction
+/// order, but still counted for their senders.
+#[derive(Default)]
+struct PoolIndex {
+    evict_order: BTreeMap<EvictKey, Hash>,
+    packaged:    HashSet<Hash>,
+    senders:     HashMap<Bytes, SenderIndex>,
+}
+
+impl PoolIndex {
+    fn add(&mut self, shared_tx: &SharedTx) {
+        self.evict_order
+            .insert(shared_tx.evict_key(), shared_tx.tx.tx_hash.clone());
+        let sender = self.senders.entry(shared_tx.sender().clone()).or_default();
+        sender.count += 1;
+        add_spend(&mut sender.spend, &shared_tx.tx);
+    }
+
+    fn set_packaged(&mut self, shared_tx: &SharedTx) {
+        if self.evict_order.remove(&shared_tx.evict_key()).is_some() {
+            self.packaged.insert(shared_tx.tx.tx_hash.clone());
+        }
+    }
+
+    fn unset_packaged(&mut self, shared_tx: &SharedTx) {
+        if self.packaged.remove(&shared_tx.tx.tx_hash) {
+            self.evict_order
+                .insert(shared_tx.evict_key(), shared_tx.tx.tx_hash.clone());
+        }
+    }
+
+    /// The transaction of the lowest fee which is not packaged.
+    fn evict_candidate(&self) -> Option<(u64, &Hash)> {
+        self.evict_order
+            .iter()
+            .next()
+            .map(|((fee_rate, _, _), tx_hash)| (*fee_rate, tx_hash))
+    }
+
+    fn remove(&mut self, shared_tx: &SharedTx) {
+        if self.evict_order.remove(&shared_tx.evict_key()).is_none()
+            && !self.packaged.remove(&shared_tx.tx.tx_hash)
+        {
+            return;
+        }
+        if let Some(sender) = self.senders.get_mut(shared_tx.sender()) {
+            sender.count -= 1;
+            sub_spend(&mut sender.spend, &shared_tx.tx);
+            if sender.count == 0 {
+                self.senders.remove(shared_tx.sender());
+            }
+        }
+    }
 }
 
-impl Stage {
-    fn next(&self) -> Self {
-        match self {
-            Stage::OrderTxs => Stage::ProposeTxs,
-            Stage::ProposeTxs => Stage::Finished,
-            Stage::Finished => panic!("There is no next stage after finished stage!"),
+/// Valid transactions of a package grouped by sender, each in insertion order.
+#[derive(Default)]
+struct SenderQueues {
+    /// The insertion sequence of the next transaction.
+    seq: usize,
+    indexes: HashMap<Bytes, usize>,
+    queues: Vec<VecDeque<(usize, SharedTx)>>,
+}
+
+impl SenderQueues {
+    fn push(&mut self, shared_tx: SharedTx) {
+        let queues = &mut self.queues;
+        let index = *self
+            .indexes
+            .entry(shared_tx.sender().clone())
+            .or_insert_with(|| {
+                queues.push(VecDeque::new());
+                queues.len() - 1
+            });
+
+        self.queues[index].push_back((self.seq, shared_tx));
+        self.seq += 1;
+    }
+
+    /// Pick transactions under the cycle limit, by fee rate or else by
+    /// insertion order. Only the first remaining transaction of each sender
+    /// can be picked, and a sender whose first one exceeds the limit is
+    /// skipped. Return the picked hashes and their cycles.
+    fn pick(&mut self, cycle_limit: u64, by_fee: bool, skip_proposed: bool) -> (Vec<Hash>, u64) {
+        let mut heads = BinaryHeap::new();
+        for index in 0..self.queues.len() {
+            if let Some(priority) = self.head_priority(index, by_fee, skip_proposed) {
+                heads.push((priority, index));
+            }
+        }
+
+        let mut tx_hashes = Vec::new();
+        let mut cycle_count: u64 = 0;
+        while let Some((_, index)) = heads.pop() {
+            let queue = &mut self.queues[index];
+            let cycle = match queue.front() {
+                Some((_, shared_tx)) => shared_tx.tx.raw.fee.cycle,
+                None => continue,
+            };
+            match cycle_count.checked_add(cycle) {
+                Some(count) if count <= cycle_limit => cycle_count = count,
+                _ => continue,
+            }
+            if let Some((_, shared_tx)) = queue.pop_front() {
+                tx_hashes.push(shared_tx.tx.tx_hash.clone());
+            }
+
+            if let Some(priority) = self.head_priority(index, by_fee, skip_proposed) {
+                heads.push((priority, index));
+            }
         }
+
+        (tx_hashes, cycle_count)
+    }
+
+    fn head_priority(
+        &mut self,
+        index: usize,
+        by_fee: bool,
+        skip_proposed: bool,
+    ) -> Option<(u64, Reverse<usize>)> {
+        let queue = &mut self.queues[index];
+        if skip_proposed {
+            while queue
+                .front()
+                .map_or(false, |(_, shared_tx)| shared_tx.is_proposed())
+            {
+                queue.pop_front();
+            }
+        }
+
+        queue.front().map(|(seq, shared_tx)| {
+            let fee_rate = if by_fee { shared_tx.fee_rate() } else { 0 };
+            (fee_rate, Reverse(*seq))
+        })
     }
 }
 
@@ -119,6 +355,15 @@ pub struct TxCache {
     queue_1: ArrayQueue<SharedTx>,
     /// A map for randomly search and removal.
     map: Map<SharedTx>,
+    /// A map from the nonce of a sender to the hash of the pending transaction,
+    /// for transaction replacement.
+    nonces: Map<Hash>,
+    /// An index for eviction and per-sender limit.
+    index: Mutex<PoolIndex>,
+    /// The insertion sequence of the next transaction.
+    seq: AtomicU64,
+    /// The hashes of timeout transactions removed during package.
+    expired: Mutex<Vec<Hash>>,
     /// This is used to pick a queue for insertion,
     /// If true selects `queue_0`, else `queue_1`.
     is_zero: AtomicBool,
@@ -135,6 +380,10 @@ impl TxCache {
             queue_0:          ArrayQueue::new(pool_size * 2),
             queue_1:          ArrayQueue::new(pool_size * 2),
             map:              Map::new(pool_size * 2),
+            nonces:           Map::new(pool_size * 2),
+            index:            Mutex::new(PoolIndex::default()),
+            seq:              AtomicU64::new(0),
+            expired:          Mutex::new(Vec::new()),
             is_zero:          AtomicBool::new(true),
             concurrent_count: AtomicUsize::new(0),
         }
@@ -146,16 +395,33 @@ impl TxCache {
 
     pub fn insert_new_tx(&self, signed_tx: SignedTransaction) -> ProtocolResult<()> {
         let tx_hash = signed_tx.tx_hash.clone();
-        let tx_wrapper = TxWrapper::new(signed_tx.clone());
+        let seq = self.seq.fetch_add(1, Ordering::SeqCst);
+        let tx_wrapper = TxWrapper::new(signed_tx.clone(), seq);
+        let shared_tx = Arc::new(tx_wrapper);
+        self.insert(tx_hash, shared_tx, None).map(|_| ())
+    }
+
+    /// Insert a new transaction. It replaces the pending transaction of the
+    /// same nonce, otherwise evicts a transaction of lower fee if the pool is
+    /// full. Return the hash of the replaced or evicted transaction.
+    pub fn insert_evict_tx(
+        &self,
+        signed_tx: SignedTransaction,
+        limits: InsertLimits,
+    ) -> ProtocolResult<Option<Hash>> {
+        let tx_hash = signed_tx.tx_hash.clone();
+        let seq = self.seq.fetch_add(1, Ordering::SeqCst);
+        let tx_wrapper = TxWrapper::new(signed_tx.clone(), seq);
         let shared_tx = Arc::new(tx_wrapper);
-        self.insert(tx_hash, shared_tx)
+        self.insert(tx_hash, shared_tx, Some(limits))
     }
 
     pub fn insert_propose_tx(&self, signed_tx: SignedTransaction) -> ProtocolResult<()> {
         let tx_hash = signed_tx.tx_hash.clone();
-        let tx_wrapper = TxWrapper::propose(signed_tx.clone());
+        let seq = self.seq.fetch_add(1, Ordering::SeqCst);
+        let tx_wrapper = TxWrapper::propose(signed_tx.clone(), seq);
         let shared_tx = Arc::new(tx_wrapper);
-        self.insert(tx_hash, shared_tx)
+        self.insert(tx_hash, shared_tx, None).map(|_| ())
     }
 
     pub fn show_unknown(&self, tx_hashes: Vec<Hash>) -> Vec<Hash> {
@@ -165,41 +431,205 @@ impl TxCache {
             .collect()
     }
 
+    /// Find the pending transaction of the same sender and nonce. The new
+    /// transaction can replace it only if the fee is higher by at least
+    /// `replace_ratio` percent, and it is not packaged for consensus.
+    ///
+    /// The replacement is best-effort, not a cancel. Nonces are random and the
+    /// executor doesn't reject a duplicate (sender, nonce), so the replaced
+    /// transaction can still be committed if another node has it.
+    pub fn check_replace(
+        &self,
+        signed_tx: &SignedTransaction,
+        replace_ratio: u64,
+    ) -> ProtocolResult<Option<Hash>> {
+        self.find_replaced(signed_tx, replace_ratio)
+            .map(|pending| pending.map(|pending| pending.tx.tx_hash.clone()))
+    }
+
+    fn find_replaced(
+        &self,
+        signed_tx: &SignedTransaction,
+        replace_ratio: u64,
+    ) -> ProtocolResult<Option<SharedTx>> {
+        let pending = self
+            .nonces
+            .get(&nonce_key(signed_tx))
+            .and_then(|tx_hash| self.map.get(&tx_hash));
+        let pending = match pending {
+            Some(shared_tx) if !shared_tx.is_removed() => shared_tx,
+            _ => return Ok(None),
+        };
+        if pending.is_packaged() {
+            return Err(MemPoolError::ReplacePackaged {
+                tx_hash:  signed_tx.tx_hash.clone(),
+                replaced: pending.tx.tx_hash.clone(),
+            }
+            .into());
+        }
+
+        let fee_rate = pending.fee_rate();
+        let bump = (u128::from(fee_rate) * u128::from(replace_ratio) / 100).max(1);
+        let min_fee_rate = (u128::from(fee_rate) + bump).min(u128::from(u64::max_value())) as u64;
+        if signed_tx.raw.fee.cycle < min_fee_rate {
+            return Err(MemPoolError::Underpriced {
+                tx_hash: signed_tx.tx_hash.clone(),
+                replaced: pending.tx.tx_hash.clone(),
+                min_fee_rate,
+            }
+            .into());
+        }
+        Ok(Some(pending))
+    }
+
+    /// Check whether a new transaction can get into the pool before it is
+    /// verified. When the pool is full, only a transaction of lower fee which
+    /// is not packaged can be evicted. The transaction to evict is picked
+    /// again by `insert_evict_tx`.
+    pub fn check_evict(
+        &self,
+        signed_tx: &SignedTransaction,
+        pool_size: usize,
+    ) -> ProtocolResult<()> {
+        if self.len() < pool_size {
+            return Ok(());
+        }
+
+        let index = self.index.lock();
+        Self::pick_evict(&index, signed_tx, pool_size).map(|_| ())
+    }
+
+    fn pick_evict(
+        index: &PoolIndex,
+        signed_tx: &SignedTransaction,
+        pool_size: usize,
+    ) -> ProtocolResult<Hash> {
+        match index.evict_candidate() {
+            Some((fee_rate, tx_hash)) if fee_rate < signed_tx.raw.fee.cycle => Ok(tx_hash.clone()),
+            _ => Err(MemPoolError::ReachLimit { pool_size }.into()),
+        }
+    }
+
+    #[inline]
+    pub fn check_sender_limit(
+        &self,
+        signed_tx: &SignedTransaction,
+        sender_limit: usize,
+    ) -> ProtocolResult<()> {
+        Self::check_sender_count(&self.index.lock(), signed_tx, sender_limit)
+    }
+
+    fn check_sender_count(
+        index: &PoolIndex,
+        signed_tx: &SignedTransaction,
+        sender_limit: usize,
+    ) -> ProtocolResult<()> {
+        let count = index
+            .senders
+            .get(&signed_tx.pubkey)
+            .map_or(0, |sender| sender.count);
+        if count >= sender_limit {
+            return Err(MemPoolError::SenderReachLimit {
+                tx_hash: signed_tx.tx_hash.clone(),
+                sender_limit,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// What the pending transactions of the sender can spend, except the
+    /// transaction to be replaced.
+    pub fn pending_spend(
+        &self,
+        signed_tx: &SignedTransaction,
+        replaced: Option<&Hash>,
+    ) -> PendingSpend {
+        let mut spend = self
+            .index
+            .lock()
+            .senders
+            .get(&signed_tx.pubkey)
+            .map(|sender| sender.spend.clone())
+            .unwrap_or_default();
+        if let Some(replaced) = replaced.and_then(|tx_hash| self.map.get(tx_hash)) {
+            sub_spend(&mut spend, &replaced.tx);
+        }
+        spend
+    }
+
+    /// Mark the transactions ordered in a proposal, they can't be replaced
+    /// until the next package or flush.
+    pub fn set_packaged(&self, tx_hashes: &[Hash]) {
+        self.mark_packaged(&mut self.index.lock(), tx_hashes);
+    }
+
+    fn mark_packaged(&self, index: &mut PoolIndex, tx_hashes: &[Hash]) {
+        for tx_hash in tx_hashes {
+            if let Some(shared_tx) = self.map.get(tx_hash) {
+                shared_tx.set_packaged();
+                index.set_packaged(&shared_tx);
+            }
+        }
+    }
+
+    // Clear the marks of the proposals which are not committed, their
+    // transactions can be replaced or evicted again.
+    fn clear_packaged(&self, index: &mut PoolIndex) {
+        let tx_hashes = index.packaged.iter().cloned().collect::<Vec<_>>();
+        for tx_hash in tx_hashes.iter() {
+            match self.map.get(tx_hash) {
+                Some(shared_tx) => {
+                    shared_tx.unset_packaged();
+                    index.unset_packaged(&shared_tx);
+                }
+                None => {
+                    index.packaged.remove(tx_hash);
+                }
+            }
+        }
+    }
+
     pub fn flush(&self, tx_hashes: &[Hash]) {
+        let mut shared_txs = Vec::with_capacity(tx_hashes.len());
         for tx_hash in tx_hashes {
             let opt = self.map.get(tx_hash);
             if let Some(shared_tx) = opt {
                 shared_tx.set_removed();
+                shared_txs.push(shared_tx);
             }
         }
         // Dividing set removed and remove into two loops is to avoid lock competition.
         self.map.deletes(tx_hashes);
+        self.remove_indexes(&shared_txs);
+        // The epoch is committed, the other proposals of it are dropped.
+        self.clear_packaged(&mut self.index.lock());
     }
 
+    /// Package transactions for consensus. The order transactions are picked
+    /// by fee rate, except that `reserved_ratio` percent of the cycle limit is
+    /// kept for the earliest transactions whatever their fees are, so that low
+    /// fee transactions are not starved. The transactions of a sender are
+    /// always packaged in insertion order.
     pub fn package(
         &self,
         cycle_limit: u64,
         current_epoch_id: u64,
         timeout: u64,
+        reserved_ratio: u64,
     ) -> ProtocolResult<MixedTxHashes> {
         let queue_role = self.get_queue_role();
 
-        let mut order_tx_hashes = Vec::new();
-        let mut propose_tx_hashes = Vec::new();
-        let mut timeout_tx_hashes = Vec::new();
-
-        let mut cycle_count: u64 = 0;
-        let mut stage = Stage::OrderTxs;
+        let mut senders = SenderQueues::default();
+        let mut timeout_txs = Vec::new();
 
         loop {
             if let Ok(shared_tx) = queue_role.incumbent.pop() {
-                let tx_hash = &shared_tx.tx.tx_hash;
-
                 if shared_tx.is_removed() {
                     continue;
                 }
                 if shared_tx.is_timeout(current_epoch_id, timeout) {
-                    timeout_tx_hashes.push(tx_hash.clone());
+                    timeout_txs.push(shared_tx);
                     continue;
                 }
                 // After previous filter, tx are valid and should cache in temp_queue.
@@ -209,25 +639,7 @@ impl TxCache {
                     .map_err(|_| MemPoolError::InsertCandidate {
                         len: queue_role.candidate.len(),
                     })?;
-
-                if stage == Stage::Finished
-                    || (stage == Stage::ProposeTxs && shared_tx.is_proposed())
-                {
-                    continue;
-                }
-                // Accumulate cycles. The order_tx_hashes and the propose_tx_hashes both collect
-                // transactions under cycle limit.
-                cycle_count += shared_tx.tx.raw.fee.cycle;
-                if cycle_count > cycle_limit {
-                    stage = stage.next();
-                    cycle_count = shared_tx.tx.raw.fee.cycle;
-                }
-
-                match stage {
-                    Stage::OrderTxs => order_tx_hashes.push(tx_hash.clone()),
-                    Stage::ProposeTxs => propose_tx_hashes.push(tx_hash.clone()),
-                    Stage::Finished => {}
-                }
+                senders.push(shared_tx);
             } else {
                 // Switch queue_roles
                 let new_role = self.switch_queue_role();
@@ -237,7 +649,29 @@ impl TxCache {
             }
         }
         // Remove timeout tx in map
+        let timeout_tx_hashes: Vec<Hash> = timeout_txs
+            .iter()
+            .map(|shared_tx| shared_tx.tx.tx_hash.clone())
+            .collect();
         self.map.deletes(&timeout_tx_hashes);
+        self.remove_indexes(&timeout_txs);
+        self.expired.lock().extend(timeout_tx_hashes);
+
+        // The order_tx_hashes and the propose_tx_hashes both collect transactions
+        // under cycle limit.
+        let reserved = (u128::from(cycle_limit) * u128::from(reserved_ratio.min(100)) / 100) as u64;
+        let (mut order_tx_hashes, cycle_count) = senders.pick(cycle_limit - reserved, true, false);
+        let (reserved_tx_hashes, _) = senders.pick(cycle_limit - cycle_count, false, false);
+        order_tx_hashes.extend(reserved_tx_hashes);
+        // The proposals of the previous rounds are not committed, only the new
+        // one is kept packaged.
+        {
+            let mut index = self.index.lock();
+            self.clear_packaged(&mut index);
+            self.mark_packaged(&mut index, &order_tx_hashes);
+        }
+        // Transactions from propose-transaction-sync are already known by others.
+        let (propose_tx_hashes, _) = senders.pick(cycle_limit, true, true);
 
         Ok(MixedTxHashes {
             order_tx_hashes,
@@ -256,12 +690,9 @@ impl TxCache {
         Ok(())
     }
 
-    #[inline]
-    pub fn check_reach_limit(&self, pool_size: usize) -> ProtocolResult<()> {
-        if self.len() >= pool_size {
-            return Err(MemPoolError::ReachLimit { pool_size }.into());
-        }
-        Ok(())
+    /// Take the hashes of timeout transactions removed since last time.
+    pub fn take_expired(&self) -> Vec<Hash> {
+        self.expired.lock().drain(..).collect()
     }
 
     #[inline]
@@ -274,6 +705,47 @@ impl TxCache {
         self.map.get(tx_hash).map(|shared_tx| shared_tx.tx.clone())
     }
 
+    pub fn get_status(&self, tx_hash: &Hash) -> TxStatus {
+        match self.map.get(tx_hash) {
+            Some(shared_tx) if shared_tx.is_proposed() => TxStatus::Proposed,
+            Some(_) => TxStatus::Pending,
+            None => TxStatus::Unknown,
+        }
+    }
+
+    /// Get the transactions matching the filter, in insertion order.
+    pub fn filter_txs<F>(&self, filter: F) -> Vec<SignedTransaction>
+    where
+        F: Fn(&SignedTransaction) -> bool,
+    {
+        let mut shared_txs: Vec<SharedTx> = self
+            .map
+            .values()
+            .into_iter()
+            .filter(|shared_tx| filter(&shared_tx.tx))
+            .collect();
+        shared_txs.sort_by_key(|shared_tx| shared_tx.seq);
+
+        shared_txs
+            .into_iter()
+            .map(|shared_tx| shared_tx.tx.clone())
+            .collect()
+    }
+
+    pub fn oldest_timeout(&self) -> Option<u64> {
+        self.map
+            .values()
+            .iter()
+            .map(|shared_tx| shared_tx.tx.raw.timeout)
+            .min()
+    }
+
+    /// The lengths of the incumbent queue and the candidate queue.
+    pub fn queue_lens(&self) -> (usize, usize) {
+        let queue_role = self.get_queue_role();
+        (queue_role.incumbent.len(), queue_role.candidate.len())
+    }
+
     #[allow(dead_code)]
     pub fn queue_len(&self) -> usize {
         if self.is_zero.load(Ordering::Relaxed) {
@@ -283,16 +755,52 @@ impl TxCache {
         }
     }
 
-    fn insert(&self, tx_hash: Hash, shared_tx: SharedTx) -> ProtocolResult<()> {
-        // If multiple transactions exactly the same insert concurrently,
-        // this will prevent them to be both insert successfully into queue.
-        if self
-            .map
-            .insert(tx_hash.clone(), Arc::<TxWrapper>::clone(&shared_tx))
-            .is_some()
-        {
-            return Err(MemPoolError::Dup { tx_hash }.into());
-        }
+    // Insert a transaction. If the limits are given, the transaction replaces
+    // the pending one of the same nonce, or evicts one if the pool is full.
+    fn insert(
+        &self,
+        tx_hash: Hash,
+        shared_tx: SharedTx,
+        limits: Option<InsertLimits>,
+    ) -> ProtocolResult<Option<Hash>> {
+        let removed = {
+            // Hold the index while inserting into map, so that a concurrent removal
+            // can't happen before adding into the index, and concurrent insertions
+            // can't replace or evict the same transaction, nor exceed the sender
+            // limit together.
+            let mut index = self.index.lock();
+            if self.map.contains_key(&tx_hash) {
+                return Err(MemPoolError::Dup { tx_hash }.into());
+            }
+
+            let removed = match limits {
+                Some(limits) => self.pick_removed(&index, &shared_tx.tx, limits)?,
+                None => None,
+            };
+            if let Some(removed) = &removed {
+                removed.set_removed();
+                index.remove(removed);
+                self.map.remove(&removed.tx.tx_hash);
+                self.remove_nonces(&[Arc::clone(removed)]);
+            }
+
+            // If multiple transactions exactly the same insert concurrently,
+            // this will prevent them to be both insert successfully into queue.
+            if self
+                .map
+                .insert(tx_hash.clone(), Arc::<TxWrapper>::clone(&shared_tx))
+                .is_some()
+            {
+                return Err(MemPoolError::Dup { tx_hash }.into());
+            }
+            index.add(&shared_tx);
+            // A transaction from propose-transaction-sync may have the same nonce as a
+            // pending one, the pending one is kept for replacement then.
+            self.nonces
+                .insert(nonce_key(&shared_tx.tx), tx_hash.clone());
+            removed
+        };
+        let removed = removed.map(|removed| removed.tx.tx_hash.clone());
 
         self.concurrent_count.fetch_add(1, Ordering::SeqCst);
         let rst = self
@@ -304,11 +812,60 @@ impl TxCache {
         // If queue inserts into queue failed, removes from map.
         if rst.is_err() {
             // If tx_hash exists, it will panic. So repeat check must do before insertion.
+            shared_tx.set_removed();
+            self.remove_indexes(&[Arc::clone(&shared_tx)]);
             self.map.remove(&tx_hash);
             Err(MemPoolError::Insert { tx_hash }.into())
         } else {
-            Ok(())
+            Ok(removed)
+        }
+    }
+
+    // Pick the pending transaction a new one replaces, or the one it evicts if
+    // the pool is full. These are checked before the verification, and again
+    // here under the index lock.
+    fn pick_removed(
+        &self,
+        index: &PoolIndex,
+        signed_tx: &SignedTransaction,
+        limits: InsertLimits,
+    ) -> ProtocolResult<Option<SharedTx>> {
+        if let Some(replaced) = self.find_replaced(signed_tx, limits.replace_ratio)? {
+            return Ok(Some(replaced));
         }
+
+        Self::check_sender_count(index, signed_tx, limits.sender_limit)?;
+        if self.len() < limits.pool_size {
+            return Ok(None);
+        }
+        let evict_hash = Self::pick_evict(index, signed_tx, limits.pool_size)?;
+        Ok(self.map.get(&evict_hash))
+    }
+
+    // Remove the removed transactions from the index, and the nonces which
+    // still point to them.
+    fn remove_indexes(&self, shared_txs: &[SharedTx]) {
+        let mut index = self.index.lock();
+        shared_txs
+            .iter()
+            .for_each(|shared_tx| index.remove(shared_tx));
+        self.remove_nonces(shared_txs);
+    }
+
+    // Must be called under the index lock, so that a nonce is never removed
+    // after a new transaction of the same nonce is inserted.
+    fn remove_nonces(&self, shared_txs: &[SharedTx]) {
+        let keys: Vec<Hash> = shared_txs
+            .iter()
+            .filter_map(|shared_tx| {
+                let key = nonce_key(&shared_tx.tx);
+                match self.nonces.get(&key) {
+                    Some(tx_hash) if tx_hash == shared_tx.tx.tx_hash => Some(key),
+                    _ => None,
+                }
+            })
+            .collect();
+        self.nonces.deletes(&keys);
     }
 
     // Process transactions insert into previous incumbent queue during role switch.
@@ -354,18 +911,22 @@ mod tests {
     use std::thread;
 
     use bytes::Bytes;
+    use common_crypto::Secp256k1;
     use num_traits::FromPrimitive;
     use rand::random;
     use rayon::iter::IntoParallelRefIterator;
     use rayon::prelude::*;
     use test::Bencher;
 
+    use protocol::traits::PendingSpend;
     use protocol::types::{
         CarryingAsset, Fee, Hash, RawTransaction, SignedTransaction, TransactionAction, UserAddress,
     };
 
+    use crate::adapter::{build_verify_pool, verify_signature, verify_signatures};
     use crate::map::Map;
-    use crate::tx_cache::{TxCache, TxWrapper};
+    use crate::tests::{default_mempool, default_mock_txs, exec_flush, exec_insert_batch};
+    use crate::tx_cache::{InsertLimits, TxCache, TxWrapper};
     use std::thread::JoinHandle;
 
     const POOL_SIZE: usize = 1000;
@@ -375,6 +936,7 @@ mod tests {
     const CYCLE_LIMIT: u64 = 500;
     const CURRENT_H: u64 = 100;
     const TIMEOUT: u64 = 150;
+    const RESERVED_RATIO: u64 = 10;
 
     fn gen_bytes() -> Vec<u8> {
         (0..BYTES_LEN).map(|_| random::<u8>()).collect()
@@ -423,6 +985,39 @@ mod tests {
         }
     }
 
+    fn mock_fee_tx(sender: u8, cycle: u64) -> SignedTransaction {
+        let mut tx = mock_signed_tx(gen_bytes());
+        tx.raw.fee.cycle = cycle;
+        tx.pubkey = Bytes::from(vec![sender]);
+        tx
+    }
+
+    fn evict_limits(pool_size: usize) -> InsertLimits {
+        InsertLimits {
+            pool_size,
+            sender_limit: POOL_SIZE,
+            replace_ratio: 10,
+        }
+    }
+
+    fn package_hashes(
+        txs: &[SignedTransaction],
+        cycle_limit: u64,
+        reserved_ratio: u64,
+    ) -> (Vec<Hash>, Vec<Hash>) {
+        let tx_cache = TxCache::new(POOL_SIZE);
+        for tx in txs.iter() {
+            tx_cache.insert_new_tx(tx.clone()).unwrap();
+        }
+        let mixed_tx_hashes = tx_cache
+            .package(cycle_limit, CURRENT_H, TIMEOUT, reserved_ratio)
+            .unwrap();
+        (
+            mixed_tx_hashes.order_tx_hashes,
+            mixed_tx_hashes.propose_tx_hashes,
+        )
+    }
+
     fn concurrent_insert(txs: Vec<SignedTransaction>, tx_cache: &TxCache) {
         txs.par_iter().for_each(|signed_tx| {
             let _ = tx_cache.insert_new_tx(signed_tx.clone());
@@ -441,7 +1036,7 @@ mod tests {
         let tx_cache_clone = Arc::<TxCache>::clone(tx_cache);
         thread::spawn(move || {
             tx_cache_clone
-                .package(CYCLE_LIMIT, CURRENT_H, TIMEOUT)
+                .package(CYCLE_LIMIT, CURRENT_H, TIMEOUT, RESERVED_RATIO)
                 .unwrap();
         })
     }
@@ -468,18 +1063,153 @@ mod tests {
         let tx = txs.get(0).unwrap();
         let map = Map::new(POOL_SIZE);
 
-        let tx_wrapper_0 = TxWrapper::new(tx.clone());
+        let tx_wrapper_0 = TxWrapper::new(tx.clone(), 0);
         tx_wrapper_0.set_removed();
         map.insert(tx.tx_hash.clone(), Arc::new(tx_wrapper_0));
         let shared_tx_0 = map.get(&tx.tx_hash).unwrap();
         assert!(shared_tx_0.is_removed());
 
-        let tx_wrapper_1 = TxWrapper::new(tx.clone());
+        let tx_wrapper_1 = TxWrapper::new(tx.clone(), 1);
         map.insert(tx.tx_hash.clone(), Arc::new(tx_wrapper_1));
         let shared_tx_1 = map.get(&tx.tx_hash).unwrap();
         assert!(shared_tx_1.is_removed());
     }
 
+    #[test]
+    fn test_package_by_fee() {
+        // The second transaction of sender 1 pays the most, but it can't
+        // overtake the first one.
+        let txs = vec![mock_fee_tx(1, 1), mock_fee_tx(1, 4), mock_fee_tx(2, 2)];
+        let (order, propose) = package_hashes(&txs, 6, 0);
+        assert_eq!(order, vec![txs[2].tx_hash.clone(), txs[0].tx_hash.clone()]);
+        assert_eq!(propose, vec![txs[1].tx_hash.clone()]);
+    }
+
+    #[test]
+    fn test_package_reserved() {
+        let mut txs = vec![mock_fee_tx(1, 1), mock_fee_tx(2, 1)];
+        txs.extend((3..6).map(|sender| mock_fee_tx(sender, 3)));
+        let hashes = |indexes: &[usize]| -> Vec<Hash> {
+            indexes.iter().map(|i| txs[*i].tx_hash.clone()).collect()
+        };
+
+        // High fee transactions take all the cycles without a reserved share.
+        let (order, propose) = package_hashes(&txs, 9, 0);
+        assert_eq!(order, hashes(&[2, 3, 4]));
+        assert_eq!(propose, hashes(&[0, 1]));
+
+        let (order, propose) = package_hashes(&txs, 9, 25);
+        assert_eq!(order, hashes(&[2, 3, 0, 1]));
+        assert_eq!(propose, hashes(&[4]));
+    }
+
+    #[test]
+    fn test_pending_spend() {
+        let tx_cache = TxCache::new(POOL_SIZE);
+        let txs = vec![mock_fee_tx(1, 2), mock_fee_tx(1, 3)];
+        for tx in txs.iter() {
+            tx_cache.insert_new_tx(tx.clone()).unwrap();
+        }
+
+        let new_tx = mock_fee_tx(1, 1);
+        let spend = tx_cache.pending_spend(&new_tx, None);
+        assert_eq!(spend.fee_cycles.len(), 2);
+        assert_eq!(spend.fee_cycles.get(&txs[1].raw.fee.asset_id), Some(&3));
+        assert_eq!(spend.carried.len(), 2);
+
+        // The replaced transaction and the flushed ones are not counted.
+        let spend = tx_cache.pending_spend(&new_tx, Some(&txs[1].tx_hash));
+        assert_eq!(spend.fee_cycles.len(), 1);
+        tx_cache.flush(&[txs[0].tx_hash.clone()]);
+        let spend = tx_cache.pending_spend(&new_tx, Some(&txs[1].tx_hash));
+        assert_eq!(spend, PendingSpend::default());
+    }
+
+    #[test]
+    fn test_evict_concurrent() {
+        let pool_size = 10;
+        let tx_cache = TxCache::new(POOL_SIZE);
+        let low_fee_txs: Vec<_> = (0..pool_size).map(|i| mock_fee_tx(i as u8, 1)).collect();
+        for tx in low_fee_txs.iter() {
+            tx_cache.insert_new_tx(tx.clone()).unwrap();
+        }
+
+        // Concurrent insertions never evict the same transaction.
+        let txs: Vec<_> = (0..100).map(|i| mock_fee_tx(i as u8, 2)).collect();
+        let evicted: Vec<_> = txs
+            .par_iter()
+            .filter_map(|tx| {
+                tx_cache
+                    .insert_evict_tx(tx.clone(), evict_limits(pool_size))
+                    .ok()
+            })
+            .collect();
+        assert_eq!(tx_cache.len(), pool_size);
+        assert_eq!(evicted.len(), pool_size);
+    }
+
+    #[test]
+    fn test_evict_skip_packaged() {
+        let pool_size = 2;
+        let tx_cache = TxCache::new(POOL_SIZE);
+        let txs = vec![mock_fee_tx(1, 1), mock_fee_tx(2, 1)];
+        for tx in txs.iter() {
+            tx_cache.insert_new_tx(tx.clone()).unwrap();
+        }
+        tx_cache.set_packaged(&[txs[0].tx_hash.clone()]);
+
+        let evicted = tx_cache
+            .insert_evict_tx(mock_fee_tx(3, 2), evict_limits(pool_size))
+            .unwrap();
+        assert_eq!(evicted, Some(txs[1].tx_hash.clone()));
+
+        // Only packaged and better transactions are left.
+        assert!(tx_cache
+            .insert_evict_tx(mock_fee_tx(4, 2), evict_limits(pool_size))
+            .is_err());
+        assert!(tx_cache.contain(&txs[0].tx_hash));
+    }
+
+    #[test]
+    fn test_clear_packaged() {
+        let tx_cache = TxCache::new(POOL_SIZE);
+        let txs: Vec<_> = (0..3).map(|i| mock_fee_tx(i as u8, 1)).collect();
+        for tx in txs.iter() {
+            tx_cache.insert_new_tx(tx.clone()).unwrap();
+        }
+        let packaged = || {
+            let mut tx_hashes = tx_cache
+                .index
+                .lock()
+                .packaged
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            tx_hashes.sort_by_key(|tx_hash| tx_hash.as_hex());
+            tx_hashes
+        };
+        let sorted = |mut tx_hashes: Vec<Hash>| {
+            tx_hashes.sort_by_key(|tx_hash| tx_hash.as_hex());
+            tx_hashes
+        };
+
+        // The marks of a proposal not committed are cleared by the next flush.
+        tx_cache.set_packaged(&[txs[0].tx_hash.clone()]);
+        tx_cache.flush(&[txs[1].tx_hash.clone()]);
+        assert!(packaged().is_empty());
+        assert_eq!(tx_cache.index.lock().evict_order.len(), 2);
+
+        // Only the latest package is marked.
+        tx_cache.set_packaged(&[txs[0].tx_hash.clone()]);
+        let order = tx_cache
+            .package(1, CURRENT_H, TIMEOUT, 0)
+            .unwrap()
+            .order_tx_hashes;
+        assert_eq!(order.len(), 1);
+        assert_eq!(packaged(), sorted(order));
+        assert_eq!(tx_cache.index.lock().evict_order.len(), 1);
+    }
+
     #[bench]
     fn bench_gen_txs(b: &mut Bencher) {
         b.iter(|| {
@@ -541,7 +1271,9 @@ mod tests {
         let tx_cache = TxCache::new(POOL_SIZE);
         concurrent_insert(txs.clone(), &tx_cache);
         b.iter(|| {
-            let mixed_tx_hashes = tx_cache.package(CYCLE_LIMIT, CURRENT_H, TIMEOUT).unwrap();
+            let mixed_tx_hashes = tx_cache
+                .package(CYCLE_LIMIT, CURRENT_H, TIMEOUT, RESERVED_RATIO)
+                .unwrap();
             assert_eq!(
                 mixed_tx_hashes.order_tx_hashes.len(),
                 (CYCLE_LIMIT / TX_CYCLE) as usize
@@ -563,4 +1295,39 @@ mod tests {
             assert_eq!(tx_cache.queue_len(), TX_NUM);
         });
     }
+
+    #[bench]
+    fn bench_insert_batch(b: &mut Bencher) {
+        let mempool = &Arc::new(default_mempool());
+        let txs = default_mock_txs(100);
+        let tx_hashes = txs.iter().map(|tx| tx.tx_hash.clone()).collect::<Vec<_>>();
+
+        // The batch is flushed after insertion, so that it is inserted again.
+        b.iter(|| {
+            exec_insert_batch(txs.clone(), Arc::clone(mempool)).unwrap();
+            exec_flush(tx_hashes.clone(), Arc::clone(mempool));
+        });
+    }
+
+    #[bench]
+    fn bench_verify_serial(b: &mut Bencher) {
+        let txs = default_mock_txs(100);
+
+        b.iter(|| {
+            for tx in txs.iter() {
+                verify_signature::<Secp256k1>(tx).unwrap();
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_verify_parallel(b: &mut Bencher) {
+        let txs = default_mock_txs(100);
+        let verify_pool = build_verify_pool();
+
+        b.iter(|| {
+            let verified = verify_pool.install(|| verify_signatures::<Secp256k1>(txs.clone()));
+            assert_eq!(verified.len(), txs.len());
+        });
+    }
 }
@@ -8,6 +8,7 @@ use tentacle::{
     bytes::Bytes,
     error::Error as TentacleError,
     service::{ServiceControl, TargetSession},
+    SessionId,
 };
 
 use crate::{
@@ -68,6 +69,22 @@ impl<P: NetworkProtocol> MessageSender for ConnectionServiceControl<P> {
         Ok(())
     }
 
+    fn send_except(
+        &self,
+        excluded: Vec<SessionId>,
+        msg: Bytes,
+        pri: Priority,
+    ) -> Result<(), NetworkError> {
+        // Only peer manager knows all the sessions
+        let broadcast = PeerManagerEvent::BroadcastExcept { excluded, msg, pri };
+
+        if self.mgr_tx.unbounded_send(broadcast).is_err() {
+            debug!("network: connection service control: peer manager service exit");
+        }
+
+        Ok(())
+    }
+
     async fn users_send(
         &self,
         user_addrs: Vec<UserAddress>,
@@ -168,4 +168,11 @@ pub enum PeerManagerEvent {
         users_msg: MultiUsersMessage,
         miss_tx:   Sender<Vec<UserAddress>>,
     },
+
+    #[display(fmt = "broadcast message except sessions {:?}", excluded)]
+    BroadcastExcept {
+        excluded: Vec<SessionId>,
+        msg:      Bytes,
+        pri:      Priority,
+    },
 }
@@ -17,3 +17,5 @@ mod traits;
 pub use config::NetworkConfig;
 pub use message::{serde, serde_multi};
 pub use service::{NetworkService, NetworkServiceHandle};
+pub use tentacle::SessionId;
+pub use traits::NetworkContext;
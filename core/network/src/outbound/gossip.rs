@@ -10,7 +10,7 @@ use crate::{
     endpoint::Endpoint,
     error::NetworkError,
     message::NetworkMessage,
-    traits::{Compression, MessageSender},
+    traits::{Compression, MessageSender, NetworkContext},
 };
 
 #[derive(Clone)]
@@ -80,7 +80,13 @@ where
         M: MessageCodec,
     {
         let msg = self.package_message(cx.clone(), end, msg).await?;
-        self.send(cx, TargetSession::All, msg, p)?;
+
+        let excluded = cx.excluded_sessions();
+        if excluded.is_empty() {
+            self.send(cx, TargetSession::All, msg, p)?;
+        } else {
+            self.sender.send_except(excluded, msg, p)?;
+        }
 
         Ok(())
     }
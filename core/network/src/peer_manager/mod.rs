@@ -36,9 +36,10 @@ use futures::{
 };
 use log::{debug, error, info, warn};
 use parking_lot::RwLock;
-use protocol::types::UserAddress;
+use protocol::{traits::Priority, types::UserAddress};
 use rand::seq::IteratorRandom;
 use tentacle::{
+    bytes::Bytes,
     multiaddr::{Multiaddr, Protocol},
     secio::{PeerId, PublicKey},
     service::{DialProtocol, SessionType, TargetSession},
@@ -857,6 +858,26 @@ impl PeerManager {
         }
     }
 
+    fn broadcast_except(&mut self, excluded: Vec<SessionId>, msg: Bytes, pri: Priority) {
+        let sessions = self
+            .peer_session
+            .values()
+            .filter(|sid| !excluded.contains(sid))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if sessions.is_empty() {
+            return;
+        }
+
+        let tar = TargetSession::Multi(sessions);
+        let send_msg = ConnectionEvent::SendMsg { tar, msg, pri };
+
+        if self.conn_tx.unbounded_send(send_msg).is_err() {
+            error!("network: connection service exit");
+        }
+    }
+
     fn process_event(&mut self, event: PeerManagerEvent) {
         match event {
             PeerManagerEvent::AttachPeerSession { pubkey, session } => {
@@ -994,6 +1015,9 @@ impl PeerManager {
             PeerManagerEvent::RouteMultiUsersMessage { users_msg, miss_tx } => {
                 self.route_multi_users_message(users_msg, miss_tx);
             }
+            PeerManagerEvent::BroadcastExcept { excluded, msg, pri } => {
+                self.broadcast_except(excluded, msg, pri);
+            }
         }
     }
 }
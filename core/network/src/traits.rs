@@ -27,6 +27,7 @@ pub trait NetworkProtocol {
 #[async_trait]
 pub trait MessageSender {
     fn send(&self, tar: TargetSession, msg: Bytes, pri: Priority) -> Result<(), NetworkError>;
+    fn send_except(&self, excluded: Vec<SessionId>, msg: Bytes, pri: Priority) -> Result<(), NetworkError>;
     async fn users_send(&self, users: Vec<UserAddress>, msg: Bytes, pri: Priority) -> Result<(), NetworkError>;
 }
 
@@ -40,6 +41,8 @@ pub trait NetworkContext: Sized {
     fn set_session_id(&mut self, sid: SessionId) -> Self;
     fn rpc_id(&self) -> Result<u64, NetworkError>;
     fn set_rpc_id(&mut self, rid: u64) -> Self;
+    fn excluded_sessions(&self) -> Vec<SessionId>;
+    fn set_excluded_sessions(&mut self, sids: Vec<SessionId>) -> Self;
 }
 
 pub trait ListenExchangeManager {
@@ -58,6 +61,11 @@ struct CtxRpcId(u64);
 
 impl Cloneable for CtxRpcId {}
 
+#[derive(Debug, Clone)]
+struct CtxExcludedSessions(Vec<SessionId>);
+
+impl Cloneable for CtxExcludedSessions {}
+
 impl NetworkContext for Context {
     fn session_id(&self) -> Result<SessionId, NetworkError> {
         self.get::<CtxSessionId>("session_id")
@@ -80,4 +88,16 @@ impl NetworkContext for Context {
     fn set_rpc_id(&mut self, rid: u64) -> Self {
         self.with_value::<CtxRpcId>("rpc_id", CtxRpcId(rid))
     }
+
+    fn excluded_sessions(&self) -> Vec<SessionId> {
+        self.get::<CtxExcludedSessions>("excluded_sessions")
+            .map(|ctx_sids| ctx_sids.0.clone())
+            .unwrap_or_default()
+    }
+
+    // Broadcast to all sessions except these ones
+    #[must_use]
+    fn set_excluded_sessions(&mut self, sids: Vec<SessionId>) -> Self {
+        self.with_value::<CtxExcludedSessions>("excluded_sessions", CtxExcludedSessions(sids))
+    }
 }
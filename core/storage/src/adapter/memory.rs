@@ -8,12 +8,14 @@ use derive_more::{Display, From};
 use parking_lot::RwLock;
 
 use protocol::codec::ProtocolCodec;
-use protocol::traits::{StorageAdapter, StorageBatchModify, StorageSchema};
+use protocol::traits::{StorageAdapter, StorageBatchModify, StorageCategory, StorageSchema};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
+type Category = HashMap<Vec<u8>, Vec<u8>>;
+
 #[derive(Debug)]
 pub struct MemoryAdapter {
-    db: Arc<RwLock<HashMap<Vec<u8>, Vec<u8>>>>,
+    db: Arc<RwLock<HashMap<StorageCategory, Category>>>,
 }
 
 impl MemoryAdapter {
@@ -42,7 +44,11 @@ impl StorageAdapter for MemoryAdapter {
         let key = key.encode().await?.to_vec();
         let val = val.encode().await?.to_vec();
 
-        self.db.write().insert(key, val);
+        self.db
+            .write()
+            .entry(S::category())
+            .or_insert_with(HashMap::new)
+            .insert(key, val);
 
         Ok(())
     }
@@ -53,7 +59,11 @@ impl StorageAdapter for MemoryAdapter {
     ) -> ProtocolResult<Option<<S as StorageSchema>::Value>> {
         let key = key.encode().await?;
 
-        let opt_bytes = self.db.read().get(&key.to_vec()).cloned();
+        let opt_bytes = self
+            .db
+            .read()
+            .get(&S::category())
+            .and_then(|category| category.get(&key.to_vec()).cloned());
 
         if let Some(bytes) = opt_bytes {
             let val = <_>::decode(bytes).await?;
@@ -64,13 +74,36 @@ impl StorageAdapter for MemoryAdapter {
         }
     }
 
+    async fn get_all<S: StorageSchema>(&self) -> ProtocolResult<Vec<<S as StorageSchema>::Value>> {
+        let bytes_vec = self
+            .db
+            .read()
+            .get(&S::category())
+            .map(|category| {
+                category
+                    .values()
+                    .map(|val| Bytes::from(val.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let mut vals = Vec::with_capacity(bytes_vec.len());
+        for bytes in bytes_vec.into_iter() {
+            vals.push(<_>::decode(bytes).await?);
+        }
+
+        Ok(vals)
+    }
+
     async fn remove<S: StorageSchema>(
         &self,
         mut key: <S as StorageSchema>::Key,
     ) -> ProtocolResult<()> {
         let key = key.encode().await?.to_vec();
 
-        self.db.write().remove(&key);
+        if let Some(category) = self.db.write().get_mut(&S::category()) {
+            category.remove(&key);
+        }
 
         Ok(())
     }
@@ -81,7 +114,11 @@ impl StorageAdapter for MemoryAdapter {
     ) -> ProtocolResult<bool> {
         let key = key.encode().await?.to_vec();
 
-        Ok(self.db.read().get(&key).is_some())
+        let db = self.db.read();
+
+        Ok(db
+            .get(&S::category())
+            .map_or(false, |category| category.contains_key(&key)))
     }
 
     async fn batch_modify<S: StorageSchema>(
@@ -106,10 +143,12 @@ impl StorageAdapter for MemoryAdapter {
             pairs.push((key, value))
         }
 
+        let mut db = self.db.write();
+        let category = db.entry(S::category()).or_insert_with(HashMap::new);
         for (key, value) in pairs.into_iter() {
             match value {
-                Some(value) => self.db.write().insert(key.to_vec(), value.to_vec()),
-                None => self.db.write().remove(&key.to_vec()),
+                Some(value) => category.insert(key.to_vec(), value.to_vec()),
+                None => category.remove(&key.to_vec()),
             };
         }
 
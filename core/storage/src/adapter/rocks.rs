@@ -5,7 +5,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use bytes::Bytes;
 use derive_more::{Display, From};
-use rocksdb::{ColumnFamily, Options, WriteBatch, DB};
+use rocksdb::{ColumnFamily, IteratorMode, Options, WriteBatch, DB};
 
 use protocol::codec::ProtocolCodec;
 use protocol::traits::{StorageAdapter, StorageBatchModify, StorageCategory, StorageSchema};
@@ -26,6 +26,10 @@ impl RocksAdapter {
             map_category(StorageCategory::Epoch),
             map_category(StorageCategory::Receipt),
             map_category(StorageCategory::SignedTransaction),
+            map_category(StorageCategory::Trace),
+            map_category(StorageCategory::Evidence),
+            map_category(StorageCategory::Proof),
+            map_category(StorageCategory::PendingTransaction),
         ];
 
         let db = DB::open_cf(&opts, path, categories.iter()).map_err(RocksAdapterError::from)?;
@@ -79,6 +83,23 @@ impl StorageAdapter for RocksAdapter {
         }
     }
 
+    async fn get_all<S: StorageSchema>(&self) -> ProtocolResult<Vec<<S as StorageSchema>::Value>> {
+        let column = get_column::<S>(&self.db)?;
+
+        let bytes_vec = {
+            db!(self.db, iterator_cf, column, IteratorMode::Start)?
+                .map(|(_, val)| Bytes::from(val.to_vec()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut vals = Vec::with_capacity(bytes_vec.len());
+        for bytes in bytes_vec.into_iter() {
+            vals.push(<_>::decode(bytes).await?);
+        }
+
+        Ok(vals)
+    }
+
     async fn remove<S: StorageSchema>(
         &self,
         mut key: <S as StorageSchema>::Key,
@@ -164,12 +185,20 @@ impl From<RocksAdapterError> for ProtocolError {
 const C_EPOCHS: &str = "c1";
 const C_SIGNED_TRANSACTIONS: &str = "c2";
 const C_RECEIPTS: &str = "c3";
+const C_TRACES: &str = "c4";
+const C_EVIDENCES: &str = "c5";
+const C_PROOFS: &str = "c6";
+const C_PENDING_TRANSACTIONS: &str = "c7";
 
 fn map_category(c: StorageCategory) -> &'static str {
     match c {
         StorageCategory::Epoch => C_EPOCHS,
         StorageCategory::Receipt => C_RECEIPTS,
         StorageCategory::SignedTransaction => C_SIGNED_TRANSACTIONS,
+        StorageCategory::Trace => C_TRACES,
+        StorageCategory::Evidence => C_EVIDENCES,
+        StorageCategory::Proof => C_PROOFS,
+        StorageCategory::PendingTransaction => C_PENDING_TRANSACTIONS,
     }
 }
 
@@ -11,13 +11,17 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use bytes::Bytes;
 use derive_more::{Display, From};
+use futures::lock::Mutex;
 use lazy_static::lazy_static;
 
 use protocol::codec::ProtocolCodec;
 use protocol::traits::{
     Storage, StorageAdapter, StorageBatchModify, StorageCategory, StorageSchema,
 };
-use protocol::types::{Epoch, EpochId, Hash, Proof, Receipt, SignedTransaction};
+use protocol::types::{
+    Epoch, EpochEvidences, EpochId, Evidence, Hash, Proof, Receipt, SignedTransaction,
+    TransactionTrace,
+};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 lazy_static! {
@@ -28,11 +32,17 @@ lazy_static! {
 #[derive(Debug)]
 pub struct ImplStorage<Adapter> {
     adapter: Arc<Adapter>,
+    // The evidences of an epoch are read, modified and written back, the
+    // concurrent inserts must not overwrite each other.
+    evidence_lock: Mutex<()>,
 }
 
 impl<Adapter: StorageAdapter> ImplStorage<Adapter> {
     pub fn new(adapter: Arc<Adapter>) -> Self {
-        Self { adapter }
+        Self {
+            adapter,
+            evidence_lock: Mutex::new(()),
+        }
     }
 }
 
@@ -58,10 +68,19 @@ impl_storage_schema_for!(
     SignedTransaction
 );
 impl_storage_schema_for!(ReceiptSchema, Hash, Receipt, Receipt);
+impl_storage_schema_for!(TraceSchema, Hash, TransactionTrace, Trace);
 impl_storage_schema_for!(EpochSchema, EpochId, Epoch, Epoch);
 impl_storage_schema_for!(HashEpochSchema, Hash, EpochId, Epoch);
 impl_storage_schema_for!(LatestEpochSchema, Hash, Epoch, Epoch);
 impl_storage_schema_for!(LatestProofSchema, Hash, Proof, Epoch);
+impl_storage_schema_for!(EvidenceSchema, EpochId, EpochEvidences, Evidence);
+impl_storage_schema_for!(ProofSchema, EpochId, Proof, Proof);
+impl_storage_schema_for!(
+    PendingTransactionSchema,
+    Hash,
+    SignedTransaction,
+    PendingTransaction
+);
 
 macro_rules! batch_insert {
     ($self_: ident,$vec: expr, $schema: ident) => {
@@ -133,6 +152,12 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         Ok(())
     }
 
+    async fn insert_traces(&self, traces: Vec<TransactionTrace>) -> ProtocolResult<()> {
+        batch_insert!(self, traces, TraceSchema);
+
+        Ok(())
+    }
+
     async fn update_latest_proof(&self, proof: Proof) -> ProtocolResult<()> {
         self.adapter
             .insert::<LatestProofSchema>(LATEST_PROOF_KEY.clone(), proof)
@@ -141,6 +166,67 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         Ok(())
     }
 
+    async fn insert_proof(&self, proof: Proof) -> ProtocolResult<()> {
+        let epoch_id = EpochId { id: proof.epoch_id };
+        self.adapter.insert::<ProofSchema>(epoch_id, proof).await?;
+
+        Ok(())
+    }
+
+    async fn insert_evidence(&self, evidence: Evidence) -> ProtocolResult<()> {
+        let _guard = self.evidence_lock.lock().await;
+        let epoch_id = EpochId {
+            id: evidence.epoch_id,
+        };
+        let mut epoch_evidences = self
+            .adapter
+            .get::<EvidenceSchema>(epoch_id.clone())
+            .await?
+            .unwrap_or_else(|| EpochEvidences {
+                epoch_id:  evidence.epoch_id,
+                evidences: Vec::new(),
+            });
+
+        // One evidence is enough to prove a validator signed twice in a step,
+        // the others of the same step are dropped.
+        if epoch_evidences.evidences.iter().any(|e| {
+            e.validator == evidence.validator
+                && e.evidence_type == evidence.evidence_type
+                && e.round == evidence.round
+        }) {
+            return Ok(());
+        }
+        epoch_evidences.evidences.push(evidence);
+
+        self.adapter
+            .insert::<EvidenceSchema>(epoch_id, epoch_evidences)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn insert_pending_transactions(
+        &self,
+        signed_txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        batch_insert!(self, signed_txs, PendingTransactionSchema);
+
+        Ok(())
+    }
+
+    async fn remove_pending_transactions(&self, tx_hashes: Vec<Hash>) -> ProtocolResult<()> {
+        let batch_remove = tx_hashes
+            .iter()
+            .map(|_| StorageBatchModify::Remove)
+            .collect::<Vec<_>>();
+
+        self.adapter
+            .batch_modify::<PendingTransactionSchema>(tx_hashes, batch_remove)
+            .await?;
+
+        Ok(())
+    }
+
     async fn get_transaction_by_hash(&self, tx_hash: Hash) -> ProtocolResult<SignedTransaction> {
         let stx = get!(self, tx_hash, TransactionSchema);
 
@@ -185,11 +271,37 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         Ok(receipts)
     }
 
+    async fn get_trace(&self, tx_hash: Hash) -> ProtocolResult<TransactionTrace> {
+        let trace = get!(self, tx_hash, TraceSchema);
+
+        Ok(trace)
+    }
+
     async fn get_latest_proof(&self) -> ProtocolResult<Proof> {
         let proof = get!(self, LATEST_PROOF_KEY.clone(), LatestProofSchema);
 
         Ok(proof)
     }
+
+    async fn get_proof(&self, epoch_id: u64) -> ProtocolResult<Proof> {
+        let epoch_id = EpochId { id: epoch_id };
+        let proof = get!(self, epoch_id, ProofSchema);
+
+        Ok(proof)
+    }
+
+    async fn get_evidences(&self, epoch_id: u64) -> ProtocolResult<Vec<Evidence>> {
+        let epoch_id = EpochId { id: epoch_id };
+        let opt = self.adapter.get::<EvidenceSchema>(epoch_id).await?;
+
+        Ok(opt.map(|e| e.evidences).unwrap_or_default())
+    }
+
+    async fn get_pending_transactions(&self) -> ProtocolResult<Vec<SignedTransaction>> {
+        let stxs = self.adapter.get_all::<PendingTransactionSchema>().await?;
+
+        Ok(stxs)
+    }
 }
 
 fn opts_to_flat<T>(values: Vec<Option<T>>) -> Vec<T> {
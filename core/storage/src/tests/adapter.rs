@@ -3,8 +3,8 @@ use protocol::types::Hash;
 
 use crate::adapter::memory::MemoryAdapter;
 use crate::adapter::rocks::RocksAdapter;
-use crate::tests::{get_random_bytes, mock_signed_tx};
-use crate::TransactionSchema;
+use crate::tests::{get_random_bytes, mock_receipt, mock_signed_tx};
+use crate::{ReceiptSchema, TransactionSchema};
 
 #[test]
 fn test_adapter_insert() {
@@ -26,6 +26,12 @@ fn test_adapter_remove() {
     adapter_remove_test(RocksAdapter::new("rocksdb/test_adapter_remove".to_string()).unwrap())
 }
 
+#[test]
+fn test_adapter_get_all() {
+    adapter_get_all_test(MemoryAdapter::new());
+    adapter_get_all_test(RocksAdapter::new("rocksdb/test_adapter_get_all".to_string()).unwrap())
+}
+
 fn adapter_insert_test(db: impl StorageAdapter) {
     let tx_hash = Hash::digest(get_random_bytes(10));
     let stx = mock_signed_tx(tx_hash.clone());
@@ -74,3 +80,22 @@ fn adapter_remove_test(db: impl StorageAdapter) {
     let is_exist = exec!(db.contains::<TransactionSchema>(tx_hash.clone()));
     assert!(!is_exist);
 }
+
+fn adapter_get_all_test(db: impl StorageAdapter) {
+    let mut hashes = Vec::new();
+    for _ in 0..10 {
+        let tx_hash = Hash::digest(get_random_bytes(10));
+        exec!(db.insert::<TransactionSchema>(tx_hash.clone(), mock_signed_tx(tx_hash.clone())));
+        hashes.push(tx_hash);
+    }
+    exec!(db.insert::<ReceiptSchema>(hashes[0].clone(), mock_receipt(hashes[0].clone())));
+
+    let stxs = exec!(db.get_all::<TransactionSchema>());
+    for tx_hash in hashes.iter() {
+        assert!(stxs.iter().any(|stx| &stx.tx_hash == tx_hash));
+    }
+    // The values of other schemas are not included.
+    assert!(exec!(db.get_all::<ReceiptSchema>())
+        .iter()
+        .all(|receipt| receipt.tx_hash != hashes[1]));
+}
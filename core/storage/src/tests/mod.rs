@@ -14,8 +14,9 @@ use num_traits::FromPrimitive;
 use rand::random;
 
 use protocol::types::{
-    CarryingAsset, Epoch, EpochHeader, Fee, Hash, Proof, RawTransaction, Receipt, ReceiptResult,
-    SignedTransaction, TransactionAction, UserAddress,
+    Address, CarryingAsset, ContractTrace, Epoch, EpochHeader, Evidence, EvidenceType, Fee, Hash,
+    Proof, RawTransaction, Receipt, ReceiptResult, SignedTransaction, StateDiff, TransactionAction,
+    TransactionTrace, UserAddress,
 };
 
 fn mock_signed_tx(tx_hash: Hash) -> SignedTransaction {
@@ -72,6 +73,24 @@ fn mock_receipt(tx_hash: Hash) -> Receipt {
     }
 }
 
+fn mock_trace(tx_hash: Hash) -> TransactionTrace {
+    let addr_str = "10CAB8EEA4799C21379C20EF5BAA2CC8AF1BEC475B";
+    let diff = StateDiff {
+        key:    get_random_bytes(32),
+        before: None,
+        after:  get_random_bytes(32),
+    };
+
+    TransactionTrace {
+        tx_hash,
+        epoch_id: 10,
+        contracts: vec![ContractTrace {
+            address: Address::from_hex(addr_str).unwrap(),
+            diffs:   vec![diff],
+        }],
+    }
+}
+
 fn mock_epoch(epoch_id: u64, epoch_hash: Hash) -> Epoch {
     let nonce = Hash::digest(Bytes::from("XXXX"));
     let addr_str = "10CAB8EEA4799C21379C20EF5BAA2CC8AF1BEC475B";
@@ -83,6 +102,7 @@ fn mock_epoch(epoch_id: u64, epoch_hash: Hash) -> Epoch {
         logs_bloom: Default::default(),
         order_root: nonce.clone(),
         confirm_root: Vec::new(),
+        exec_epoch_id: 0,
         state_root: nonce.clone(),
         receipt_root: Vec::new(),
         cycles_used: 999_999,
@@ -108,6 +128,19 @@ fn mock_proof(epoch_hash: Hash) -> Proof {
     }
 }
 
+fn mock_evidence(epoch_id: u64, evidence_type: EvidenceType) -> Evidence {
+    let addr_str = "10CAB8EEA4799C21379C20EF5BAA2CC8AF1BEC475B";
+
+    Evidence {
+        validator: UserAddress::from_hex(addr_str).unwrap(),
+        epoch_id,
+        round: 1,
+        evidence_type,
+        first: get_random_bytes(64),
+        second: get_random_bytes(64),
+    }
+}
+
 fn get_random_bytes(len: usize) -> Bytes {
     let vec: Vec<u8> = (0..len).map(|_| random::<u8>()).collect();
     Bytes::from(vec)
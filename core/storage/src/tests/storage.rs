@@ -1,11 +1,16 @@
 use std::sync::Arc;
 
+use futures::future;
+
 use protocol::codec::ProtocolCodec;
 use protocol::traits::Storage;
-use protocol::types::Hash;
+use protocol::types::{EvidenceType, Hash, SignedTransaction};
 
 use crate::adapter::memory::MemoryAdapter;
-use crate::tests::{get_random_bytes, mock_epoch, mock_proof, mock_receipt, mock_signed_tx};
+use crate::tests::{
+    get_random_bytes, mock_epoch, mock_evidence, mock_proof, mock_receipt, mock_signed_tx,
+    mock_trace,
+};
 use crate::ImplStorage;
 
 #[test]
@@ -53,6 +58,19 @@ fn test_storage_receipts_insert() {
     }
 }
 
+#[test]
+fn test_storage_traces_insert() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+
+    let tx_hash = Hash::digest(get_random_bytes(10));
+    let trace = mock_trace(tx_hash.clone());
+
+    exec!(storage.insert_traces(vec![trace.clone()]));
+    let trace_2 = exec!(storage.get_trace(tx_hash));
+
+    assert_eq!(trace, trace_2);
+}
+
 #[test]
 fn test_storage_transactions_insert() {
     let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
@@ -90,3 +108,88 @@ fn test_storage_latest_proof_insert() {
 
     assert_eq!(proof.epoch_hash, proof_2.epoch_hash);
 }
+
+#[test]
+fn test_storage_proof_insert() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+
+    let mut proofs = Vec::new();
+    for epoch_id in 1..=3 {
+        let mut proof = mock_proof(Hash::digest(get_random_bytes(10)));
+        proof.epoch_id = epoch_id;
+        exec!(storage.insert_proof(proof.clone()));
+        proofs.push(proof);
+    }
+
+    for proof in proofs.into_iter() {
+        let proof_2 = exec!(storage.get_proof(proof.epoch_id));
+        assert_eq!(proof, proof_2);
+    }
+    assert!(futures::executor::block_on(storage.get_proof(4)).is_err());
+}
+
+#[test]
+fn test_storage_evidence_insert() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+
+    assert!(exec!(storage.get_evidences(10)).is_empty());
+
+    let prevote = mock_evidence(10, EvidenceType::Prevote);
+    let precommit = mock_evidence(10, EvidenceType::Precommit);
+    exec!(storage.insert_evidence(prevote.clone()));
+    exec!(storage.insert_evidence(precommit.clone()));
+    // Duplicate evidences are stored once.
+    exec!(storage.insert_evidence(prevote.clone()));
+    // Another evidence of the same validator in the same step is dropped.
+    exec!(storage.insert_evidence(mock_evidence(10, EvidenceType::Prevote)));
+    exec!(storage.insert_evidence(mock_evidence(11, EvidenceType::Proposal)));
+
+    assert_eq!(exec!(storage.get_evidences(10)), vec![prevote, precommit]);
+    assert_eq!(exec!(storage.get_evidences(11)).len(), 1);
+}
+
+#[test]
+fn test_storage_evidence_concurrent_insert() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+
+    let evidences = (0..10)
+        .map(|round| {
+            let mut evidence = mock_evidence(10, EvidenceType::Prevote);
+            evidence.round = round;
+            evidence
+        })
+        .collect::<Vec<_>>();
+    let inserted = futures::executor::block_on(future::join_all(
+        evidences
+            .iter()
+            .map(|evidence| storage.insert_evidence(evidence.clone())),
+    ));
+    assert!(inserted.iter().all(Result::is_ok));
+
+    assert_eq!(exec!(storage.get_evidences(10)).len(), evidences.len());
+}
+
+#[test]
+fn test_storage_pending_transactions() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+
+    let stxs: Vec<SignedTransaction> = (0..5)
+        .map(|_| mock_signed_tx(Hash::digest(get_random_bytes(10))))
+        .collect();
+    exec!(storage.insert_pending_transactions(stxs.clone()));
+    // The committed transactions are not pending.
+    exec!(storage.insert_transactions(stxs[..1].to_vec()));
+    assert_eq!(exec!(storage.get_pending_transactions()).len(), 5);
+
+    let removed: Vec<Hash> = stxs[..2].iter().map(|stx| stx.tx_hash.clone()).collect();
+    exec!(storage.remove_pending_transactions(removed));
+
+    let mut pending: Vec<Hash> = exec!(storage.get_pending_transactions())
+        .into_iter()
+        .map(|stx| stx.tx_hash)
+        .collect();
+    let mut expect: Vec<Hash> = stxs[2..].iter().map(|stx| stx.tx_hash.clone()).collect();
+    pending.sort_by(|a, b| a.as_bytes().cmp(&b.as_bytes()));
+    expect.sort_by(|a, b| a.as_bytes().cmp(&b.as_bytes()));
+    assert_eq!(pending, expect);
+}
@@ -1,4 +1,7 @@
-use protocol::types::{Hash, UserAddress};
+use std::convert::TryFrom;
+
+use common_crypto::{BLS12381PrivateKey, Crypto, PrivateKey, PublicKey, Signature, BLS12381};
+use protocol::types::{Hash, UserAddress, Validator};
 use rand::{rngs::OsRng, RngCore};
 use tentacle_secio::SecioKeyPair;
 
@@ -17,4 +20,31 @@ pub fn main() {
         hex::encode(keypair.to_public_key().inner())
     );
     println!("user addr hex: {}", user_addr.as_hex());
+
+    // The BLS private key is a 48 bytes big-endian scalar, keep it less than
+    // the group order. An existing BLS private key can be given as the first
+    // argument to print its public key and proof of possession.
+    let bls_seckey = match std::env::args().nth(1) {
+        Some(seckey) => hex::decode(seckey).expect("bls seckey hex"),
+        None => {
+            let mut bls_seed = [0u8; 32];
+            OsRng.fill_bytes(&mut bls_seed);
+            bls_seed[0] &= 0x3f;
+
+            let mut bls_seckey = vec![0u8; 16];
+            bls_seckey.extend_from_slice(&bls_seed);
+            bls_seckey
+        }
+    };
+    let bls_keypair = BLS12381PrivateKey::try_from(bls_seckey.as_ref()).expect("bls keypair");
+    let bls_pubkey = bls_keypair.pub_key().to_bytes();
+    let bls_pop = BLS12381::sign_message(
+        &Validator::bls_pop_message(&bls_pubkey).as_bytes(),
+        &bls_keypair.to_bytes(),
+    )
+    .expect("bls pop");
+
+    println!("bls seckey hex: {:?}", hex::encode(bls_seckey));
+    println!("bls pubkey hex: {:?}", hex::encode(bls_pubkey));
+    println!("bls pop hex: {:?}", hex::encode(bls_pop.to_bytes()));
 }
@@ -70,6 +70,9 @@ pub struct EpochHeader {
 
     #[prost(message, repeated, tag = "14")]
     pub validators: Vec<Validator>,
+
+    #[prost(uint64, tag = "15")]
+    pub exec_epoch_id: u64,
 }
 
 #[derive(Clone, Message)]
@@ -100,6 +103,9 @@ pub struct Validator {
 
     #[prost(uint32, tag = "3")]
     pub vote_weight: u32,
+
+    #[prost(bytes, tag = "4")]
+    pub bls_pub_key: Vec<u8>,
 }
 
 #[derive(Clone, Message)]
@@ -201,6 +207,7 @@ impl From<epoch::EpochHeader> for EpochHeader {
             proof,
             validator_version: epoch_header.validator_version,
             validators,
+            exec_epoch_id: epoch_header.exec_epoch_id,
         }
     }
 }
@@ -239,6 +246,7 @@ impl TryFrom<EpochHeader> for epoch::EpochHeader {
             logs_bloom: Bloom::from_slice(&epoch_header.logs_bloom),
             order_root: protocol_primitive::Hash::try_from(order_root)?,
             confirm_root,
+            exec_epoch_id: epoch_header.exec_epoch_id,
             state_root: protocol_primitive::Hash::try_from(state_root)?,
             receipt_root,
             cycles_used: epoch_header.cycles_used,
@@ -296,6 +304,7 @@ impl From<epoch::Validator> for Validator {
             address,
             propose_weight: u32::from(validator.propose_weight),
             vote_weight: u32::from(validator.vote_weight),
+            bls_pub_key: validator.bls_pub_key.to_vec(),
         }
     }
 }
@@ -308,6 +317,7 @@ impl TryFrom<Validator> for epoch::Validator {
 
         let validator = epoch::Validator {
             address:        protocol_primitive::UserAddress::try_from(address)?,
+            bls_pub_key:    Bytes::from(validator.bls_pub_key),
             propose_weight: validator.propose_weight as u8,
             vote_weight:    validator.vote_weight as u8,
         };
@@ -0,0 +1,150 @@
+use std::convert::TryFrom;
+
+use bytes::Bytes;
+use prost::{Enumeration, Message};
+
+use crate::{
+    codec::{primitive::UserAddress, CodecError, ProtocolCodecSync},
+    field, impl_default_bytes_codec_for,
+    types::primitive as protocol_primitive,
+    ProtocolError, ProtocolResult,
+};
+
+// #####################
+// Protobuf
+// #####################
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Enumeration)]
+pub enum EvidenceType {
+    Proposal = 0,
+    Prevote = 1,
+    Precommit = 2,
+}
+
+#[derive(Clone, Message)]
+pub struct Evidence {
+    #[prost(message, tag = "1")]
+    pub validator: Option<UserAddress>,
+
+    #[prost(uint64, tag = "2")]
+    pub epoch_id: u64,
+
+    #[prost(uint64, tag = "3")]
+    pub round: u64,
+
+    #[prost(enumeration = "EvidenceType", tag = "4")]
+    pub evidence_type: i32,
+
+    #[prost(bytes, tag = "5")]
+    pub first: Vec<u8>,
+
+    #[prost(bytes, tag = "6")]
+    pub second: Vec<u8>,
+}
+
+#[derive(Clone, Message)]
+pub struct EpochEvidences {
+    #[prost(uint64, tag = "1")]
+    pub epoch_id: u64,
+
+    #[prost(message, repeated, tag = "2")]
+    pub evidences: Vec<Evidence>,
+}
+
+// #################
+// Conversion
+// #################
+
+// Evidence
+
+impl From<evidence::Evidence> for Evidence {
+    fn from(evidence: evidence::Evidence) -> Evidence {
+        let evidence_type = match evidence.evidence_type {
+            evidence::EvidenceType::Proposal => EvidenceType::Proposal,
+            evidence::EvidenceType::Prevote => EvidenceType::Prevote,
+            evidence::EvidenceType::Precommit => EvidenceType::Precommit,
+        };
+
+        Evidence {
+            validator:     Some(UserAddress::from(evidence.validator)),
+            epoch_id:      evidence.epoch_id,
+            round:         evidence.round,
+            evidence_type: evidence_type as i32,
+            first:         evidence.first.to_vec(),
+            second:        evidence.second.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<Evidence> for evidence::Evidence {
+    type Error = ProtocolError;
+
+    fn try_from(evidence: Evidence) -> Result<evidence::Evidence, Self::Error> {
+        let validator = field!(evidence.validator, "Evidence", "validator")?;
+        let evidence_type = match evidence.evidence_type {
+            0 => evidence::EvidenceType::Proposal,
+            1 => evidence::EvidenceType::Prevote,
+            2 => evidence::EvidenceType::Precommit,
+            value => {
+                return Err(CodecError::InvalidEnumValue {
+                    r#type: "EvidenceType",
+                    value,
+                }
+                .into())
+            }
+        };
+
+        let evidence = evidence::Evidence {
+            validator: protocol_primitive::UserAddress::try_from(validator)?,
+            epoch_id: evidence.epoch_id,
+            round: evidence.round,
+            evidence_type,
+            first: Bytes::from(evidence.first),
+            second: Bytes::from(evidence.second),
+        };
+
+        Ok(evidence)
+    }
+}
+
+// EpochEvidences
+
+impl From<evidence::EpochEvidences> for EpochEvidences {
+    fn from(epoch_evidences: evidence::EpochEvidences) -> EpochEvidences {
+        let evidences = epoch_evidences
+            .evidences
+            .into_iter()
+            .map(Evidence::from)
+            .collect::<Vec<_>>();
+
+        EpochEvidences {
+            epoch_id: epoch_evidences.epoch_id,
+            evidences,
+        }
+    }
+}
+
+impl TryFrom<EpochEvidences> for evidence::EpochEvidences {
+    type Error = ProtocolError;
+
+    fn try_from(epoch_evidences: EpochEvidences) -> Result<evidence::EpochEvidences, Self::Error> {
+        let evidences = epoch_evidences
+            .evidences
+            .into_iter()
+            .map(evidence::Evidence::try_from)
+            .collect::<Result<Vec<_>, ProtocolError>>()?;
+
+        let epoch_evidences = evidence::EpochEvidences {
+            epoch_id: epoch_evidences.epoch_id,
+            evidences,
+        };
+
+        Ok(epoch_evidences)
+    }
+}
+
+// #################
+// Codec
+// #################
+
+impl_default_bytes_codec_for!(evidence, [Evidence, EpochEvidences]);
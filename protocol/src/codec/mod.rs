@@ -4,10 +4,12 @@
 #[macro_use]
 mod r#macro;
 pub mod epoch;
+pub mod evidence;
 pub mod primitive;
 pub mod receipt;
 #[cfg(test)]
 mod tests;
+pub mod trace;
 pub mod transaction;
 
 use std::error::Error;
@@ -68,6 +70,9 @@ pub enum CodecError {
     #[display(fmt = "invalid contract type {}", _0)]
     InvalidContractType(i32),
 
+    #[display(fmt = "invalid {} value {}", r#type, value)]
+    InvalidEnumValue { r#type: &'static str, value: i32 },
+
     #[display(fmt = "wrong bytes length: {{ expect: {}, got: {} }}", expect, real)]
     WrongBytesLength { expect: usize, real: usize },
 }
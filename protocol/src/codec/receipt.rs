@@ -32,7 +32,7 @@ pub struct Receipt {
     #[prost(message, tag = "4")]
     pub cycles_used: Option<Fee>,
 
-    #[prost(oneof = "ReceiptResult", tags = "5, 6, 7, 8, 9")]
+    #[prost(oneof = "ReceiptResult", tags = "5, 6, 7, 8, 9, 10")]
     pub result: Option<ReceiptResult>,
 }
 
@@ -48,6 +48,8 @@ pub enum ReceiptResult {
     Call(Call),
     #[prost(message, tag = "9")]
     Fail(Fail),
+    #[prost(message, tag = "10")]
+    Reward(RewardList),
 }
 
 #[derive(Clone, Message)]
@@ -98,10 +100,56 @@ pub struct Fail {
     pub user: String,
 }
 
+#[derive(Clone, Message)]
+pub struct Reward {
+    #[prost(message, tag = "1")]
+    pub receiver: Option<UserAddress>,
+    #[prost(message, tag = "2")]
+    pub asset_id: Option<AssetID>,
+    #[prost(message, tag = "3")]
+    pub amount: Option<Balance>,
+}
+
+#[derive(Clone, Message)]
+pub struct RewardList {
+    #[prost(message, repeated, tag = "1")]
+    pub rewards: Vec<Reward>,
+}
+
 // #################
 // Conversion
 // #################
 
+// Reward
+
+impl From<receipt::Reward> for Reward {
+    fn from(reward: receipt::Reward) -> Reward {
+        Reward {
+            receiver: Some(UserAddress::from(reward.receiver)),
+            asset_id: Some(AssetID::from(reward.asset_id)),
+            amount:   Some(Balance::from(reward.amount)),
+        }
+    }
+}
+
+impl TryFrom<Reward> for receipt::Reward {
+    type Error = ProtocolError;
+
+    fn try_from(reward: Reward) -> Result<receipt::Reward, Self::Error> {
+        let receiver = field!(reward.receiver, "Reward", "receiver")?;
+        let asset_id = field!(reward.asset_id, "Reward", "asset_id")?;
+        let amount = field!(reward.amount, "Reward", "amount")?;
+
+        let reward = receipt::Reward {
+            receiver: protocol_primitive::UserAddress::try_from(receiver)?,
+            asset_id: protocol_primitive::AssetID::try_from(asset_id)?,
+            amount:   protocol_primitive::Balance::try_from(amount)?,
+        };
+
+        Ok(reward)
+    }
+}
+
 // ReceiptResult
 
 impl From<receipt::ReceiptResult> for ReceiptResult {
@@ -164,6 +212,11 @@ impl From<receipt::ReceiptResult> for ReceiptResult {
 
                 ReceiptResult::Fail(fail)
             }
+            receipt::ReceiptResult::Reward { rewards } => {
+                let rewards = rewards.into_iter().map(Reward::from).collect::<Vec<_>>();
+
+                ReceiptResult::Reward(RewardList { rewards })
+            }
         }
     }
 }
@@ -245,6 +298,15 @@ impl TryFrom<ReceiptResult> for receipt::ReceiptResult {
 
                 Ok(action)
             }
+            ReceiptResult::Reward(list) => {
+                let rewards = list
+                    .rewards
+                    .into_iter()
+                    .map(receipt::Reward::try_from)
+                    .collect::<Result<Vec<_>, ProtocolError>>()?;
+
+                Ok(receipt::ReceiptResult::Reward { rewards })
+            }
         }
     }
 }
@@ -39,8 +39,12 @@ fn test_codec() {
     test!(receipt, ReceiptResult, mock_result, ReceiptType::Deploy);
     test!(receipt, ReceiptResult, mock_result, ReceiptType::Call);
     test!(receipt, ReceiptResult, mock_result, ReceiptType::Fail);
+    test!(receipt, ReceiptResult, mock_result, ReceiptType::Reward);
+    test!(receipt, Reward, mock_reward);
     test!(receipt, Receipt, mock_receipt, ReceiptType::Transfer);
 
+    test!(trace, TransactionTrace, mock_transaction_trace);
+
     test!(transaction, TransactionAction, mock_action, AType::Transfer);
     test!(transaction, TransactionAction, mock_action, AType::Approve);
     test!(transaction, TransactionAction, mock_action, AType::Deploy);
@@ -54,6 +58,9 @@ fn test_codec() {
     test!(epoch, EpochHeader, mock_epoch_header);
     test!(epoch, Epoch, mock_epoch, 100);
     test!(epoch, Pill, mock_pill, 100, 200);
+
+    test!(evidence, Evidence, mock_evidence, EvidenceType::Prevote);
+    test!(evidence, EpochEvidences, mock_epoch_evidences);
 }
 
 #[test]
@@ -0,0 +1,159 @@
+use std::convert::TryFrom;
+
+use bytes::Bytes;
+use prost::Message;
+
+use crate::{
+    codec::{primitive::Hash, CodecError, ProtocolCodecSync},
+    field, impl_default_bytes_codec_for,
+    types::primitive as protocol_primitive,
+    ProtocolError, ProtocolResult,
+};
+
+// #####################
+// Protobuf
+// #####################
+
+#[derive(Clone, Message)]
+pub struct TransactionTrace {
+    #[prost(message, tag = "1")]
+    pub tx_hash: Option<Hash>,
+
+    #[prost(uint64, tag = "2")]
+    pub epoch_id: u64,
+
+    #[prost(message, repeated, tag = "3")]
+    pub contracts: Vec<ContractTrace>,
+}
+
+#[derive(Clone, Message)]
+pub struct ContractTrace {
+    #[prost(bytes, tag = "1")]
+    pub address: Vec<u8>,
+
+    #[prost(message, repeated, tag = "2")]
+    pub diffs: Vec<StateDiff>,
+}
+
+#[derive(Clone, Message)]
+pub struct StateDiff {
+    #[prost(bytes, tag = "1")]
+    pub key: Vec<u8>,
+
+    #[prost(bytes, optional, tag = "2")]
+    pub before: Option<Vec<u8>>,
+
+    #[prost(bytes, tag = "3")]
+    pub after: Vec<u8>,
+}
+
+// #################
+// Conversion
+// #################
+
+// StateDiff
+
+impl From<trace::StateDiff> for StateDiff {
+    fn from(diff: trace::StateDiff) -> StateDiff {
+        StateDiff {
+            key:    diff.key.to_vec(),
+            before: diff.before.map(|before| before.to_vec()),
+            after:  diff.after.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<StateDiff> for trace::StateDiff {
+    type Error = ProtocolError;
+
+    fn try_from(diff: StateDiff) -> Result<trace::StateDiff, Self::Error> {
+        let diff = trace::StateDiff {
+            key:    Bytes::from(diff.key),
+            before: diff.before.map(Bytes::from),
+            after:  Bytes::from(diff.after),
+        };
+
+        Ok(diff)
+    }
+}
+
+// ContractTrace
+
+impl From<trace::ContractTrace> for ContractTrace {
+    fn from(contract: trace::ContractTrace) -> ContractTrace {
+        let diffs = contract
+            .diffs
+            .into_iter()
+            .map(StateDiff::from)
+            .collect::<Vec<_>>();
+
+        ContractTrace {
+            address: contract.address.as_bytes().to_vec(),
+            diffs,
+        }
+    }
+}
+
+impl TryFrom<ContractTrace> for trace::ContractTrace {
+    type Error = ProtocolError;
+
+    fn try_from(contract: ContractTrace) -> Result<trace::ContractTrace, Self::Error> {
+        let diffs = contract
+            .diffs
+            .into_iter()
+            .map(trace::StateDiff::try_from)
+            .collect::<Result<Vec<_>, ProtocolError>>()?;
+
+        let contract = trace::ContractTrace {
+            address: protocol_primitive::Address::from_bytes(Bytes::from(contract.address))?,
+            diffs,
+        };
+
+        Ok(contract)
+    }
+}
+
+// TransactionTrace
+
+impl From<trace::TransactionTrace> for TransactionTrace {
+    fn from(trace: trace::TransactionTrace) -> TransactionTrace {
+        let contracts = trace
+            .contracts
+            .into_iter()
+            .map(ContractTrace::from)
+            .collect::<Vec<_>>();
+
+        TransactionTrace {
+            tx_hash: Some(Hash::from(trace.tx_hash)),
+            epoch_id: trace.epoch_id,
+            contracts,
+        }
+    }
+}
+
+impl TryFrom<TransactionTrace> for trace::TransactionTrace {
+    type Error = ProtocolError;
+
+    fn try_from(trace: TransactionTrace) -> Result<trace::TransactionTrace, Self::Error> {
+        let tx_hash = field!(trace.tx_hash, "TransactionTrace", "tx_hash")?;
+        let contracts = trace
+            .contracts
+            .into_iter()
+            .map(trace::ContractTrace::try_from)
+            .collect::<Result<Vec<_>, ProtocolError>>()?;
+
+        let trace = trace::TransactionTrace {
+            tx_hash: protocol_primitive::Hash::try_from(tx_hash)?,
+            epoch_id: trace.epoch_id,
+            contracts,
+        };
+
+        Ok(trace)
+    }
+}
+
+// #################
+// Codec
+// #################
+
+impl_default_bytes_codec_for!(trace, [TransactionTrace]);
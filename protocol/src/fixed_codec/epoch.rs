@@ -44,8 +44,9 @@ impl rlp::Decodable for Proof {
 
 impl rlp::Encodable for Validator {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
-        s.begin_list(3)
+        s.begin_list(4)
             .append(&self.address)
+            .append(&self.bls_pub_key.to_vec())
             .append(&self.propose_weight)
             .append(&self.vote_weight);
     }
@@ -53,16 +54,18 @@ impl rlp::Encodable for Validator {
 
 impl rlp::Decodable for Validator {
     fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
-        if !r.is_list() && r.size() != 3 {
+        if !r.is_list() && r.size() != 4 {
             return Err(rlp::DecoderError::RlpIncorrectListLen);
         }
 
         let address = rlp::decode(r.at(0)?.as_raw())?;
-        let propose_weight = r.at(1)?.as_val()?;
-        let vote_weight = r.at(2)?.as_val()?;
+        let bls_pub_key = Bytes::from(r.at(1)?.data()?);
+        let propose_weight = r.at(2)?.as_val()?;
+        let vote_weight = r.at(3)?.as_val()?;
 
         Ok(Validator {
             address,
+            bls_pub_key,
             propose_weight,
             vote_weight,
         })
@@ -71,11 +74,12 @@ impl rlp::Decodable for Validator {
 
 impl rlp::Encodable for EpochHeader {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
-        s.begin_list(14)
+        s.begin_list(15)
             .append(&self.chain_id)
             .append_list(&self.confirm_root)
             .append(&self.cycles_used)
             .append(&self.epoch_id)
+            .append(&self.exec_epoch_id)
             .append(&self.logs_bloom)
             .append(&self.order_root)
             .append(&self.pre_hash)
@@ -91,7 +95,7 @@ impl rlp::Encodable for EpochHeader {
 
 impl rlp::Decodable for EpochHeader {
     fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
-        if !r.is_list() && r.size() != 14 {
+        if !r.is_list() && r.size() != 15 {
             return Err(rlp::DecoderError::RlpIncorrectListLen);
         }
 
@@ -99,16 +103,17 @@ impl rlp::Decodable for EpochHeader {
         let confirm_root: Vec<Hash> = rlp::decode_list(r.at(1)?.as_raw());
         let cycles_used: u64 = r.at(2)?.as_val()?;
         let epoch_id: u64 = r.at(3)?.as_val()?;
-        let logs_bloom: Bloom = rlp::decode(r.at(4)?.as_raw())?;
-        let order_root = rlp::decode(r.at(5)?.as_raw())?;
-        let pre_hash = rlp::decode(r.at(6)?.as_raw())?;
-        let proof: Proof = rlp::decode(r.at(7)?.as_raw())?;
-        let proposer = rlp::decode(r.at(8)?.as_raw())?;
-        let receipt_root: Vec<Hash> = rlp::decode_list(r.at(9)?.as_raw());
-        let state_root = rlp::decode(r.at(10)?.as_raw())?;
-        let timestamp: u64 = r.at(11)?.as_val()?;
-        let validator_version: u64 = r.at(12)?.as_val()?;
-        let validators: Vec<Validator> = rlp::decode_list(r.at(13)?.as_raw());
+        let exec_epoch_id: u64 = r.at(4)?.as_val()?;
+        let logs_bloom: Bloom = rlp::decode(r.at(5)?.as_raw())?;
+        let order_root = rlp::decode(r.at(6)?.as_raw())?;
+        let pre_hash = rlp::decode(r.at(7)?.as_raw())?;
+        let proof: Proof = rlp::decode(r.at(8)?.as_raw())?;
+        let proposer = rlp::decode(r.at(9)?.as_raw())?;
+        let receipt_root: Vec<Hash> = rlp::decode_list(r.at(10)?.as_raw());
+        let state_root = rlp::decode(r.at(11)?.as_raw())?;
+        let timestamp: u64 = r.at(12)?.as_val()?;
+        let validator_version: u64 = r.at(13)?.as_val()?;
+        let validators: Vec<Validator> = rlp::decode_list(r.at(14)?.as_raw());
 
         Ok(EpochHeader {
             chain_id,
@@ -118,6 +123,7 @@ impl rlp::Decodable for EpochHeader {
             logs_bloom,
             order_root,
             confirm_root,
+            exec_epoch_id,
             state_root,
             receipt_root,
             cycles_used,
@@ -0,0 +1,56 @@
+use bytes::Bytes;
+
+use crate::fixed_codec::{FixedCodecError, ProtocolFixedCodec};
+use crate::types::evidence::{Evidence, EvidenceType};
+use crate::{impl_default_fixed_codec_for, ProtocolResult};
+
+// Impl ProtocolFixedCodec trait for types
+impl_default_fixed_codec_for!(evidence, [Evidence]);
+
+impl rlp::Encodable for Evidence {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let type_flag: u8 = match self.evidence_type {
+            EvidenceType::Proposal => 0,
+            EvidenceType::Prevote => 1,
+            EvidenceType::Precommit => 2,
+        };
+
+        s.begin_list(6)
+            .append(&self.epoch_id)
+            .append(&type_flag)
+            .append(&self.first.to_vec())
+            .append(&self.round)
+            .append(&self.second.to_vec())
+            .append(&self.validator);
+    }
+}
+
+impl rlp::Decodable for Evidence {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() && r.size() != 6 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let epoch_id = r.at(0)?.as_val()?;
+        let type_flag: u8 = r.at(1)?.as_val()?;
+        let evidence_type = match type_flag {
+            0 => EvidenceType::Proposal,
+            1 => EvidenceType::Prevote,
+            2 => EvidenceType::Precommit,
+            _ => return Err(rlp::DecoderError::Custom("invalid evidence type flag")),
+        };
+        let first = Bytes::from(r.at(2)?.data()?);
+        let round = r.at(3)?.as_val()?;
+        let second = Bytes::from(r.at(4)?.data()?);
+        let validator = rlp::decode(r.at(5)?.as_raw())?;
+
+        Ok(Evidence {
+            validator,
+            epoch_id,
+            round,
+            evidence_type,
+            first,
+            second,
+        })
+    }
+}
@@ -1,17 +1,23 @@
 use bytes::Bytes;
 
 use crate::fixed_codec::{FixedCodecError, ProtocolFixedCodec};
-use crate::types::genesis::{Genesis, GenesisStateAlloc, GenesisStateAsset, GenesisSystemToken};
+use crate::types::genesis::{
+    Genesis, GenesisAsset, GenesisConsensus, GenesisReward, GenesisStateAlloc, GenesisStateAsset,
+    GenesisValidator,
+};
 use crate::{impl_default_fixed_codec_for, ProtocolResult};
 
 impl_default_fixed_codec_for!(genesis, [
     Genesis,
+    GenesisAsset,
+    GenesisConsensus,
+    GenesisReward,
     GenesisStateAlloc,
     GenesisStateAsset,
-    GenesisSystemToken
+    GenesisValidator
 ]);
 
-impl rlp::Encodable for GenesisSystemToken {
+impl rlp::Encodable for GenesisAsset {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
         s.begin_list(4)
             .append(&self.code.as_bytes())
@@ -21,7 +27,7 @@ impl rlp::Encodable for GenesisSystemToken {
     }
 }
 
-impl rlp::Decodable for GenesisSystemToken {
+impl rlp::Decodable for GenesisAsset {
     fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
         if !r.is_list() && r.size() != 4 {
             return Err(rlp::DecoderError::RlpIncorrectListLen);
@@ -42,7 +48,7 @@ impl rlp::Decodable for GenesisSystemToken {
         let symbol = String::from_utf8(values[3].to_vec())
             .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
 
-        Ok(GenesisSystemToken {
+        Ok(GenesisAsset {
             code,
             name,
             supply,
@@ -51,6 +57,93 @@ impl rlp::Decodable for GenesisSystemToken {
     }
 }
 
+impl rlp::Encodable for GenesisConsensus {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(3)
+            .append(&self.cycles_limit)
+            .append(&self.interval)
+            .append(&self.reward);
+    }
+}
+
+impl rlp::Decodable for GenesisConsensus {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() && r.size() != 3 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let cycles_limit = r.at(0)?.as_val()?;
+        let interval = r.at(1)?.as_val()?;
+        let reward = rlp::decode(r.at(2)?.as_raw())?;
+
+        Ok(GenesisConsensus {
+            cycles_limit,
+            interval,
+            reward,
+        })
+    }
+}
+
+impl rlp::Encodable for GenesisReward {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2)
+            .append(&self.issuance)
+            .append(&self.proposer_ratio);
+    }
+}
+
+impl rlp::Decodable for GenesisReward {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() && r.size() != 2 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let issuance = r.at(0)?.as_val()?;
+        let proposer_ratio = r.at(1)?.as_val()?;
+
+        Ok(GenesisReward {
+            issuance,
+            proposer_ratio,
+        })
+    }
+}
+
+impl rlp::Encodable for GenesisValidator {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(5)
+            .append(&self.address.as_bytes())
+            .append(&self.bls_pub_key.as_bytes())
+            .append(&self.bls_pop.as_bytes())
+            .append(&self.propose_weight)
+            .append(&self.vote_weight);
+    }
+}
+
+impl rlp::Decodable for GenesisValidator {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() && r.size() != 5 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let address = String::from_utf8(r.at(0)?.data()?.to_vec())
+            .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
+        let bls_pub_key = String::from_utf8(r.at(1)?.data()?.to_vec())
+            .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
+        let bls_pop = String::from_utf8(r.at(2)?.data()?.to_vec())
+            .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
+        let propose_weight = r.at(3)?.as_val()?;
+        let vote_weight = r.at(4)?.as_val()?;
+
+        Ok(GenesisValidator {
+            address,
+            bls_pub_key,
+            bls_pop,
+            propose_weight,
+            vote_weight,
+        })
+    }
+}
+
 impl rlp::Encodable for GenesisStateAsset {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
         s.begin_list(2)
@@ -98,31 +191,44 @@ impl rlp::Decodable for GenesisStateAlloc {
 
 impl rlp::Encodable for Genesis {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
-        s.begin_list(4)
+        s.begin_list(8)
+            .append_list(&self.assets)
+            .append(&self.chain_id.as_bytes())
+            .append(&self.consensus)
             .append(&self.prevhash.as_bytes())
             .append_list(&self.state_alloc)
             .append(&self.system_token)
-            .append(&self.timestamp);
+            .append(&self.timestamp)
+            .append_list(&self.validators);
     }
 }
 
 impl rlp::Decodable for Genesis {
     fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
-        if !r.is_list() && r.size() != 4 {
+        if !r.is_list() && r.size() != 8 {
             return Err(rlp::DecoderError::RlpIncorrectListLen);
         }
 
-        let prevhash = String::from_utf8(r.at(0)?.data()?.to_vec())
+        let assets: Vec<GenesisAsset> = rlp::decode_list(r.at(0)?.as_raw());
+        let chain_id = String::from_utf8(r.at(1)?.data()?.to_vec())
+            .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
+        let consensus = rlp::decode(r.at(2)?.as_raw())?;
+        let prevhash = String::from_utf8(r.at(3)?.data()?.to_vec())
             .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
-        let state_alloc: Vec<GenesisStateAlloc> = rlp::decode_list(r.at(1)?.as_raw());
-        let system_token = rlp::decode(r.at(2)?.as_raw())?;
-        let timestamp = r.at(3)?.as_val()?;
+        let state_alloc: Vec<GenesisStateAlloc> = rlp::decode_list(r.at(4)?.as_raw());
+        let system_token = rlp::decode(r.at(5)?.as_raw())?;
+        let timestamp = r.at(6)?.as_val()?;
+        let validators: Vec<GenesisValidator> = rlp::decode_list(r.at(7)?.as_raw());
 
         Ok(Genesis {
+            chain_id,
             timestamp,
             prevhash,
+            consensus,
+            validators,
             system_token,
             state_alloc,
+            assets,
         })
     }
 }
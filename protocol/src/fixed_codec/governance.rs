@@ -0,0 +1,69 @@
+use bytes::Bytes;
+
+use crate::fixed_codec::{FixedCodecError, ProtocolFixedCodec};
+use crate::types::epoch::Validator;
+use crate::types::governance::{ValidatorProposal, ValidatorSet};
+use crate::types::primitive::{Hash, UserAddress};
+use crate::{impl_default_fixed_codec_for, ProtocolResult};
+
+impl_default_fixed_codec_for!(governance, [ValidatorSet, ValidatorProposal]);
+
+impl rlp::Encodable for ValidatorSet {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(3)
+            .append(&self.activate_epoch_id)
+            .append_list(&self.validators)
+            .append(&self.version);
+    }
+}
+
+impl rlp::Decodable for ValidatorSet {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() && r.size() != 3 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let activate_epoch_id = r.at(0)?.as_val()?;
+        let validators: Vec<Validator> = rlp::decode_list(r.at(1)?.as_raw());
+        let version = r.at(2)?.as_val()?;
+
+        Ok(ValidatorSet {
+            version,
+            activate_epoch_id,
+            validators,
+        })
+    }
+}
+
+impl rlp::Encodable for ValidatorProposal {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(5)
+            .append_list(&self.approvals)
+            .append(&self.base_version)
+            .append(&self.id)
+            .append(&self.proposer)
+            .append(&self.validator_set);
+    }
+}
+
+impl rlp::Decodable for ValidatorProposal {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() && r.size() != 5 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let approvals: Vec<UserAddress> = rlp::decode_list(r.at(0)?.as_raw());
+        let base_version = r.at(1)?.as_val()?;
+        let id: Hash = rlp::decode(r.at(2)?.as_raw())?;
+        let proposer = rlp::decode(r.at(3)?.as_raw())?;
+        let validator_set = rlp::decode(r.at(4)?.as_raw())?;
+
+        Ok(ValidatorProposal {
+            id,
+            proposer,
+            base_version,
+            validator_set,
+            approvals,
+        })
+    }
+}
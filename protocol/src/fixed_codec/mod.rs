@@ -1,7 +1,9 @@
 #[macro_use]
 mod r#macro;
 pub mod epoch;
+pub mod evidence;
 pub mod genesis;
+pub mod governance;
 pub mod primitive;
 pub mod receipt;
 #[cfg(test)]
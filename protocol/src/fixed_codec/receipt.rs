@@ -2,11 +2,11 @@ use bytes::Bytes;
 
 use crate::fixed_codec::{FixedCodecError, ProtocolFixedCodec};
 use crate::types::primitive::{Balance, ContractType, Fee};
-use crate::types::receipt::{Receipt, ReceiptResult};
+use crate::types::receipt::{Receipt, ReceiptResult, Reward};
 use crate::{impl_default_fixed_codec_for, ProtocolResult};
 
 // Impl ProtocolFixedCodec trait for types
-impl_default_fixed_codec_for!(receipt, [Receipt, ReceiptResult]);
+impl_default_fixed_codec_for!(receipt, [Receipt, ReceiptResult, Reward]);
 
 impl rlp::Encodable for Receipt {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
@@ -45,6 +45,34 @@ const TRANSFER_RESULT_FLAG: u8 = 0;
 const DEPLOY_RESULT_FLAG: u8 = 1;
 const CALL_RESULT_FLAG: u8 = 2;
 const FAIL_RESULT_FLAG: u8 = 3;
+const REWARD_RESULT_FLAG: u8 = 4;
+
+impl rlp::Encodable for Reward {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(3)
+            .append(&self.amount.to_bytes_be())
+            .append(&self.asset_id)
+            .append(&self.receiver);
+    }
+}
+
+impl rlp::Decodable for Reward {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() && r.size() != 3 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let amount = Balance::from_bytes_be(r.at(0)?.data()?);
+        let asset_id = rlp::decode(r.at(1)?.as_raw())?;
+        let receiver = rlp::decode(r.at(2)?.as_raw())?;
+
+        Ok(Reward {
+            receiver,
+            asset_id,
+            amount,
+        })
+    }
+}
 
 impl rlp::Encodable for ReceiptResult {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
@@ -93,6 +121,11 @@ impl rlp::Encodable for ReceiptResult {
                     .append(&system.as_bytes())
                     .append(&user.as_bytes());
             }
+            ReceiptResult::Reward { rewards } => {
+                s.begin_list(2)
+                    .append(&REWARD_RESULT_FLAG)
+                    .append_list(rewards);
+            }
             _ => {}
         }
     }
@@ -152,6 +185,11 @@ impl rlp::Decodable for ReceiptResult {
 
                 Ok(ReceiptResult::Fail { system, user })
             }
+            REWARD_RESULT_FLAG => {
+                let rewards: Vec<Reward> = rlp::decode_list(r.at(1)?.as_raw());
+
+                Ok(ReceiptResult::Reward { rewards })
+            }
             _ => Err(rlp::DecoderError::RlpListLenWithZeroPrefix),
         }
     }
@@ -46,12 +46,21 @@ fn test_fixed_codec() {
     test_eq!(epoch, Validator, mock_validator);
     test_eq!(epoch, EpochId, mock_epoch_id);
 
+    test_eq!(evidence, Evidence, mock_evidence, EvidenceType::Proposal);
+    test_eq!(evidence, Evidence, mock_evidence, EvidenceType::Prevote);
+    test_eq!(evidence, Evidence, mock_evidence, EvidenceType::Precommit);
+
     test_eq!(receipt, Receipt, mock_receipt, ReceiptType::Transfer);
     test_eq!(receipt, Receipt, mock_receipt, ReceiptType::Deploy);
     test_eq!(receipt, Receipt, mock_receipt, ReceiptType::Call);
     test_eq!(receipt, Receipt, mock_receipt, ReceiptType::Fail);
+    test_eq!(receipt, Receipt, mock_receipt, ReceiptType::Reward);
+    test_eq!(receipt, Reward, mock_reward);
 
     test_eq!(genesis, Genesis, mock_genesis);
+
+    test_eq!(governance, ValidatorSet, mock_validator_set);
+    test_eq!(governance, ValidatorProposal, mock_validator_proposal);
 }
 
 #[test]
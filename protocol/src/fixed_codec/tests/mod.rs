@@ -7,12 +7,18 @@ use num_traits::FromPrimitive;
 use rand::random;
 
 use crate::types::epoch::{Epoch, EpochHeader, EpochId, Pill, Proof, Validator};
-use crate::types::genesis::{Genesis, GenesisStateAlloc, GenesisStateAsset, GenesisSystemToken};
+use crate::types::evidence::{EpochEvidences, Evidence, EvidenceType};
+use crate::types::genesis::{
+    Genesis, GenesisAsset, GenesisConsensus, GenesisReward, GenesisStateAlloc, GenesisStateAsset,
+    GenesisValidator,
+};
+use crate::types::governance::{ValidatorProposal, ValidatorSet};
 use crate::types::primitive::{
-    Account, Asset, AssetID, AssetInfo, Balance, ContractAccount, ContractAddress, ContractType,
-    Fee, Hash, MerkleRoot, UserAccount, UserAddress,
+    Account, Address, Asset, AssetID, AssetInfo, Balance, ContractAccount, ContractAddress,
+    ContractType, Fee, Hash, MerkleRoot, UserAccount, UserAddress,
 };
-use crate::types::receipt::{Receipt, ReceiptResult};
+use crate::types::receipt::{Receipt, ReceiptResult, Reward};
+use crate::types::trace::{ContractTrace, StateDiff, TransactionTrace};
 use crate::types::transaction::{
     CarryingAsset, RawTransaction, SignedTransaction, TransactionAction,
 };
@@ -23,6 +29,7 @@ pub enum ReceiptType {
     Deploy,
     Call,
     Fail,
+    Reward,
 }
 
 pub enum AType {
@@ -138,6 +145,17 @@ pub fn mock_result(rtype: ReceiptType) -> ReceiptResult {
             system: "system".to_string(),
             user:   "user".to_string(),
         },
+        ReceiptType::Reward => ReceiptResult::Reward {
+            rewards: vec![mock_reward(), mock_reward()],
+        },
+    }
+}
+
+pub fn mock_reward() -> Reward {
+    Reward {
+        receiver: mock_account_address(),
+        asset_id: mock_asset_id(),
+        amount:   mock_balance(),
     }
 }
 
@@ -211,6 +229,7 @@ pub fn mock_sign_tx(atype: AType) -> SignedTransaction {
 pub fn mock_validator() -> Validator {
     Validator {
         address:        mock_account_address(),
+        bls_pub_key:    get_random_bytes(97),
         propose_weight: 1u8,
         vote_weight:    1u8,
     }
@@ -239,6 +258,7 @@ pub fn mock_epoch_header() -> EpochHeader {
         logs_bloom:        Default::default(),
         order_root:        mock_merkle_root(),
         confirm_root:      vec![mock_hash(), mock_hash()],
+        exec_epoch_id:     40,
         state_root:        mock_merkle_root(),
         receipt_root:      vec![mock_hash(), mock_hash()],
         cycles_used:       999_999,
@@ -268,15 +288,106 @@ pub fn mock_pill(order_size: usize, propose_size: usize) -> Pill {
     }
 }
 
+// #####################
+// Mock Governance
+// #####################
+
+pub fn mock_validator_set() -> ValidatorSet {
+    ValidatorSet {
+        version:           2,
+        activate_epoch_id: 100,
+        validators:        vec![mock_validator(), mock_validator()],
+    }
+}
+
+pub fn mock_validator_proposal() -> ValidatorProposal {
+    ValidatorProposal {
+        id:            mock_hash(),
+        proposer:      mock_account_address(),
+        base_version:  1,
+        validator_set: mock_validator_set(),
+        approvals:     vec![mock_account_address(), mock_account_address()],
+    }
+}
+
+// #####################
+// Mock Trace
+// #####################
+
+pub fn mock_state_diff(before: Option<Bytes>) -> StateDiff {
+    StateDiff {
+        key: get_random_bytes(32),
+        before,
+        after: get_random_bytes(64),
+    }
+}
+
+pub fn mock_evidence(evidence_type: EvidenceType) -> Evidence {
+    Evidence {
+        validator: mock_account_address(),
+        epoch_id: 7,
+        round: 2,
+        evidence_type,
+        first: get_random_bytes(120),
+        second: get_random_bytes(120),
+    }
+}
+
+pub fn mock_epoch_evidences() -> EpochEvidences {
+    EpochEvidences {
+        epoch_id:  7,
+        evidences: vec![
+            mock_evidence(EvidenceType::Proposal),
+            mock_evidence(EvidenceType::Precommit),
+        ],
+    }
+}
+
+pub fn mock_transaction_trace() -> TransactionTrace {
+    TransactionTrace {
+        tx_hash:   mock_hash(),
+        epoch_id:  13,
+        contracts: vec![
+            ContractTrace {
+                address: Address::User(mock_account_address()),
+                diffs:   vec![mock_state_diff(None)],
+            },
+            ContractTrace {
+                address: Address::Contract(mock_contract_address()),
+                diffs:   vec![
+                    mock_state_diff(Some(get_random_bytes(64))),
+                    mock_state_diff(None),
+                ],
+            },
+        ],
+    }
+}
+
 // #####################
 // Mock Genesis
 // #####################
 
 pub fn mock_genesis() -> Genesis {
     Genesis {
+        chain_id:     "chainidtest".to_string(),
         timestamp:    99,
         prevhash:     "prevhashtest".to_string(),
-        system_token: GenesisSystemToken {
+        consensus:    GenesisConsensus {
+            cycles_limit: 999,
+            interval:     3000,
+            reward:       GenesisReward {
+                issuance:       10,
+                proposer_ratio: 40,
+            },
+        },
+        validators:   vec![GenesisValidator {
+            address:        "validatortest".to_string(),
+            bls_pub_key:    "blspubkeytest".to_string(),
+            bls_pop:        "blspoptest".to_string(),
+            propose_weight: 1,
+            vote_weight:    2,
+        }],
+        system_token: GenesisAsset {
             code:   "codetest".to_string(),
             name:   "nametest".to_string(),
             symbol: "symbol".to_string(),
@@ -310,6 +421,13 @@ pub fn mock_genesis() -> Genesis {
                 ],
             },
         ],
+
+        assets: vec![GenesisAsset {
+            code:   "othercodetest".to_string(),
+            name:   "othernametest".to_string(),
+            symbol: "othersymbol".to_string(),
+            supply: 8,
+        }],
     }
 }
 
@@ -1,7 +1,10 @@
 use async_trait::async_trait;
 
-use crate::traits::Context;
-use crate::types::{Address, AssetID, Balance, Epoch, Hash, Receipt, SignedTransaction};
+use crate::traits::{ConsensusStatus, Context, MemPoolStats, TxStatus};
+use crate::types::{
+    Address, AssetID, Balance, Epoch, Evidence, Hash, Proof, Receipt, SignedTransaction,
+    TransactionTrace, UserAddress,
+};
 use crate::ProtocolResult;
 
 #[async_trait]
@@ -14,8 +17,16 @@ pub trait APIAdapter: Send + Sync {
 
     async fn get_epoch_by_id(&self, ctx: Context, epoch_id: Option<u64>) -> ProtocolResult<Epoch>;
 
+    async fn get_proof(&self, ctx: Context, epoch_id: Option<u64>) -> ProtocolResult<Proof>;
+
     async fn get_receipt_by_tx_hash(&self, ctx: Context, tx_hash: Hash) -> ProtocolResult<Receipt>;
 
+    async fn get_trace_by_tx_hash(
+        &self,
+        ctx: Context,
+        tx_hash: Hash,
+    ) -> ProtocolResult<TransactionTrace>;
+
     async fn get_balance(
         &self,
         ctx: Context,
@@ -23,4 +34,19 @@ pub trait APIAdapter: Send + Sync {
         id: &AssetID,
         epoch_id: Option<u64>,
     ) -> ProtocolResult<Balance>;
+
+    async fn get_evidences(&self, ctx: Context, epoch_id: u64) -> ProtocolResult<Vec<Evidence>>;
+
+    async fn get_consensus_status(&self, ctx: Context) -> ProtocolResult<ConsensusStatus>;
+
+    async fn get_transaction_status(&self, ctx: Context, tx_hash: Hash)
+        -> ProtocolResult<TxStatus>;
+
+    async fn get_pending_transactions(
+        &self,
+        ctx: Context,
+        address: UserAddress,
+    ) -> ProtocolResult<Vec<SignedTransaction>>;
+
+    async fn get_mempool_stats(&self, ctx: Context) -> ProtocolResult<MemPoolStats>;
 }
@@ -1,11 +1,13 @@
 use async_trait::async_trait;
+use bytes::Bytes;
 use creep::Context;
 
+use crate::traits::executor::{EpochReward, ExecutorExecResp, RewardSchedule};
 use crate::types::{
-    Address, Bloom, Epoch, Hash, MerkleRoot, Proof, Receipt, SignedTransaction, UserAddress,
-    Validator,
+    Address, Bloom, Epoch, Evidence, Hash, MerkleRoot, Proof, Receipt, SignedTransaction,
+    TransactionTrace, UserAddress, Validator, ValidatorSet,
 };
-use crate::{traits::executor::ExecutorExecResp, traits::mempool::MixedTxHashes, ProtocolResult};
+use crate::{traits::mempool::MixedTxHashes, ProtocolResult};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum MessageTarget {
@@ -26,16 +28,59 @@ pub struct CurrentConsensusStatus {
     pub epoch_id:           u64,
     pub prev_hash:          Hash,
     pub logs_bloom:         Bloom,
-    pub order_root:         MerkleRoot,
-    pub confirm_root:       Vec<MerkleRoot>,
+    pub exec_epoch_id:      u64,
     pub state_root:         MerkleRoot,
-    pub receipt_root:       Vec<MerkleRoot>,
     pub cycles_used:        u64,
     pub proof:              Proof,
     pub validators:         Vec<Validator>,
+    pub validator_version:  u64,
+    pub reward_schedule:    RewardSchedule,
     pub consensus_interval: u64,
 }
 
+/// The step of the current round, it goes forward as the node votes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConsensusStep {
+    Propose,
+    Prevote,
+    Precommit,
+    Commit,
+}
+
+/// The proposal locked by a prevote QC.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockedProposal {
+    pub round:      u64,
+    pub epoch_hash: Hash,
+}
+
+/// The votes of a validator in the current round. An empty hash is a vote for
+/// nil.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorVotes {
+    pub address:   UserAddress,
+    pub prevote:   Option<Bytes>,
+    pub precommit: Option<Bytes>,
+}
+
+/// The state of the consensus protocol observed by the node. The votes only
+/// contain the votes of this node and the votes sent to it as the leader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusStatus {
+    pub epoch_id: u64,
+    pub round:    u64,
+    pub step:     ConsensusStep,
+    pub proposal: Option<Hash>,
+    pub locked:   Option<LockedProposal>,
+    pub votes:    Vec<ValidatorVotes>,
+    pub syncing:  bool,
+}
+
+/// Inspect the running consensus for diagnosis.
+pub trait ConsensusMonitor: Send + Sync {
+    fn consensus_status(&self) -> ConsensusStatus;
+}
+
 #[async_trait]
 pub trait Consensus: Send + Sync {
     /// Network set a received signed proposal to consensus.
@@ -95,6 +140,7 @@ pub trait ConsensusAdapter: Send + Sync {
         cycles_price: u64,
         coinbase: Address,
         signed_txs: Vec<SignedTransaction>,
+        reward: EpochReward,
     ) -> ProtocolResult<ExecutorExecResp>;
 
     /// Flush the given transactions in the mempool.
@@ -106,7 +152,10 @@ pub trait ConsensusAdapter: Send + Sync {
     /// Save some receipts to the database.
     async fn save_receipts(&self, ctx: Context, receipts: Vec<Receipt>) -> ProtocolResult<()>;
 
-    ///
+    /// Save the state diffs of some transactions to the database.
+    async fn save_traces(&self, ctx: Context, traces: Vec<TransactionTrace>) -> ProtocolResult<()>;
+
+    /// Save the proof of an epoch, it is also the latest proof.
     async fn save_proof(&self, ctx: Context, proof: Proof) -> ProtocolResult<()>;
 
     /// Save some signed transactions to the database.
@@ -116,12 +165,18 @@ pub trait ConsensusAdapter: Send + Sync {
         signed_txs: Vec<SignedTransaction>,
     ) -> ProtocolResult<()>;
 
-    /// Get the validator list of the given last epoch.
+    /// Save the evidence of a validator signing twice to the database.
+    async fn save_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()>;
+
+    /// Get the validator set which takes effect at the given epoch ID from the
+    /// governance contract in the world state of `state_root`. Return `None`
+    /// if the governance contract has not been initialized.
     async fn get_last_validators(
         &self,
-        ctx: Context,
+        node_info: NodeInfo,
+        state_root: MerkleRoot,
         epoch_id: u64,
-    ) -> ProtocolResult<Vec<Validator>>;
+    ) -> ProtocolResult<Option<ValidatorSet>>;
 
     /// Get the current epoch ID from storage.
     async fn get_current_epoch_id(&self, ctx: Context) -> ProtocolResult<u64>;
@@ -129,6 +184,20 @@ pub trait ConsensusAdapter: Send + Sync {
     /// Pull some epochs from other nodes from `begin` to `end`.
     async fn pull_epoch(&self, ctx: Context, epoch_id: u64, end: &str) -> ProtocolResult<Epoch>;
 
+    /// Pull at most `count` consecutive epochs starting from `begin` from
+    /// other nodes in one request. The response may be shorter if the peer
+    /// does not have all of them.
+    async fn pull_epochs(
+        &self,
+        ctx: Context,
+        begin: u64,
+        count: u64,
+        end: &str,
+    ) -> ProtocolResult<Vec<Epoch>>;
+
+    /// Pull the proof of the given epoch from other nodes.
+    async fn pull_proof(&self, ctx: Context, epoch_id: u64, end: &str) -> ProtocolResult<Proof>;
+
     /// Pull signed transactions corresponding to the given hashes from other
     /// nodes.
     async fn pull_txs(
@@ -140,4 +209,12 @@ pub trait ConsensusAdapter: Send + Sync {
 
     /// Get an epoch corresponding to the given epoch ID.
     async fn get_epoch_by_id(&self, ctx: Context, epoch_id: u64) -> ProtocolResult<Epoch>;
+
+    /// Get the signed transactions corresponding to the given hashes from
+    /// storage.
+    async fn get_signed_txs(
+        &self,
+        ctx: Context,
+        hashes: Vec<Hash>,
+    ) -> ProtocolResult<Vec<SignedTransaction>>;
 }
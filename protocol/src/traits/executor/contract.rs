@@ -1,5 +1,10 @@
+use bytes::Bytes;
+
 use crate::traits::executor::{ContractSchema, RcInvokeContext};
-use crate::types::{Account, Address, Asset, AssetID, Balance, ContractAddress, MerkleRoot};
+use crate::types::{
+    Account, Address, Asset, AssetID, Balance, ContractAddress, Hash, MerkleRoot, Validator,
+    ValidatorProposal, ValidatorSet,
+};
 use crate::ProtocolResult;
 
 // As the world state access layer, the ContractStateAdapter provides `cache`
@@ -53,6 +58,15 @@ pub trait BankContract<Adapter: ContractStateAdapter> {
     ) -> ProtocolResult<Asset>;
 
     fn get_asset(&self, ictx: RcInvokeContext, id: &AssetID) -> ProtocolResult<Asset>;
+
+    // Mark the asset as the native asset, which is used to pay the epoch
+    // rewards.
+    fn set_native_asset(&mut self, id: &AssetID) -> ProtocolResult<()>;
+
+    fn get_native_asset(&self) -> ProtocolResult<Option<AssetID>>;
+
+    // Increase the supply of the asset.
+    fn issue(&mut self, id: &AssetID, amount: Balance) -> ProtocolResult<Asset>;
 }
 
 pub trait AccountContract<Adapter: ContractStateAdapter> {
@@ -82,3 +96,35 @@ pub trait AccountContract<Adapter: ContractStateAdapter> {
 
     fn get_nonce(&self, address: &Address) -> ProtocolResult<u64>;
 }
+
+/// GovernanceContract manages the validator set in the `world state`.
+///
+/// A change of the validator set is proposed by one of the current validators
+/// and scheduled once it is approved by more than 2/3 of the vote weight. The
+/// scheduled set takes effect at the `activate_epoch_id` of the proposal.
+pub trait GovernanceContract<Adapter: ContractStateAdapter> {
+    // Set the initial validator set, only be called in genesis. The proofs of
+    // possession of the BLS keys are checked by the genesis.
+    fn init_validators(&mut self, validators: Vec<Validator>) -> ProtocolResult<ValidatorSet>;
+
+    // Every validator must come with the proof of possession of its BLS key,
+    // in the same order.
+    fn propose_validators(
+        &mut self,
+        ictx: RcInvokeContext,
+        validators: Vec<Validator>,
+        bls_pops: Vec<Bytes>,
+        activate_epoch_id: u64,
+    ) -> ProtocolResult<ValidatorProposal>;
+
+    fn approve_validators(
+        &mut self,
+        ictx: RcInvokeContext,
+        proposal_id: &Hash,
+    ) -> ProtocolResult<ValidatorProposal>;
+
+    // Get the validator set which is in effect at the given epoch id.
+    fn get_validators(&self, epoch_id: u64) -> ProtocolResult<Option<ValidatorSet>>;
+
+    fn get_proposal(&self, proposal_id: &Hash) -> ProtocolResult<ValidatorProposal>;
+}
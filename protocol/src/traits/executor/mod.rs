@@ -9,7 +9,7 @@ use bytes::Bytes;
 use crate::fixed_codec::ProtocolFixedCodec;
 use crate::types::{
     Address, AssetID, Balance, Bloom, CarryingAsset, ContractAddress, Fee, Genesis, Hash,
-    MerkleRoot, Receipt, SignedTransaction,
+    MerkleRoot, Receipt, SignedTransaction, TransactionTrace, Validator, ValidatorSet,
 };
 use crate::ProtocolResult;
 
@@ -17,10 +17,39 @@ pub trait TrieDB = cita_trie::DB;
 
 #[derive(Clone, Debug)]
 pub struct ExecutorExecResp {
-    pub receipts:        Vec<Receipt>,
+    pub receipts: Vec<Receipt>,
     pub all_cycles_used: Vec<Fee>,
-    pub logs_bloom:      Bloom,
-    pub state_root:      MerkleRoot,
+    pub logs_bloom: Bloom,
+    pub state_root: MerkleRoot,
+    /// The state diffs of every transaction, empty unless tracing is enabled.
+    pub traces: Vec<TransactionTrace>,
+}
+
+/// The reward schedule of an epoch. Both the issuance of the native asset and
+/// the fees are split between the proposer and the signers of the previous
+/// epoch proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RewardSchedule {
+    /// The amount of native asset issued for every epoch.
+    pub issuance: u64,
+    /// The percentage of rewards for the proposer, the rest are shared by the
+    /// signers in proportion to their vote weights.
+    pub proposer_ratio: u64,
+}
+
+impl Default for RewardSchedule {
+    fn default() -> Self {
+        RewardSchedule {
+            issuance:       0,
+            proposer_ratio: 100,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EpochReward {
+    pub schedule: RewardSchedule,
+    pub signers:  Vec<Validator>,
 }
 
 pub trait ExecutorFactory<DB: TrieDB>: Send + Sync {
@@ -37,9 +66,18 @@ pub trait ExecutorFactory<DB: TrieDB>: Send + Sync {
 pub trait Executor {
     fn create_genesis(&mut self, genesis: &Genesis) -> ProtocolResult<MerkleRoot>;
 
-    fn exec(&mut self, signed_txs: Vec<SignedTransaction>) -> ProtocolResult<ExecutorExecResp>;
+    fn exec(
+        &mut self,
+        signed_txs: Vec<SignedTransaction>,
+        reward: EpochReward,
+    ) -> ProtocolResult<ExecutorExecResp>;
+
+    /// Record the state diffs of every transaction executed afterwards.
+    fn enable_trace(&mut self);
 
     fn get_balance(&self, address: &Address, id: &AssetID) -> ProtocolResult<Balance>;
+
+    fn get_validators(&self, epoch_id: u64) -> ProtocolResult<Option<ValidatorSet>>;
 }
 
 #[derive(Clone, Debug)]
@@ -1,9 +1,19 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use creep::Context;
 
-use crate::types::{Hash, SignedTransaction};
+use crate::types::{AssetID, Balance, Hash, SignedTransaction, UserAddress};
 use crate::ProtocolResult;
 
+/// The assets which the pending transactions of a sender can spend, the fees
+/// are counted in cycles.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PendingSpend {
+    pub fee_cycles: HashMap<AssetID, u64>,
+    pub carried:    HashMap<AssetID, Balance>,
+}
+
 #[allow(dead_code)]
 pub struct MixedTxHashes {
     pub order_tx_hashes:   Vec<Hash>,
@@ -16,10 +26,45 @@ impl MixedTxHashes {
     }
 }
 
+/// Where a transaction is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TxStatus {
+    /// Waiting in the mempool to be packaged.
+    Pending,
+    /// Synchronized from the proposal of others.
+    Proposed,
+    /// Fetched for the order transactions of the proposal in consensus.
+    Ordered,
+    /// Committed in an epoch, only known by storage.
+    Committed,
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemPoolStats {
+    /// The number of transactions in the pool.
+    pub pool_size: usize,
+    /// The number of transactions fetched for the order transactions.
+    pub callback_size: usize,
+    /// The length of the queue for insertion.
+    pub incumbent_queue_len: usize,
+    /// The length of the other queue, which is only filled during package.
+    pub candidate_queue_len: usize,
+    /// The smallest timeout of the transactions in the pool.
+    pub oldest_timeout: Option<u64>,
+}
+
 #[async_trait]
 pub trait MemPool: Send + Sync {
     async fn insert(&self, ctx: Context, tx: SignedTransaction) -> ProtocolResult<()>;
 
+    /// Insert a batch of transactions, whose signatures are verified in
+    /// parallel. The transactions of invalid signatures are dropped.
+    async fn insert_batch(&self, ctx: Context, txs: Vec<SignedTransaction>) -> ProtocolResult<()>;
+
+    /// Pull the unknown transactions of the hashes announced by a peer.
+    async fn receive_tx_hashes(&self, ctx: Context, tx_hashes: Vec<Hash>) -> ProtocolResult<()>;
+
     async fn package(&self, ctx: Context, cycle_limit: u64) -> ProtocolResult<MixedTxHashes>;
 
     async fn flush(&self, ctx: Context, tx_hashes: Vec<Hash>) -> ProtocolResult<()>;
@@ -41,6 +86,17 @@ pub trait MemPool: Send + Sync {
         ctx: Context,
         propose_tx_hashes: Vec<Hash>,
     ) -> ProtocolResult<()>;
+
+    async fn get_tx_status(&self, ctx: Context, tx_hash: Hash) -> ProtocolResult<TxStatus>;
+
+    /// Get the transactions of the sender in the pool, in insertion order.
+    async fn get_pending_txs(
+        &self,
+        ctx: Context,
+        sender: UserAddress,
+    ) -> ProtocolResult<Vec<SignedTransaction>>;
+
+    async fn get_stats(&self, ctx: Context) -> ProtocolResult<MemPoolStats>;
 }
 
 #[async_trait]
@@ -51,13 +107,55 @@ pub trait MemPoolAdapter: Send + Sync {
         tx_hashes: Vec<Hash>,
     ) -> ProtocolResult<Vec<SignedTransaction>>;
 
+    /// Announce the hash of the transaction to the peers which haven't
+    /// announced it.
     async fn broadcast_tx(&self, ctx: Context, tx: SignedTransaction) -> ProtocolResult<()>;
 
+    /// Record the peer of the context as an announcer of the hashes, return
+    /// the hashes announced for the first time.
+    async fn record_announcers(
+        &self,
+        ctx: Context,
+        tx_hashes: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Hash>>;
+
+    /// Forget the peer of the context as an announcer of the hashes it failed
+    /// to serve. Return the contexts of the next announcers with the hashes to
+    /// pull from them.
+    async fn forget_announcers(
+        &self,
+        ctx: Context,
+        tx_hashes: Vec<Hash>,
+    ) -> ProtocolResult<Vec<(Context, Vec<Hash>)>>;
+
     async fn check_signature(&self, ctx: Context, tx: SignedTransaction) -> ProtocolResult<()>;
 
-    async fn check_transaction(&self, ctx: Context, tx: SignedTransaction) -> ProtocolResult<()>;
+    /// Verify the signatures of a batch, return the transactions with valid
+    /// signatures in the same order.
+    async fn check_signatures(
+        &self,
+        ctx: Context,
+        txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<Vec<SignedTransaction>>;
+
+    /// Check the transaction against the latest state, the sender must afford
+    /// it besides the pending spend of its pending transactions.
+    async fn check_transaction(
+        &self,
+        ctx: Context,
+        tx: SignedTransaction,
+        pending: PendingSpend,
+    ) -> ProtocolResult<()>;
 
     async fn check_storage_exist(&self, ctx: Context, tx_hash: Hash) -> ProtocolResult<()>;
 
     async fn get_latest_epoch_id(&self, ctx: Context) -> ProtocolResult<u64>;
+
+    /// Persist the accepted transactions, so that they can be reloaded after
+    /// restart.
+    async fn journal_txs(&self, ctx: Context, txs: Vec<SignedTransaction>) -> ProtocolResult<()>;
+
+    async fn remove_journal_txs(&self, ctx: Context, tx_hashes: Vec<Hash>) -> ProtocolResult<()>;
+
+    async fn load_journal_txs(&self, ctx: Context) -> ProtocolResult<Vec<SignedTransaction>>;
 }
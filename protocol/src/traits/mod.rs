@@ -7,8 +7,11 @@ mod storage;
 pub mod executor;
 
 pub use api::APIAdapter;
-pub use consensus::{Consensus, ConsensusAdapter, CurrentConsensusStatus, MessageTarget, NodeInfo};
-pub use mempool::{MemPool, MemPoolAdapter, MixedTxHashes};
+pub use consensus::{
+    Consensus, ConsensusAdapter, ConsensusMonitor, ConsensusStatus, ConsensusStep,
+    CurrentConsensusStatus, LockedProposal, MessageTarget, NodeInfo, ValidatorVotes,
+};
+pub use mempool::{MemPool, MemPoolAdapter, MemPoolStats, MixedTxHashes, PendingSpend, TxStatus};
 pub use network::{Gossip, MessageCodec, MessageHandler, Priority, Rpc};
 pub use storage::{Storage, StorageAdapter, StorageBatchModify, StorageCategory, StorageSchema};
 
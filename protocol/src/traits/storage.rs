@@ -3,15 +3,20 @@ use derive_more::Display;
 
 use crate::codec::ProtocolCodec;
 use crate::types::epoch::{Epoch, Proof};
+use crate::types::evidence::Evidence;
 use crate::types::receipt::Receipt;
-use crate::types::{Hash, SignedTransaction};
+use crate::types::{Hash, SignedTransaction, TransactionTrace};
 use crate::ProtocolResult;
 
-#[derive(Debug, Copy, Clone, Display)]
+#[derive(Debug, Copy, Clone, Display, PartialEq, Eq, Hash)]
 pub enum StorageCategory {
     Epoch,
     Receipt,
     SignedTransaction,
+    Trace,
+    Evidence,
+    Proof,
+    PendingTransaction,
 }
 
 pub trait StorageSchema {
@@ -29,8 +34,21 @@ pub trait Storage: Send + Sync {
 
     async fn insert_receipts(&self, receipts: Vec<Receipt>) -> ProtocolResult<()>;
 
+    async fn insert_traces(&self, traces: Vec<TransactionTrace>) -> ProtocolResult<()>;
+
     async fn update_latest_proof(&self, proof: Proof) -> ProtocolResult<()>;
 
+    async fn insert_proof(&self, proof: Proof) -> ProtocolResult<()>;
+
+    async fn insert_evidence(&self, evidence: Evidence) -> ProtocolResult<()>;
+
+    async fn insert_pending_transactions(
+        &self,
+        signed_txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()>;
+
+    async fn remove_pending_transactions(&self, tx_hashes: Vec<Hash>) -> ProtocolResult<()>;
+
     async fn get_transaction_by_hash(&self, tx_hash: Hash) -> ProtocolResult<SignedTransaction>;
 
     async fn get_transactions(&self, hashes: Vec<Hash>) -> ProtocolResult<Vec<SignedTransaction>>;
@@ -45,7 +63,15 @@ pub trait Storage: Send + Sync {
 
     async fn get_receipts(&self, hash: Vec<Hash>) -> ProtocolResult<Vec<Receipt>>;
 
+    async fn get_trace(&self, tx_hash: Hash) -> ProtocolResult<TransactionTrace>;
+
     async fn get_latest_proof(&self) -> ProtocolResult<Proof>;
+
+    async fn get_proof(&self, epoch_id: u64) -> ProtocolResult<Proof>;
+
+    async fn get_evidences(&self, epoch_id: u64) -> ProtocolResult<Vec<Evidence>>;
+
+    async fn get_pending_transactions(&self) -> ProtocolResult<Vec<SignedTransaction>>;
 }
 
 pub enum StorageBatchModify<S: StorageSchema> {
@@ -79,6 +105,9 @@ pub trait StorageAdapter: Send + Sync {
         Ok(vec)
     }
 
+    /// Get all the values of the schema, in no particular order.
+    async fn get_all<S: StorageSchema>(&self) -> ProtocolResult<Vec<<S as StorageSchema>::Value>>;
+
     async fn remove<S: StorageSchema>(&self, key: <S as StorageSchema>::Key) -> ProtocolResult<()>;
 
     async fn contains<S: StorageSchema>(
@@ -1,5 +1,9 @@
+use std::convert::TryFrom;
+
 use bytes::Bytes;
 
+use common_crypto::{BLS12381PublicKey, Crypto, BLS12381};
+
 use crate::types::{Bloom, Hash, MerkleRoot, UserAddress};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -17,6 +21,7 @@ pub struct EpochHeader {
     pub logs_bloom:        Bloom,
     pub order_root:        MerkleRoot,
     pub confirm_root:      Vec<MerkleRoot>,
+    pub exec_epoch_id:     u64,
     pub state_root:        MerkleRoot,
     pub receipt_root:      Vec<MerkleRoot>,
     pub cycles_used:       u64,
@@ -38,10 +43,34 @@ pub struct Proof {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Validator {
     pub address:        UserAddress,
+    pub bls_pub_key:    Bytes,
     pub propose_weight: u8,
     pub vote_weight:    u8,
 }
 
+impl Validator {
+    /// The message signed by the BLS private key of a validator to prove the
+    /// possession of it.
+    pub fn bls_pop_message(bls_pub_key: &Bytes) -> Hash {
+        Hash::digest(Bytes::from(
+            [b"bls_pop".as_ref(), bls_pub_key.as_ref()].concat(),
+        ))
+    }
+
+    pub fn is_valid_bls_pub_key(&self) -> bool {
+        BLS12381PublicKey::try_from(self.bls_pub_key.as_ref()).is_ok()
+    }
+
+    /// Votes are verified by the aggregated public key of the voters, so a key
+    /// must come with a proof of possession. Otherwise a key derived from the
+    /// keys of others could forge their votes.
+    pub fn verify_bls_pop(&self, pop: &Bytes) -> bool {
+        let message = Validator::bls_pop_message(&self.bls_pub_key).as_bytes();
+        self.is_valid_bls_pub_key()
+            && BLS12381::verify_signature(&message, pop, &self.bls_pub_key).is_ok()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Pill {
     pub epoch:          Epoch,
@@ -0,0 +1,31 @@
+use bytes::Bytes;
+
+use crate::types::primitive::UserAddress;
+
+/// The type of the consensus messages signed twice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EvidenceType {
+    Proposal,
+    Prevote,
+    Precommit,
+}
+
+/// Two distinct consensus messages signed by one validator for the same epoch,
+/// round and type. The messages are the rlp encoded signed proposals or votes
+/// of overlord, they can be verified by the BLS public key of the validator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Evidence {
+    pub validator:     UserAddress,
+    pub epoch_id:      u64,
+    pub round:         u64,
+    pub evidence_type: EvidenceType,
+    pub first:         Bytes,
+    pub second:        Bytes,
+}
+
+/// The evidences of the messages signed at an epoch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EpochEvidences {
+    pub epoch_id:  u64,
+    pub evidences: Vec<Evidence>,
+}
@@ -1,11 +1,74 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use bytes::Bytes;
+use derive_more::Display;
+use num_bigint::BigUint;
 use serde_derive::Deserialize;
 
+use crate::types::{
+    Address, Asset, AssetID, Balance, Bloom, ContractAddress, ContractType, Epoch, EpochHeader,
+    Hash, MerkleRoot, Proof, UserAddress, Validator, GENESIS_EPOCH_ID,
+};
+use crate::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 pub struct Genesis {
+    pub chain_id:     String,
     pub timestamp:    u64,
     pub prevhash:     String,
-    pub system_token: GenesisSystemToken,
+    pub consensus:    GenesisConsensus,
+    pub validators:   Vec<GenesisValidator>,
+    pub system_token: GenesisAsset,
     pub state_alloc:  Vec<GenesisStateAlloc>,
+
+    // assets registered besides the system token
+    #[serde(default)]
+    pub assets: Vec<GenesisAsset>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct GenesisConsensus {
+    pub cycles_limit: u64,
+    pub interval:     u64,
+
+    // every node must pay the same rewards, so they are fixed by the genesis
+    #[serde(default)]
+    pub reward: GenesisReward,
+}
+
+/// The reward schedule of every epoch, the proposer ratio is a percentage.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct GenesisReward {
+    #[serde(default)]
+    pub issuance: u64,
+    #[serde(default = "default_proposer_ratio")]
+    pub proposer_ratio: u64,
+}
+
+fn default_proposer_ratio() -> u64 {
+    100
+}
+
+impl Default for GenesisReward {
+    fn default() -> Self {
+        GenesisReward {
+            issuance:       0,
+            proposer_ratio: default_proposer_ratio(),
+        }
+    }
+}
+
+/// The BLS public key verifies the consensus votes of the validator, and the
+/// BLS proof of possession is the signature of the key on
+/// `Validator::bls_pop_message`, they are hex strings.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct GenesisValidator {
+    pub address:        String,
+    pub bls_pub_key:    String,
+    pub bls_pop:        String,
+    pub propose_weight: u8,
+    pub vote_weight:    u8,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
@@ -14,6 +77,7 @@ pub struct GenesisStateAlloc {
     pub assets:  Vec<GenesisStateAsset>,
 }
 
+/// The balance is a decimal string, or a hex string with the `0x` prefix.
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 pub struct GenesisStateAsset {
     pub asset_id: String,
@@ -21,39 +85,540 @@ pub struct GenesisStateAsset {
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
-pub struct GenesisSystemToken {
+pub struct GenesisAsset {
     pub code:   String,
     pub name:   String,
     pub symbol: String,
     pub supply: u64,
 }
 
+impl Genesis {
+    pub fn chain_id(&self) -> ProtocolResult<Hash> {
+        Hash::from_hex(&self.chain_id).map_err(|_| {
+            GenesisError::InvalidHash {
+                field: "chain_id",
+                value: self.chain_id.clone(),
+            }
+            .into()
+        })
+    }
+
+    pub fn validators(&self) -> ProtocolResult<Vec<Validator>> {
+        self.validators
+            .iter()
+            .map(|v| {
+                let address = UserAddress::from_hex(&v.address).map_err(|_| {
+                    GenesisError::InvalidAddress {
+                        field:   "validators",
+                        address: v.address.clone(),
+                    }
+                })?;
+                let bls_pub_key =
+                    hex::decode(&v.bls_pub_key).map_err(|_| GenesisError::InvalidBlsPubKey {
+                        address: v.address.clone(),
+                    })?;
+                let validator = Validator {
+                    address,
+                    bls_pub_key: Bytes::from(bls_pub_key),
+                    propose_weight: v.propose_weight,
+                    vote_weight: v.vote_weight,
+                };
+
+                if !validator.is_valid_bls_pub_key() {
+                    return Err(GenesisError::InvalidBlsPubKey {
+                        address: v.address.clone(),
+                    }
+                    .into());
+                }
+                let bls_pop = hex::decode(&v.bls_pop).unwrap_or_default();
+                if !validator.verify_bls_pop(&Bytes::from(bls_pop)) {
+                    return Err(GenesisError::InvalidBlsPop {
+                        address: v.address.clone(),
+                    }
+                    .into());
+                }
+
+                Ok(validator)
+            })
+            .collect()
+    }
+
+    /// All assets registered in genesis, the system token comes first.
+    pub fn all_assets(&self) -> impl Iterator<Item = &GenesisAsset> {
+        std::iter::once(&self.system_token).chain(self.assets.iter())
+    }
+
+    /// Check the genesis before creating the chain from it, so that a bad
+    /// genesis file is rejected with the field at fault.
+    pub fn validate(&self) -> ProtocolResult<()> {
+        let chain_id = self.chain_id()?;
+        Hash::from_hex(&self.prevhash).map_err(|_| GenesisError::InvalidHash {
+            field: "prevhash",
+            value: self.prevhash.clone(),
+        })?;
+
+        if self.consensus.cycles_limit == 0 {
+            return Err(GenesisError::InvalidConsensus("cycles_limit is zero").into());
+        }
+        if self.consensus.interval == 0 {
+            return Err(GenesisError::InvalidConsensus("interval is zero").into());
+        }
+        if self.consensus.reward.proposer_ratio > 100 {
+            return Err(GenesisError::InvalidConsensus("proposer_ratio exceeds 100").into());
+        }
+
+        let validators = self.validators()?;
+        if validators.is_empty() {
+            return Err(GenesisError::EmptyValidators.into());
+        }
+        let mut addresses = HashSet::with_capacity(validators.len());
+        for validator in validators.iter() {
+            if !addresses.insert(validator.address.clone()) {
+                return Err(GenesisError::DuplicateValidator {
+                    address: validator.address.as_hex(),
+                }
+                .into());
+            }
+        }
+        if validators.iter().all(|v| v.propose_weight == 0) {
+            return Err(GenesisError::ZeroWeight("propose_weight").into());
+        }
+        if validators.iter().all(|v| v.vote_weight == 0) {
+            return Err(GenesisError::ZeroWeight("vote_weight").into());
+        }
+
+        // The remaining supply of every registered asset.
+        let mut remains = HashMap::new();
+        for (nonce, asset) in self.all_assets().enumerate() {
+            let asset_id = Asset::gen_id(&chain_id, &asset.manage_contract(nonce as u64)?);
+            if remains
+                .insert(asset_id.clone(), BigUint::from(asset.supply))
+                .is_some()
+            {
+                return Err(GenesisError::DuplicateAsset {
+                    asset_id: asset_id.as_hex(),
+                }
+                .into());
+            }
+        }
+
+        let mut alloc_addresses = HashSet::with_capacity(self.state_alloc.len());
+        for alloc in self.state_alloc.iter() {
+            let address = alloc.address()?;
+            if !alloc_addresses.insert(address) {
+                return Err(GenesisError::DuplicateAlloc {
+                    address: alloc.address.clone(),
+                }
+                .into());
+            }
+
+            let mut asset_ids = HashSet::with_capacity(alloc.assets.len());
+            for alloc_asset in alloc.assets.iter() {
+                let asset_id = alloc_asset.asset_id()?;
+                if !asset_ids.insert(asset_id.clone()) {
+                    return Err(GenesisError::DuplicateAllocAsset {
+                        address:  alloc.address.clone(),
+                        asset_id: alloc_asset.asset_id.clone(),
+                    }
+                    .into());
+                }
+
+                let balance = alloc_asset.balance()?;
+                let remain =
+                    remains
+                        .get_mut(&asset_id)
+                        .ok_or_else(|| GenesisError::UnknownAsset {
+                            address:  alloc.address.clone(),
+                            asset_id: alloc_asset.asset_id.clone(),
+                        })?;
+                if *remain < balance {
+                    return Err(GenesisError::SupplyExceeded {
+                        asset_id: alloc_asset.asset_id.clone(),
+                    }
+                    .into());
+                }
+                *remain -= balance;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build the genesis epoch on the state root created from the genesis. The
+    /// epoch only depends on the genesis, so every node gets the same one.
+    pub fn build_epoch(&self, state_root: MerkleRoot) -> ProtocolResult<Epoch> {
+        let validators = self.validators()?;
+        let proposer = validators
+            .first()
+            .map(|v| v.address.clone())
+            .ok_or(GenesisError::EmptyValidators)?;
+
+        let header = EpochHeader {
+            chain_id: self.chain_id()?,
+            epoch_id: GENESIS_EPOCH_ID,
+            pre_hash: Hash::from_hex(&self.prevhash)?,
+            timestamp: self.timestamp,
+            logs_bloom: Bloom::default(),
+            order_root: Hash::from_empty(),
+            confirm_root: vec![],
+            exec_epoch_id: 0,
+            state_root,
+            receipt_root: vec![Hash::from_empty()],
+            cycles_used: 0,
+            proposer,
+            proof: Proof {
+                epoch_id:   GENESIS_EPOCH_ID,
+                round:      0,
+                epoch_hash: Hash::from_empty(),
+                signature:  Bytes::new(),
+                bitmap:     Bytes::new(),
+            },
+            validator_version: 0,
+            validators,
+        };
+
+        Ok(Epoch {
+            header,
+            ordered_tx_hashes: vec![],
+        })
+    }
+}
+
+impl GenesisStateAlloc {
+    pub fn address(&self) -> ProtocolResult<Address> {
+        Address::from_hex(&self.address).map_err(|_| {
+            GenesisError::InvalidAddress {
+                field:   "state_alloc",
+                address: self.address.clone(),
+            }
+            .into()
+        })
+    }
+}
+
+impl GenesisStateAsset {
+    pub fn asset_id(&self) -> ProtocolResult<AssetID> {
+        Hash::from_hex(&self.asset_id).map_err(|_| {
+            GenesisError::InvalidHash {
+                field: "asset_id",
+                value: self.asset_id.clone(),
+            }
+            .into()
+        })
+    }
+
+    pub fn balance(&self) -> ProtocolResult<Balance> {
+        let balance = if self.balance.starts_with("0x") {
+            BigUint::parse_bytes(self.balance[2..].as_bytes(), 16)
+        } else {
+            BigUint::parse_bytes(self.balance.as_bytes(), 10)
+        };
+
+        balance.ok_or_else(|| {
+            GenesisError::InvalidBalance {
+                balance: self.balance.clone(),
+            }
+            .into()
+        })
+    }
+}
+
+impl GenesisAsset {
+    /// The manage contract of a genesis asset is created from its code, with
+    /// the position of the asset in genesis as the nonce.
+    pub fn manage_contract(&self, nonce: u64) -> ProtocolResult<ContractAddress> {
+        let code = hex::decode(&self.code).map_err(|_| GenesisError::InvalidCode {
+            symbol: self.symbol.clone(),
+        })?;
+
+        ContractAddress::from_code(Bytes::from(code), nonce, ContractType::Asset)
+    }
+}
+
+#[derive(Debug, Display)]
+pub enum GenesisError {
+    #[display(fmt = "genesis {} {:?} is not a valid hash", field, value)]
+    InvalidHash { field: &'static str, value: String },
+
+    #[display(fmt = "genesis {} {:?} is not a valid address", field, address)]
+    InvalidAddress {
+        field:   &'static str,
+        address: String,
+    },
+
+    #[display(fmt = "genesis consensus is invalid: {}", _0)]
+    InvalidConsensus(&'static str),
+
+    #[display(fmt = "genesis has no validator")]
+    EmptyValidators,
+
+    #[display(fmt = "genesis validator {} has invalid BLS public key", address)]
+    InvalidBlsPubKey { address: String },
+
+    #[display(
+        fmt = "genesis validator {} has invalid BLS proof of possession",
+        address
+    )]
+    InvalidBlsPop { address: String },
+
+    #[display(fmt = "genesis validator {} is duplicated", address)]
+    DuplicateValidator { address: String },
+
+    #[display(fmt = "genesis validators have zero total {}", _0)]
+    ZeroWeight(&'static str),
+
+    #[display(fmt = "genesis asset {} has invalid hex code", symbol)]
+    InvalidCode { symbol: String },
+
+    #[display(fmt = "genesis asset {} is registered twice", asset_id)]
+    DuplicateAsset { asset_id: String },
+
+    #[display(fmt = "genesis allocation to {} is duplicated", address)]
+    DuplicateAlloc { address: String },
+
+    #[display(
+        fmt = "genesis allocation to {} has duplicated asset {}",
+        address,
+        asset_id
+    )]
+    DuplicateAllocAsset { address: String, asset_id: String },
+
+    #[display(
+        fmt = "genesis allocation to {} refers to unregistered asset {}",
+        address,
+        asset_id
+    )]
+    UnknownAsset { address: String, asset_id: String },
+
+    #[display(
+        fmt = "genesis balance {:?} is neither a decimal nor a 0x-prefixed hex",
+        balance
+    )]
+    InvalidBalance { balance: String },
+
+    #[display(fmt = "genesis allocations of asset {} exceed its supply", asset_id)]
+    SupplyExceeded { asset_id: String },
+
+    #[display(
+        fmt = "genesis hash {} mismatches the stored genesis hash {}",
+        genesis,
+        stored
+    )]
+    HashMismatch { genesis: String, stored: String },
+}
+
+impl Error for GenesisError {}
+
+impl From<GenesisError> for ProtocolError {
+    fn from(error: GenesisError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Types, Box::new(error))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Genesis;
+    use std::convert::TryFrom;
 
-    #[test]
-    fn test_name() {
-        let genesis_string = r#"{
+    use num_bigint::BigUint;
+
+    use common_crypto::{BLS12381PrivateKey, Crypto, PrivateKey, PublicKey, Signature, BLS12381};
+
+    use crate::fixed_codec::ProtocolFixedCodec;
+    use crate::types::{Hash, MerkleRoot, Validator};
+    use crate::{ProtocolError, ProtocolResult};
+
+    use super::{Genesis, GenesisError, GenesisStateAsset};
+
+    const GENESIS: &str = r#"{
+            "chain_id": "b6a4d7da21443f5e816e8700eea87610e6d769657d6b8ec73028457bf2ca4036",
             "timestamp": 100000,
-            "prevhash": "0x0000000000",
+            "prevhash": "44915be5b6c20b0678cf05fcddbbaa832e25d7e6ac538784cd5c24de00d47472",
+            "consensus": {
+                "cycles_limit": 99999999,
+                "interval": 3000,
+                "reward": {
+                    "issuance": 100,
+                    "proposer_ratio": 40
+                }
+            },
+            "validators": [
+                {
+                    "address": "10f8389d774afdad8755ef8e629e5a154fddc6325a",
+                    "bls_pub_key": "",
+                    "bls_pop": "",
+                    "propose_weight": 1,
+                    "vote_weight": 1
+                }
+            ],
             "system_token": {
                 "code": "",
                 "name": "System token",
                 "symbol": "ST",
                 "supply": 21000000000
             },
+            "assets": [
+                {
+                    "code": "01",
+                    "name": "Other token",
+                    "symbol": "OT",
+                    "supply": 1000
+                }
+            ],
             "state_alloc": [
                 {
-                    "address": "0xfffff",
+                    "address": "10f8389d774afdad8755ef8e629e5a154fddc6325a",
+                    "assets": [{
+                        "asset_id": "fee0decb4f6a76d402f200b5642a9236ba455c22aa80ef82d69fc70ea5ba20b5",
+                        "balance": "0x3b9aca00"
+                    }]
+                },
+                {
+                    "address": "103e9b982b443592ffc3d4c2a484c220fb3e29e2e4",
                     "assets": [{
-                        "asset_id": "0xff",
-                        "balance": "0xfff"
+                        "asset_id": "fee0decb4f6a76d402f200b5642a9236ba455c22aa80ef82d69fc70ea5ba20b5",
+                        "balance": "0x100"
                     }]
                 }
             ]
         }"#;
 
-        let _: Genesis = serde_json::from_str(genesis_string).unwrap();
+    // The BLS key of the validator is generated, so that it has a valid proof
+    // of possession.
+    fn mock_genesis() -> Genesis {
+        let mut genesis: Genesis = serde_json::from_str(GENESIS).unwrap();
+
+        let mut key = [0u8; 48];
+        key[47] = 1;
+        let key = BLS12381PrivateKey::try_from(key.as_ref()).unwrap();
+        let bls_pub_key = key.pub_key().to_bytes();
+        let message = Validator::bls_pop_message(&bls_pub_key).as_bytes();
+        let bls_pop = BLS12381::sign_message(&message, &key.to_bytes())
+            .unwrap()
+            .to_bytes();
+
+        genesis.validators[0].bls_pub_key = hex::encode(bls_pub_key);
+        genesis.validators[0].bls_pop = hex::encode(bls_pop);
+        genesis
+    }
+
+    #[test]
+    fn test_name() {
+        let _: Genesis = serde_json::from_str(GENESIS).unwrap();
+    }
+
+    #[test]
+    fn test_build_epoch() {
+        let genesis = mock_genesis();
+        let state_root = MerkleRoot::from_empty();
+
+        let epoch = genesis.build_epoch(state_root.clone()).unwrap();
+        assert_eq!(epoch.header.chain_id, genesis.chain_id().unwrap());
+        assert_eq!(epoch.header.validators, genesis.validators().unwrap());
+        assert_eq!(epoch.header.proposer, epoch.header.validators[0].address);
+
+        let other = genesis.build_epoch(state_root).unwrap();
+        assert_eq!(
+            Hash::digest(epoch.encode_fixed().unwrap()),
+            Hash::digest(other.encode_fixed().unwrap())
+        );
+    }
+
+    fn assert_genesis_err<T: std::fmt::Debug>(result: ProtocolResult<T>, expect: GenesisError) {
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            ProtocolError::from(expect).to_string()
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let genesis = mock_genesis();
+        genesis.validate().unwrap();
+
+        let mut invalid = genesis.clone();
+        invalid.consensus.reward.proposer_ratio = 101;
+        assert_genesis_err(
+            invalid.validate(),
+            GenesisError::InvalidConsensus("proposer_ratio exceeds 100"),
+        );
+
+        let mut invalid = genesis.clone();
+        invalid.validators.clear();
+        assert_genesis_err(invalid.validate(), GenesisError::EmptyValidators);
+
+        let mut invalid = genesis.clone();
+        invalid.validators.push(genesis.validators[0].clone());
+        assert_genesis_err(invalid.validate(), GenesisError::DuplicateValidator {
+            address: genesis.validators().unwrap()[0].address.as_hex(),
+        });
+
+        let mut invalid = genesis.clone();
+        invalid.validators[0].bls_pub_key = String::new();
+        assert_genesis_err(invalid.validate(), GenesisError::InvalidBlsPubKey {
+            address: genesis.validators[0].address.clone(),
+        });
+
+        let mut invalid = genesis.clone();
+        invalid.validators[0].bls_pop = genesis.validators[0].bls_pub_key.clone();
+        assert_genesis_err(invalid.validate(), GenesisError::InvalidBlsPop {
+            address: genesis.validators[0].address.clone(),
+        });
+
+        let mut invalid = genesis.clone();
+        invalid.state_alloc[1].address = "0xfffff".to_owned();
+        assert_genesis_err(invalid.validate(), GenesisError::InvalidAddress {
+            field:   "state_alloc",
+            address: "0xfffff".to_owned(),
+        });
+
+        let mut invalid = genesis.clone();
+        invalid.state_alloc[1].address = genesis.state_alloc[0].address.clone();
+        assert_genesis_err(invalid.validate(), GenesisError::DuplicateAlloc {
+            address: genesis.state_alloc[0].address.clone(),
+        });
+
+        let mut invalid = genesis.clone();
+        invalid.state_alloc[0].assets[0].asset_id = Hash::from_empty().as_hex();
+        assert_genesis_err(invalid.validate(), GenesisError::UnknownAsset {
+            address:  genesis.state_alloc[0].address.clone(),
+            asset_id: Hash::from_empty().as_hex(),
+        });
+
+        // The supply of the system token is 21000000000.
+        let mut invalid = genesis.clone();
+        invalid.state_alloc[0].assets[0].balance = "0x4e3b29200".to_owned();
+        assert_genesis_err(invalid.validate(), GenesisError::SupplyExceeded {
+            asset_id: genesis.state_alloc[0].assets[0].asset_id.clone(),
+        });
+
+        let mut valid = genesis.clone();
+        valid.state_alloc[0].assets[0].balance = "1000".to_owned();
+        assert!(valid.validate().is_ok());
+
+        let mut invalid = genesis.clone();
+        invalid.state_alloc[0].assets[0].balance = "0x100g".to_owned();
+        assert_genesis_err(invalid.validate(), GenesisError::InvalidBalance {
+            balance: "0x100g".to_owned(),
+        });
+    }
+
+    #[test]
+    fn test_balance() {
+        let balance = |s: &str| {
+            GenesisStateAsset {
+                asset_id: String::new(),
+                balance:  s.to_owned(),
+            }
+            .balance()
+        };
+
+        assert_eq!(balance("0x1000").unwrap(), BigUint::from(4096u64));
+        assert_eq!(balance("1000").unwrap(), BigUint::from(1000u64));
+        assert_genesis_err(balance("1a00"), GenesisError::InvalidBalance {
+            balance: "1a00".to_owned(),
+        });
+        assert!(balance("").is_err());
+        assert!(balance("0x").is_err());
+        assert!(balance("1000x").is_err());
     }
 }
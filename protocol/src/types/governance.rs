@@ -0,0 +1,21 @@
+use crate::types::{Hash, UserAddress, Validator};
+
+/// A validator set recorded by the governance contract. It takes effect from
+/// `activate_epoch_id`, and `version` is increased by one for every change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorSet {
+    pub version:           u64,
+    pub activate_epoch_id: u64,
+    pub validators:        Vec<Validator>,
+}
+
+/// A pending validator set change. It is scheduled once the approvals reach a
+/// quorum of the validator set with `base_version`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorProposal {
+    pub id:            Hash,
+    pub proposer:      UserAddress,
+    pub base_version:  u64,
+    pub validator_set: ValidatorSet,
+    pub approvals:     Vec<UserAddress>,
+}
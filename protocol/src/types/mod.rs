@@ -1,7 +1,10 @@
 pub(crate) mod epoch;
+pub(crate) mod evidence;
 pub(crate) mod genesis;
+pub(crate) mod governance;
 pub(crate) mod primitive;
 pub(crate) mod receipt;
+pub(crate) mod trace;
 pub(crate) mod transaction;
 
 use std::error::Error;
@@ -12,13 +15,19 @@ use crate::{ProtocolError, ProtocolErrorKind};
 
 pub use epoch::{Epoch, EpochHeader, EpochId, Pill, Proof, Validator};
 pub use ethbloom::{Bloom, BloomRef, Input as BloomInput};
-pub use genesis::{Genesis, GenesisStateAlloc, GenesisStateAsset};
+pub use evidence::{EpochEvidences, Evidence, EvidenceType};
+pub use genesis::{
+    Genesis, GenesisAsset, GenesisConsensus, GenesisError, GenesisStateAlloc, GenesisStateAsset,
+    GenesisValidator,
+};
+pub use governance::{ValidatorProposal, ValidatorSet};
 pub use primitive::{
     Account, Address, ApprovedInfo, Asset, AssetID, AssetInfo, Balance, ContractAccount,
     ContractAddress, ContractType, Fee, Hash, MerkleRoot, UserAccount, UserAddress,
     GENESIS_EPOCH_ID,
 };
-pub use receipt::{Receipt, ReceiptResult};
+pub use receipt::{Receipt, ReceiptResult, Reward};
+pub use trace::{ContractTrace, StateDiff, TransactionTrace};
 pub use transaction::{CarryingAsset, RawTransaction, SignedTransaction, TransactionAction};
 
 #[derive(Debug, Display, From)]
@@ -346,6 +346,15 @@ pub struct Asset {
     pub storage_root:    MerkleRoot,
 }
 
+impl Asset {
+    /// The asset id is determined by the chain and the manage contract.
+    pub fn gen_id(chain_id: &Hash, manage_contract: &ContractAddress) -> AssetID {
+        Hash::digest(Bytes::from(
+            [chain_id.as_bytes(), manage_contract.as_bytes()].concat(),
+        ))
+    }
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Fee {
     pub asset_id: AssetID,
@@ -39,4 +39,14 @@ pub enum ReceiptResult {
         system: String,
         user:   String,
     },
+    Reward {
+        rewards: Vec<Reward>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reward {
+    pub receiver: UserAddress,
+    pub asset_id: AssetID,
+    pub amount:   Balance,
 }
@@ -0,0 +1,27 @@
+use bytes::Bytes;
+
+use crate::types::{Address, Hash};
+
+/// The state changes made by a transaction, grouped by the contracts whose
+/// state were touched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionTrace {
+    pub tx_hash:   Hash,
+    pub epoch_id:  u64,
+    pub contracts: Vec<ContractTrace>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractTrace {
+    pub address: Address,
+    pub diffs:   Vec<StateDiff>,
+}
+
+/// A key of the contract state and its values before and after the
+/// transaction, `before` is `None` if the key was not in the state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateDiff {
+    pub key:    Bytes,
+    pub before: Option<Bytes>,
+    pub after:  Bytes,
+}
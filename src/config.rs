@@ -4,7 +4,10 @@ use std::path::PathBuf;
 use serde_derive::Deserialize;
 
 use core_consensus::DurationConfig;
-use core_mempool::{DEFAULT_BROADCAST_TXS_INTERVAL, DEFAULT_BROADCAST_TXS_SIZE};
+use core_mempool::{
+    DEFAULT_BROADCAST_TXS_INTERVAL, DEFAULT_BROADCAST_TXS_SIZE, DEFAULT_REPLACE_RATIO,
+    DEFAULT_RESERVED_RATIO, DEFAULT_SENDER_LIMIT,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct ConfigGraphQL {
@@ -33,6 +36,18 @@ fn default_broadcast_txs_interval() -> u64 {
     DEFAULT_BROADCAST_TXS_INTERVAL
 }
 
+fn default_reserved_ratio() -> u64 {
+    DEFAULT_RESERVED_RATIO
+}
+
+fn default_replace_ratio() -> u64 {
+    DEFAULT_REPLACE_RATIO
+}
+
+fn default_sender_limit() -> usize {
+    DEFAULT_SENDER_LIMIT
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ConfigMempool {
     pub timeout_gap: u64,
@@ -42,28 +57,38 @@ pub struct ConfigMempool {
     pub broadcast_txs_size: usize,
     #[serde(default = "default_broadcast_txs_interval")]
     pub broadcast_txs_interval: u64,
+    // the percentage of the cycle limit reserved for low fee transactions
+    #[serde(default = "default_reserved_ratio")]
+    pub reserved_ratio: u64,
+    // the percentage by which a replacement must raise the fee of a pending tx
+    #[serde(default = "default_replace_ratio")]
+    pub replace_ratio: u64,
+    // the max number of pending txs of a sender
+    #[serde(default = "default_sender_limit")]
+    pub sender_limit: usize,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ConfigConsensus {
-    pub cycles_limit:  u64,
-    pub cycles_price:  u64,
-    pub interval:      u64,
-    pub duration:      DurationConfig,
-    pub verifier_list: Vec<String>,
+    pub cycles_price: u64,
+    pub duration:     DurationConfig,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ConfigExecutor {
     pub light: bool,
+
+    /// Record the state diffs of every transaction, they can be queried by
+    /// `traceTransaction`.
+    #[serde(default)]
+    pub trace: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
-    // chain id
-    pub chain_id: String,
     // crypto
-    pub privkey: String,
+    pub privkey:     String,
+    pub bls_privkey: String,
     // db config
     pub data_path: PathBuf,
 
@@ -89,9 +114,9 @@ impl Config {
         path_state
     }
 
-    // pub fn data_path_for_bft_wal(&self) -> PathBuf {
-    //     let mut path_state = self.data_path.clone();
-    //     path_state.push("bft_wal");
-    //     path_state
-    // }
+    pub fn data_path_for_bft_wal(&self) -> PathBuf {
+        let mut path_state = self.data_path.clone();
+        path_state.push("bft_wal");
+        path_state
+    }
 }
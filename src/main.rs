@@ -3,17 +3,18 @@
 mod config;
 
 use std::convert::TryFrom;
+use std::error::Error;
 use std::fs::File;
 use std::path::Path;
+use std::process;
 use std::sync::Arc;
 
-use bytes::Bytes;
-
-use common_crypto::{PrivateKey, PublicKey, Secp256k1, Secp256k1PrivateKey};
+use common_crypto::{BLS12381PrivateKey, PrivateKey, PublicKey, Secp256k1, Secp256k1PrivateKey};
 use core_api::adapter::DefaultAPIAdapter;
 use core_api::config::GraphQLConfig;
 use core_consensus::adapter::OverlordConsensusAdapter;
 use core_consensus::consensus::OverlordConsensus;
+use core_consensus::dev::{DevConsensus, DevNetwork};
 use core_consensus::fixed_types::ConsensusRpcResponse;
 use core_consensus::message::{
     ProposalMessageHandler, QCMessageHandler, RichEpochIDMessageHandler, RpcHandler,
@@ -23,16 +24,17 @@ use core_consensus::message::{
 use core_executor::trie::RocksTrieDB;
 use core_executor::TransactionExecutorFactory;
 use core_mempool::{
-    DefaultMemPoolAdapter, HashMemPool, MsgPushTxs, NewTxsHandler, END_GOSSIP_NEW_TXS,
-    END_RESP_PULL_TXS, END_RPC_PULL_TXS,
+    DefaultMemPoolAdapter, HashMemPool, MsgPushTxs, NewTxHashesHandler, PullTxsHandler,
+    END_GOSSIP_NEW_TX_HASHES, END_RESP_PULL_TXS, END_RPC_PULL_TXS,
 };
 use core_network::{NetworkConfig, NetworkService};
 use core_storage::{adapter::rocks::RocksAdapter, ImplStorage};
 
-use protocol::traits::executor::ExecutorFactory;
-use protocol::traits::{CurrentConsensusStatus, NodeInfo, Storage};
+use protocol::traits::executor::{ExecutorFactory, RewardSchedule, TrieDB};
+use protocol::traits::{ConsensusAdapter, Context, CurrentConsensusStatus, NodeInfo, Storage};
 use protocol::types::{
-    Address, Bloom, Epoch, EpochHeader, Genesis, Hash, MerkleRoot, Proof, UserAddress, Validator,
+    Address, Epoch, Genesis, GenesisError, Hash, MerkleRoot, UserAddress, ValidatorSet,
+    GENESIS_EPOCH_ID,
 };
 use protocol::{fixed_codec::ProtocolFixedCodec, ProtocolResult};
 
@@ -53,33 +55,67 @@ async fn main() {
             clap::Arg::from_usage("-g --genesis=[FILE] 'a required file for the genesis json'")
                 .default_value("./devtools/chain/genesis.json"),
         )
+        .arg(clap::Arg::from_usage(
+            "--dev 'run a single node development chain without overlord and network'",
+        ))
+        .arg(
+            clap::Arg::from_usage(
+                "--dev-interval=[MS] 'seal an epoch every MS milliseconds in the development mode, \
+                 seal instantly when transactions arrive if not set'",
+            )
+            .requires("dev"),
+        )
         .get_matches();
     let args_config = matches.value_of("config").unwrap();
     let cfg: Config = common_config_parser::parse(args_config).unwrap();
     log::info!("Go with config: {:?}", cfg);
 
-    // init genesis
+    // Read genesis.
     let genesis_path = matches.value_of("genesis").unwrap();
     log::info!("Genesis path: {}", genesis_path);
-    handle_init(&cfg, genesis_path).await.unwrap();
+    let genesis = match read_genesis(genesis_path) {
+        Ok(genesis) => genesis,
+        Err(e) => {
+            log::error!("Invalid genesis {}: {}", genesis_path, e);
+            process::exit(1);
+        }
+    };
+
+    // init genesis
+    if let Err(e) = handle_init(&cfg, &genesis).await {
+        log::error!("Init genesis failed: {}", e);
+        process::exit(1);
+    }
 
-    start(&cfg).await.unwrap();
+    if matches.is_present("dev") {
+        let interval = matches.value_of("dev-interval").map(|interval| {
+            interval.parse::<u64>().unwrap_or_else(|e| {
+                log::error!("Invalid dev interval {}: {}", interval, e);
+                process::exit(1);
+            })
+        });
+        start_dev(&cfg, &genesis, interval).await.unwrap();
+    } else {
+        start(&cfg, &genesis).await.unwrap();
+    }
 }
 
-async fn handle_init(cfg: &Config, genesis_path: impl AsRef<Path>) -> ProtocolResult<()> {
-    let chain_id = Hash::from_hex(&cfg.chain_id).unwrap();
+fn read_genesis(genesis_path: impl AsRef<Path>) -> Result<Genesis, Box<dyn Error>> {
+    let mut r = File::open(genesis_path)?;
+    let genesis: Genesis = serde_json::from_reader(&mut r)?;
+    log::info!("Genesis data: {:?}", genesis);
 
+    genesis.validate()?;
+    Ok(genesis)
+}
+
+async fn handle_init(cfg: &Config, genesis: &Genesis) -> ProtocolResult<()> {
     // self private key
     let my_privkey =
         Secp256k1PrivateKey::try_from(hex::decode(cfg.privkey.clone()).unwrap().as_ref()).unwrap();
     let my_pubkey = my_privkey.pub_key();
     let my_address = UserAddress::from_pubkey_bytes(my_pubkey.to_bytes()).unwrap();
 
-    // Read genesis.
-    let mut r = File::open(genesis_path).unwrap();
-    let genesis: Genesis = serde_json::from_reader(&mut r).unwrap();
-    log::info!("Genesis data: {:?}", genesis);
-
     // Init Block db
     let path_block = cfg.data_path_for_block();
     log::info!("Data path for block: {:?}", path_block);
@@ -88,6 +124,28 @@ async fn handle_init(cfg: &Config, genesis_path: impl AsRef<Path>) -> ProtocolRe
 
     match storage.get_latest_epoch().await {
         Ok(_) => {
+            // The genesis state is rebuilt on an in-memory trie, so the whole
+            // genesis epoch, its state root included, is compared with the stored
+            // one without touching the state of the running chain.
+            let stored_epoch = storage.get_epoch_by_epoch_id(GENESIS_EPOCH_ID).await?;
+            let stored_hash = Hash::digest(stored_epoch.encode_fixed()?);
+            let genesis_epoch = build_genesis_epoch(
+                cfg,
+                genesis,
+                Arc::new(cita_trie::MemoryDB::new(false)),
+                Address::User(my_address),
+            )?;
+            let genesis_hash = Hash::digest(genesis_epoch.encode_fixed()?);
+            log::info!("Genesis hash: {}", genesis_hash.as_hex());
+
+            if stored_hash != genesis_hash {
+                return Err(GenesisError::HashMismatch {
+                    genesis: genesis_hash.as_hex(),
+                    stored:  stored_hash.as_hex(),
+                }
+                .into());
+            }
+
             log::info!("The Genesis block has been initialized.");
             return Ok(());
         }
@@ -102,58 +160,41 @@ async fn handle_init(cfg: &Config, genesis_path: impl AsRef<Path>) -> ProtocolRe
     let path_state = cfg.data_path_for_state();
     let trie_db = Arc::new(RocksTrieDB::new(path_state, cfg.executor.light).unwrap());
 
-    // Init genesis
-    let genesis_state_root = {
-        let mut executor = TransactionExecutorFactory::from_root(
-            chain_id.clone(),
-            MerkleRoot::from_empty(),
-            Arc::clone(&trie_db),
-            0,
-            cfg.consensus.cycles_price,
-            Address::User(my_address),
-        )?;
-
-        executor.create_genesis(&genesis)?
-    };
-
     // Build genesis block.
-    let genesis_epoch_header = EpochHeader {
-        chain_id:          chain_id.clone(),
-        epoch_id:          0,
-        pre_hash:          Hash::from_empty(),
-        timestamp:         genesis.timestamp,
-        logs_bloom:        Bloom::default(),
-        order_root:        Hash::from_empty(),
-        confirm_root:      vec![],
-        state_root:        genesis_state_root,
-        receipt_root:      vec![Hash::from_empty()],
-        cycles_used:       0,
-        proposer:          UserAddress::from_hex("100000000000000000000000000000000000000000")
-            .unwrap(),
-        proof:             Proof {
-            epoch_id:   0,
-            round:      0,
-            epoch_hash: Hash::from_empty(),
-            signature:  Bytes::new(),
-            bitmap:     Bytes::new(),
-        },
-        validator_version: 0,
-        validators:        vec![],
-    };
-    let latest_proof = genesis_epoch_header.proof.clone();
-    storage
-        .insert_epoch(Epoch {
-            header:            genesis_epoch_header,
-            ordered_tx_hashes: vec![],
-        })
-        .await
-        .unwrap();
+    let genesis_epoch = build_genesis_epoch(cfg, genesis, trie_db, Address::User(my_address))?;
+    let genesis_hash = Hash::digest(genesis_epoch.encode_fixed()?);
+    log::info!("Genesis hash: {}", genesis_hash.as_hex());
+
+    let latest_proof = genesis_epoch.header.proof.clone();
+    storage.insert_epoch(genesis_epoch).await.unwrap();
     storage.update_latest_proof(latest_proof).await.unwrap();
     Ok(())
 }
 
-async fn start(cfg: &Config) -> ProtocolResult<()> {
-    let chain_id = Hash::from_hex(&cfg.chain_id).unwrap();
+// Create the genesis state on the trie db and build the genesis epoch on it.
+// The state of the genesis is always created from an empty root, so it doesn't
+// touch the state of a running chain.
+fn build_genesis_epoch<DB: 'static + TrieDB>(
+    cfg: &Config,
+    genesis: &Genesis,
+    trie_db: Arc<DB>,
+    coinbase: Address,
+) -> ProtocolResult<Epoch> {
+    let mut executor = TransactionExecutorFactory::from_root(
+        genesis.chain_id()?,
+        MerkleRoot::from_empty(),
+        trie_db,
+        0,
+        cfg.consensus.cycles_price,
+        coinbase,
+    )?;
+    let genesis_state_root = executor.create_genesis(genesis)?;
+
+    genesis.build_epoch(genesis_state_root)
+}
+
+async fn start(cfg: &Config, genesis: &Genesis) -> ProtocolResult<()> {
+    let chain_id = genesis.chain_id()?;
 
     // self private key
     let my_privkey =
@@ -161,6 +202,11 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
     let my_pubkey = my_privkey.pub_key();
     let my_address = UserAddress::from_pubkey_bytes(my_pubkey.to_bytes()).unwrap();
 
+    // self BLS private key to sign consensus votes
+    let my_bls_privkey =
+        BLS12381PrivateKey::try_from(hex::decode(cfg.bls_privkey.clone()).unwrap().as_ref())
+            .unwrap();
+
     // Init Block db
     let path_block = cfg.data_path_for_block();
     log::info!("Data path for block: {:?}", path_block);
@@ -188,26 +234,39 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
         .listen(cfg.network.listening_address)
         .unwrap();
 
+    // Init trie db
+    let path_state = cfg.data_path_for_state();
+    let trie_db = Arc::new(RocksTrieDB::new(path_state, cfg.executor.light).unwrap());
+
     // Init mempool
     let current_epoch = storage.get_latest_epoch().await.unwrap();
-    let mempool_adapter = DefaultMemPoolAdapter::<Secp256k1, _, _>::new(
-        network_service.handle(),
-        Arc::clone(&storage),
-        cfg.mempool.timeout_gap,
-        cfg.mempool.broadcast_txs_size,
-        cfg.mempool.broadcast_txs_interval,
-    );
+    let mempool_adapter =
+        DefaultMemPoolAdapter::<TransactionExecutorFactory, Secp256k1, _, _, _>::new(
+            network_service.handle(),
+            Arc::clone(&storage),
+            Arc::clone(&trie_db),
+            cfg.mempool.timeout_gap,
+            cfg.consensus.cycles_price,
+            genesis.consensus.cycles_limit,
+            cfg.mempool.broadcast_txs_size,
+            cfg.mempool.broadcast_txs_interval,
+        );
     let mempool = Arc::new(HashMemPool::new(
         cfg.mempool.pool_size as usize,
         cfg.mempool.timeout_gap,
+        cfg.mempool.reserved_ratio,
+        cfg.mempool.replace_ratio,
+        cfg.mempool.sender_limit,
         mempool_adapter,
     ));
+    // Reload the pending transactions journaled before restart
+    mempool.load_journal(Context::new()).await.unwrap();
 
     // register broadcast new transaction
     network_service
         .register_endpoint_handler(
-            END_GOSSIP_NEW_TXS,
-            Box::new(NewTxsHandler::new(Arc::clone(&mempool))),
+            END_GOSSIP_NEW_TX_HASHES,
+            Box::new(NewTxHashesHandler::new(Arc::clone(&mempool))),
         )
         .unwrap();
 
@@ -215,7 +274,10 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
     network_service
         .register_endpoint_handler(
             END_RPC_PULL_TXS,
-            Box::new(NewTxsHandler::new(Arc::clone(&mempool))),
+            Box::new(PullTxsHandler::new(
+                network_service.handle(),
+                Arc::clone(&mempool),
+            )),
         )
         .unwrap();
 
@@ -223,10 +285,6 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
         .register_rpc_response::<MsgPushTxs>(END_RESP_PULL_TXS)
         .unwrap();
 
-    // Init trie db
-    let path_state = cfg.data_path_for_state();
-    let trie_db = Arc::new(RocksTrieDB::new(path_state, cfg.executor.light).unwrap());
-
     // Init Consensus
     let consensus_adapter = Arc::new(OverlordConsensusAdapter::<
         TransactionExecutorFactory,
@@ -241,46 +299,29 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
         Arc::clone(&mempool),
         Arc::clone(&storage),
         Arc::clone(&trie_db),
+        cfg.executor.trace,
     ));
 
     let node_info = NodeInfo {
         chain_id:     chain_id.clone(),
         self_address: my_address.clone(),
     };
-    let current_header = &current_epoch.header;
-    let prevhash = Hash::digest(current_epoch.encode_fixed()?);
-
-    let current_consensus_status = CurrentConsensusStatus {
-        cycles_price:       cfg.consensus.cycles_price,
-        cycles_limit:       cfg.consensus.cycles_limit,
-        epoch_id:           current_epoch.header.epoch_id + 1,
-        prev_hash:          prevhash,
-        logs_bloom:         current_header.logs_bloom,
-        order_root:         Hash::from_empty(),
-        confirm_root:       vec![Hash::from_empty()],
-        state_root:         current_header.state_root.clone(),
-        receipt_root:       vec![Hash::from_empty()],
-        cycles_used:        current_header.cycles_used,
-        proof:              current_header.proof.clone(),
-        validators:         cfg
-            .consensus
-            .verifier_list
-            .iter()
-            .map(|v| Validator {
-                address:        UserAddress::from_hex(v).unwrap(),
-                propose_weight: 1,
-                vote_weight:    1,
-            })
-            .collect(),
-        consensus_interval: cfg.consensus.interval,
-    };
+    let current_consensus_status = current_consensus_status(
+        cfg,
+        genesis,
+        &node_info,
+        consensus_adapter.as_ref(),
+        &current_epoch,
+    )
+    .await?;
 
     let overlord_consensus = Arc::new(OverlordConsensus::new(
         current_consensus_status,
         node_info,
-        my_privkey,
+        my_bls_privkey,
+        cfg.data_path_for_bft_wal(),
         consensus_adapter,
-    ));
+    )?);
 
     // register consensus
     network_service
@@ -326,10 +367,11 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
     runtime::spawn(network_service);
 
     // Init graphql
-    let api_adapter = DefaultAPIAdapter::<TransactionExecutorFactory, _, _, _>::new(
+    let api_adapter = DefaultAPIAdapter::<TransactionExecutorFactory, _, _, _, _>::new(
         Arc::clone(&mempool),
         Arc::clone(&storage),
         Arc::clone(&trie_db),
+        Arc::clone(&overlord_consensus),
     );
     let mut graphql_config = GraphQLConfig::default();
     graphql_config.listening_address = cfg.graphql.listening_address;
@@ -341,9 +383,158 @@ async fn start(cfg: &Config) -> ProtocolResult<()> {
 
     // Run consensus
     overlord_consensus
-        .run(cfg.consensus.interval, Some(cfg.consensus.duration.clone()))
+        .run(
+            genesis.consensus.interval,
+            Some(cfg.consensus.duration.clone()),
+        )
         .await
         .unwrap();
 
     Ok(())
 }
+
+async fn start_dev(cfg: &Config, genesis: &Genesis, interval: Option<u64>) -> ProtocolResult<()> {
+    let chain_id = genesis.chain_id()?;
+
+    // self private key, the node seals all the epochs.
+    let my_privkey =
+        Secp256k1PrivateKey::try_from(hex::decode(cfg.privkey.clone()).unwrap().as_ref()).unwrap();
+    let my_pubkey = my_privkey.pub_key();
+    let my_address = UserAddress::from_pubkey_bytes(my_pubkey.to_bytes()).unwrap();
+
+    // Init Block db
+    let path_block = cfg.data_path_for_block();
+    log::info!("Data path for block: {:?}", path_block);
+    let rocks_adapter = Arc::new(RocksAdapter::new(path_block).unwrap());
+    let storage = Arc::new(ImplStorage::new(Arc::clone(&rocks_adapter)));
+
+    // Init trie db
+    let path_state = cfg.data_path_for_state();
+    let trie_db = Arc::new(RocksTrieDB::new(path_state, cfg.executor.light).unwrap());
+
+    // Init mempool, there are no peers to broadcast transactions to.
+    let current_epoch = storage.get_latest_epoch().await.unwrap();
+    let mempool_adapter =
+        DefaultMemPoolAdapter::<TransactionExecutorFactory, Secp256k1, _, _, _>::new(
+            DevNetwork,
+            Arc::clone(&storage),
+            Arc::clone(&trie_db),
+            cfg.mempool.timeout_gap,
+            cfg.consensus.cycles_price,
+            genesis.consensus.cycles_limit,
+            cfg.mempool.broadcast_txs_size,
+            cfg.mempool.broadcast_txs_interval,
+        );
+    let mempool = Arc::new(HashMemPool::new(
+        cfg.mempool.pool_size as usize,
+        cfg.mempool.timeout_gap,
+        cfg.mempool.reserved_ratio,
+        cfg.mempool.replace_ratio,
+        cfg.mempool.sender_limit,
+        mempool_adapter,
+    ));
+    // Reload the pending transactions journaled before restart
+    mempool.load_journal(Context::new()).await.unwrap();
+
+    // Init Consensus
+    let consensus_adapter = Arc::new(OverlordConsensusAdapter::<
+        TransactionExecutorFactory,
+        _,
+        _,
+        _,
+        _,
+        _,
+    >::new(
+        Arc::new(DevNetwork),
+        Arc::new(DevNetwork),
+        Arc::clone(&mempool),
+        Arc::clone(&storage),
+        Arc::clone(&trie_db),
+        cfg.executor.trace,
+    ));
+
+    let node_info = NodeInfo {
+        chain_id:     chain_id.clone(),
+        self_address: my_address.clone(),
+    };
+    let current_consensus_status = current_consensus_status(
+        cfg,
+        genesis,
+        &node_info,
+        consensus_adapter.as_ref(),
+        &current_epoch,
+    )
+    .await?;
+    let dev_consensus = Arc::new(DevConsensus::new(
+        current_consensus_status,
+        node_info,
+        consensus_adapter,
+        interval,
+    ));
+
+    // Init graphql
+    let api_adapter = DefaultAPIAdapter::<TransactionExecutorFactory, _, _, _, _>::new(
+        Arc::clone(&mempool),
+        Arc::clone(&storage),
+        Arc::clone(&trie_db),
+        Arc::clone(&dev_consensus),
+    );
+    let mut graphql_config = GraphQLConfig::default();
+    graphql_config.listening_address = cfg.graphql.listening_address;
+    graphql_config.graphql_uri = cfg.graphql.graphql_uri.clone();
+    graphql_config.graphiql_uri = cfg.graphql.graphiql_uri.clone();
+
+    // Run GraphQL server
+    runtime::spawn(core_api::start_graphql(graphql_config, api_adapter));
+
+    // Run the development consensus
+    dev_consensus.run().await
+}
+
+async fn current_consensus_status<A: ConsensusAdapter>(
+    cfg: &Config,
+    genesis: &Genesis,
+    node_info: &NodeInfo,
+    consensus_adapter: &A,
+    current_epoch: &Epoch,
+) -> ProtocolResult<CurrentConsensusStatus> {
+    let current_header = &current_epoch.header;
+    let prevhash = Hash::digest(current_epoch.encode_fixed()?);
+
+    // Use the validators of the governance contract if it has been initialized,
+    // otherwise use the validators of the genesis.
+    let validator_set = match consensus_adapter
+        .get_last_validators(
+            node_info.clone(),
+            current_header.state_root.clone(),
+            current_header.epoch_id + 1,
+        )
+        .await?
+    {
+        Some(validator_set) => validator_set,
+        None => ValidatorSet {
+            version:           0,
+            activate_epoch_id: 0,
+            validators:        genesis.validators()?,
+        },
+    };
+
+    Ok(CurrentConsensusStatus {
+        cycles_price:       cfg.consensus.cycles_price,
+        cycles_limit:       genesis.consensus.cycles_limit,
+        epoch_id:           current_epoch.header.epoch_id + 1,
+        prev_hash:          prevhash,
+        logs_bloom:         current_header.logs_bloom,
+        exec_epoch_id:      current_header.exec_epoch_id,
+        state_root:         current_header.state_root.clone(),
+        cycles_used:        current_header.cycles_used,
+        proof:              current_header.proof.clone(),
+        validators:         validator_set.validators,
+        validator_version:  validator_set.version,
+        reward_schedule:    RewardSchedule {
+            issuance:       genesis.consensus.reward.issuance,
+            proposer_ratio: genesis.consensus.reward.proposer_ratio,
+        },
+        consensus_interval: genesis.consensus.interval,
+    })
+}